@@ -0,0 +1,1228 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{
+    charges_transfer_fee, check_deadline, check_pool_mint_extensions, fee_on, mul_div_ceil,
+    mul_div_floor, PlatformConfig, PoolError, ProtocolFeesCollected,
+};
+
+// Concentrated-liquidity pools. Prices are Q64.64 square roots of token B per
+// token A; liquidity is provided in ranges of ticks, where tick `i` sits at
+// price 1.0001^i. Initialized ticks live in zero-copy tick arrays of
+// TICK_ARRAY_SIZE spaced ticks, and positions are PDAs per owner and range.
+
+pub const TICK_ARRAY_SIZE: usize = 64;
+// Keeps square-root prices within [2^-32, 2^32] so they fit Q64.64
+pub const MIN_TICK: i32 = -443_636;
+pub const MAX_TICK: i32 = 443_636;
+pub const MAX_TICK_SPACING: u16 = 16_384;
+
+const Q64: u128 = 1 << 64;
+
+// sqrt(1.0001)^(2^i) in Q64.64, for each bit of a tick index
+const SQRT_PRICE_POWERS: [u128; 19] = [
+    0x1000346d6ff11672b,
+    0x100068db8bac710cb,
+    0x1000d1b9c68abe5f7,
+    0x1001a37e4a234cb08,
+    0x100347278ab0e92ae,
+    0x10068efb00a525481,
+    0x100d20a63b417383a,
+    0x101a4c11c742dd773,
+    0x1034c35c31f64cfa7,
+    0x106a34b78c8aaffc0,
+    0x10d72a6a46ccd8bcf,
+    0x11b9a258e63928597,
+    0x13a2e2bda04f8379f,
+    0x181954be69e0da8fe,
+    0x244c2655d185a0291,
+    0x525816eeb9f935b1c,
+    0x1a7c8d00b551684ff5,
+    0x2bd893d0b2df7c97884,
+    0x78278e1e19e448cf8b95d,
+];
+
+#[derive(Accounts)]
+#[instruction(tick_spacing: u16)]
+pub struct InitializeClPool<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ClPoolState::INIT_SPACE,
+        seeds = [b"cl_pool", token_a.key().as_ref(), token_b.key().as_ref(), &tick_spacing.to_le_bytes()],
+        bump,
+    )]
+    pub pool: Box<Account<'info, ClPoolState>>,
+    #[account(mint::token_program = token_a_program)]
+    pub token_a: Box<InterfaceAccount<'info, Mint>>,
+    // Mints are passed sorted so each pair and spacing maps to a single pool address
+    #[account(
+        mint::token_program = token_b_program,
+        constraint = token_a.key() < token_b.key() @ PoolError::InvalidMint,
+    )]
+    pub token_b: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: PDA that owns the reserves
+    #[account(seeds = [b"pool_authority", pool.key().as_ref()], bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"reserve_a", pool.key().as_ref()],
+        bump,
+        token::mint = token_a,
+        token::authority = pool_authority,
+        token::token_program = token_a_program,
+    )]
+    pub token_a_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"reserve_b", pool.key().as_ref()],
+        bump,
+        token::mint = token_b,
+        token::authority = pool_authority,
+        token::token_program = token_b_program,
+    )]
+    pub token_b_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+    pub pool: Account<'info, ClPoolState>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<TickArray>(),
+        seeds = [b"tick_array", pool.key().as_ref(), &start_tick_index.to_le_bytes()],
+        bump,
+    )]
+    pub tick_array: AccountLoader<'info, TickArray>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenClPosition<'info> {
+    pub pool: Account<'info, ClPoolState>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + ClPosition::INIT_SPACE,
+        seeds = [
+            b"position",
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+            &tick_lower.to_le_bytes(),
+            &tick_upper.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub position: Account<'info, ClPosition>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Shared by increasing, decreasing and collecting from a position
+#[derive(Accounts)]
+pub struct ModifyClPosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"cl_pool", pool.token_a.as_ref(), pool.token_b.as_ref(), &pool.tick_spacing.to_le_bytes()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, ClPoolState>>,
+    #[account(mut, has_one = pool, has_one = owner @ PoolError::NotPositionOwner)]
+    pub position: Box<Account<'info, ClPosition>>,
+    #[account(
+        mut,
+        seeds = [
+            b"tick_array",
+            pool.key().as_ref(),
+            &tick_array_start(position.tick_lower, pool.tick_spacing).to_le_bytes(),
+        ],
+        bump = tick_array_lower.load()?.bump,
+    )]
+    pub tick_array_lower: AccountLoader<'info, TickArray>,
+    #[account(
+        mut,
+        seeds = [
+            b"tick_array",
+            pool.key().as_ref(),
+            &tick_array_start(position.tick_upper, pool.tick_spacing).to_le_bytes(),
+        ],
+        bump = tick_array_upper.load()?.bump,
+    )]
+    pub tick_array_upper: AccountLoader<'info, TickArray>,
+    #[account(address = pool.token_a, mint::token_program = token_a_program)]
+    pub token_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool.token_b, mint::token_program = token_b_program)]
+    pub token_b: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: PDA that owns the reserves
+    #[account(seeds = [b"pool_authority", pool.key().as_ref()], bump = pool.authority_bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    #[account(mut, address = pool.token_a_reserve)]
+    pub token_a_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = pool.token_b_reserve)]
+    pub token_b_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = token_a, token::authority = owner)]
+    pub user_token_a_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = token_b, token::authority = owner)]
+    pub user_token_b_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        constraint = !platform.is_paused @ crate::PiDaoError::ContractPaused,
+    )]
+    pub platform: Box<Account<'info, PlatformConfig>>,
+    pub owner: Signer<'info>,
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseClPosition<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ PoolError::NotPositionOwner,
+        constraint = position.liquidity == 0
+            && position.tokens_owed_a == 0
+            && position.tokens_owed_b == 0 @ PoolError::PositionNotEmpty,
+    )]
+    pub position: Account<'info, ClPosition>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+// Tick arrays covering the price range the swap may cross are passed as
+// remaining accounts, ordered in the direction of the swap
+#[derive(Accounts)]
+pub struct ClSwap<'info> {
+    #[account(
+        mut,
+        seeds = [b"cl_pool", pool.token_a.as_ref(), pool.token_b.as_ref(), &pool.tick_spacing.to_le_bytes()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, ClPoolState>>,
+    #[account(address = pool.token_a, mint::token_program = token_a_program)]
+    pub token_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool.token_b, mint::token_program = token_b_program)]
+    pub token_b: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: PDA that owns the reserves
+    #[account(seeds = [b"pool_authority", pool.key().as_ref()], bump = pool.authority_bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    #[account(mut, address = pool.token_a_reserve)]
+    pub token_a_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = pool.token_b_reserve)]
+    pub token_b_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        token::authority = authority,
+        constraint = user_source_account.mint == token_a.key()
+            || user_source_account.mint == token_b.key() @ PoolError::InvalidSwapAccounts,
+    )]
+    pub user_source_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = user_destination_account.mint != user_source_account.mint
+            && (user_destination_account.mint == token_a.key()
+                || user_destination_account.mint == token_b.key()) @ PoolError::InvalidSwapAccounts,
+    )]
+    pub user_destination_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        constraint = !platform.is_paused @ crate::PiDaoError::ContractPaused,
+    )]
+    pub platform: Box<Account<'info, PlatformConfig>>,
+    pub authority: Signer<'info>,
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CollectClProtocolFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"cl_pool", pool.token_a.as_ref(), pool.token_b.as_ref(), &pool.tick_spacing.to_le_bytes()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, ClPoolState>>,
+    #[account(address = pool.token_a, mint::token_program = token_a_program)]
+    pub token_a: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool.token_b, mint::token_program = token_b_program)]
+    pub token_b: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: PDA that owns the reserves
+    #[account(seeds = [b"pool_authority", pool.key().as_ref()], bump = pool.authority_bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    #[account(mut, address = pool.token_a_reserve)]
+    pub token_a_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = pool.token_b_reserve)]
+    pub token_b_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(seeds = [b"platform_config"], bump = platform.bump)]
+    pub platform: Box<Account<'info, PlatformConfig>>,
+    #[account(mut, token::mint = token_a, token::authority = platform.fee_recipient)]
+    pub fee_recipient_token_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = token_b, token::authority = platform.fee_recipient)]
+    pub fee_recipient_token_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ClPoolState {
+    pub version: u8,
+    pub bump: u8,
+    pub authority_bump: u8,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub token_a_reserve: Pubkey,
+    pub token_b_reserve: Pubkey,
+    pub tick_spacing: u16,
+    pub fee: u64, // Fee in basis points (1/10000)
+    pub sqrt_price: u128,
+    pub tick_current: i32,
+    pub liquidity: u128, // Liquidity of the positions in range at the current price
+    // Q64.64 fees earned per unit of liquidity over the pool's life; they wrap
+    pub fee_growth_global_a: u128,
+    pub fee_growth_global_b: u128,
+    pub protocol_fees_a: u64, // Held in the reserves but owed to the platform
+    pub protocol_fees_b: u64,
+    pub reserved: [u8; 64],
+}
+
+impl ClPoolState {
+    pub const VERSION: u8 = 1;
+}
+
+#[zero_copy]
+pub struct Tick {
+    pub liquidity_net: [u8; 16],   // i128, little-endian
+    pub liquidity_gross: [u8; 16], // u128, little-endian
+    // Fee growth on the side of the tick away from the current price
+    pub fee_growth_outside_a: [u8; 16],
+    pub fee_growth_outside_b: [u8; 16],
+    pub initialized: u8,
+    pub padding: [u8; 7],
+}
+
+impl Tick {
+    pub fn liquidity_net(&self) -> i128 {
+        i128::from_le_bytes(self.liquidity_net)
+    }
+
+    pub fn fee_growth_outside(&self) -> (u128, u128) {
+        (
+            u128::from_le_bytes(self.fee_growth_outside_a),
+            u128::from_le_bytes(self.fee_growth_outside_b),
+        )
+    }
+
+    // Add `liquidity_delta` to a position boundary at this tick. Returns whether
+    // no liquidity references the tick any more; the caller clears it once the
+    // position's fees have been read from it.
+    pub fn update(
+        &mut self,
+        tick_index: i32,
+        tick_current: i32,
+        liquidity_delta: i128,
+        fee_growth_global: (u128, u128),
+        upper: bool,
+    ) -> Result<bool> {
+        let gross_before = u128::from_le_bytes(self.liquidity_gross);
+        let gross_after = add_liquidity_delta(gross_before, liquidity_delta)?;
+        if gross_before == 0 && gross_after > 0 {
+            // By convention, all growth before the tick existed happened below it
+            if tick_index <= tick_current {
+                self.fee_growth_outside_a = fee_growth_global.0.to_le_bytes();
+                self.fee_growth_outside_b = fee_growth_global.1.to_le_bytes();
+            }
+            self.initialized = 1;
+        }
+
+        // Crossing a lower boundary upwards adds the liquidity; an upper one removes it
+        let net = if upper {
+            self.liquidity_net().checked_sub(liquidity_delta)
+        } else {
+            self.liquidity_net().checked_add(liquidity_delta)
+        }
+        .ok_or(PoolError::Overflow)?;
+        self.liquidity_net = net.to_le_bytes();
+        self.liquidity_gross = gross_after.to_le_bytes();
+        Ok(gross_after == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.fee_growth_outside_a = [0; 16];
+        self.fee_growth_outside_b = [0; 16];
+        self.initialized = 0;
+    }
+
+    // Flip the outside fee growth as the price moves across the tick
+    pub fn cross(&mut self, fee_growth_global: (u128, u128)) -> i128 {
+        let (outside_a, outside_b) = self.fee_growth_outside();
+        self.fee_growth_outside_a = fee_growth_global.0.wrapping_sub(outside_a).to_le_bytes();
+        self.fee_growth_outside_b = fee_growth_global.1.wrapping_sub(outside_b).to_le_bytes();
+        self.liquidity_net()
+    }
+}
+
+#[account(zero_copy)]
+pub struct TickArray {
+    pub pool: Pubkey,
+    pub start_tick_index: i32,
+    pub version: u8,
+    pub bump: u8,
+    pub padding: [u8; 2],
+    pub ticks: [Tick; TICK_ARRAY_SIZE],
+}
+
+impl TickArray {
+    pub const VERSION: u8 = 1;
+
+    pub fn tick_mut(&mut self, tick_index: i32, tick_spacing: u16) -> &mut Tick {
+        let offset = (tick_index - self.start_tick_index) / tick_spacing as i32;
+        &mut self.ticks[offset as usize]
+    }
+
+    // Next initialized tick from `tick` in the swap direction, or the edge of
+    // the array when none is left in it. `None` when the search starts past the
+    // array entirely.
+    pub fn next_tick(&self, tick: i32, tick_spacing: u16, a_to_b: bool) -> Option<(i32, bool)> {
+        let spacing = tick_spacing as i32;
+        let last = self.start_tick_index + (TICK_ARRAY_SIZE as i32 - 1) * spacing;
+        let index_of = |offset: usize| self.start_tick_index + offset as i32 * spacing;
+
+        if a_to_b {
+            if tick < self.start_tick_index {
+                return None;
+            }
+            // Ticks at or below the current one, highest first
+            let highest = std::cmp::min(
+                (tick - self.start_tick_index).div_euclid(spacing) as usize,
+                TICK_ARRAY_SIZE - 1,
+            );
+            let found = (0..=highest).rev().find(|&offset| self.ticks[offset].initialized == 1);
+            Some(found.map_or((self.start_tick_index, false), |offset| (index_of(offset), true)))
+        } else {
+            if tick >= last {
+                return None;
+            }
+            // Ticks strictly above the current one, lowest first
+            let lowest = std::cmp::max((tick - self.start_tick_index).div_euclid(spacing) + 1, 0) as usize;
+            let found = (lowest..TICK_ARRAY_SIZE).find(|&offset| self.ticks[offset].initialized == 1);
+            Some(found.map_or((last, false), |offset| (index_of(offset), true)))
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ClPosition {
+    pub version: u8,
+    pub bump: u8,
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    // Fee growth inside the range as of the last update, per unit of liquidity
+    pub fee_growth_inside_a_last: u128,
+    pub fee_growth_inside_b_last: u128,
+    pub tokens_owed_a: u64,
+    pub tokens_owed_b: u64,
+    pub reserved: [u8; 32],
+}
+
+impl ClPosition {
+    pub const VERSION: u8 = 1;
+}
+
+// First tick of the array holding `tick`
+pub fn tick_array_start(tick: i32, tick_spacing: u16) -> i32 {
+    let span = tick_spacing as i32 * TICK_ARRAY_SIZE as i32;
+    tick.div_euclid(span) * span
+}
+
+// 128x128-bit product as (high, low) words
+fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+    let lo_lo = a_lo * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_lo = a_hi * b_lo;
+    let hi_hi = a_hi * b_hi;
+    let middle = (lo_lo >> 64) + (lo_hi & MASK) + (hi_lo & MASK);
+    let low = (lo_lo & MASK) | (middle << 64);
+    let high = hi_hi + (lo_hi >> 64) + (hi_lo >> 64) + (middle >> 64);
+    (high, low)
+}
+
+// a * b / denominator through a 256-bit intermediate
+pub fn mul_div_wide(a: u128, b: u128, denominator: u128, round_up: bool) -> Result<u128> {
+    require!(denominator > 0, PoolError::Overflow);
+    let (high, low) = full_mul(a, b);
+    if high == 0 {
+        let quotient = low / denominator;
+        return Ok(if round_up && low % denominator > 0 {
+            quotient.checked_add(1).ok_or(PoolError::Overflow)?
+        } else {
+            quotient
+        });
+    }
+    require!(high < denominator, PoolError::Overflow);
+
+    // Long division of the 256-bit product, one bit at a time
+    let (mut remainder, mut quotient) = (high, 0u128);
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+    Ok(if round_up && remainder > 0 {
+        quotient.checked_add(1).ok_or(PoolError::Overflow)?
+    } else {
+        quotient
+    })
+}
+
+fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    if delta >= 0 {
+        liquidity.checked_add(delta as u128)
+    } else {
+        liquidity.checked_sub(delta.unsigned_abs())
+    }
+    .ok_or(error!(PoolError::Overflow))
+}
+
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    require!((MIN_TICK..=MAX_TICK).contains(&tick), PoolError::InvalidTickRange);
+    let magnitude = tick.unsigned_abs();
+    let mut ratio = Q64;
+    for (bit, power) in SQRT_PRICE_POWERS.iter().enumerate() {
+        if magnitude & (1 << bit) != 0 {
+            let (high, low) = full_mul(ratio, *power);
+            ratio = (high << 64) | (low >> 64);
+        }
+    }
+    Ok(if tick < 0 { u128::MAX / ratio } else { ratio })
+}
+
+// Highest tick in [low, high] whose price is at or below `sqrt_price`
+pub fn tick_at_sqrt_price(sqrt_price: u128, low: i32, high: i32) -> Result<i32> {
+    let (mut low, mut high) = (low, high);
+    while low < high {
+        let middle = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(middle)? <= sqrt_price {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    Ok(low)
+}
+
+// Token A held by `liquidity` between two prices
+pub fn amount_a_delta(sqrt_price_a: u128, sqrt_price_b: u128, liquidity: u128, round_up: bool) -> Result<u64> {
+    let (lower, upper) = if sqrt_price_a < sqrt_price_b {
+        (sqrt_price_a, sqrt_price_b)
+    } else {
+        (sqrt_price_b, sqrt_price_a)
+    };
+    if liquidity == 0 || lower == upper {
+        return Ok(0);
+    }
+    let scaled = mul_div_wide(liquidity, upper - lower, upper, round_up)?;
+    let amount = mul_div_wide(scaled, Q64, lower, round_up)?;
+    u64::try_from(amount).map_err(|_| error!(PoolError::Overflow))
+}
+
+// Token B held by `liquidity` between two prices
+pub fn amount_b_delta(sqrt_price_a: u128, sqrt_price_b: u128, liquidity: u128, round_up: bool) -> Result<u64> {
+    let difference = sqrt_price_a.abs_diff(sqrt_price_b);
+    let amount = mul_div_wide(liquidity, difference, Q64, round_up)?;
+    u64::try_from(amount).map_err(|_| error!(PoolError::Overflow))
+}
+
+// Price after `amount` of the input token is added at constant liquidity,
+// rounded so the pool never gives away more than it receives
+fn next_sqrt_price(sqrt_price: u128, liquidity: u128, amount: u64, a_to_b: bool) -> Result<u128> {
+    if amount == 0 {
+        return Ok(sqrt_price);
+    }
+    if a_to_b {
+        let product = mul_div_wide(amount as u128, sqrt_price, Q64, false)?;
+        let denominator = liquidity.checked_add(product).ok_or(PoolError::Overflow)?;
+        mul_div_wide(liquidity, sqrt_price, denominator, true)
+    } else {
+        let increase = mul_div_wide(amount as u128, Q64, liquidity, false)?;
+        Ok(sqrt_price.checked_add(increase).ok_or(PoolError::Overflow)?)
+    }
+}
+
+pub struct SwapStep {
+    pub sqrt_price_next: u128,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+}
+
+// Trade towards `sqrt_price_target` within a single liquidity range, with the
+// fee taken out of the input first
+pub fn compute_swap_step(
+    sqrt_price: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee: u64,
+    a_to_b: bool,
+) -> Result<SwapStep> {
+    let remaining_less_fee = mul_div_floor(amount_remaining, 10_000 - fee, 10_000)?;
+    let amount_to_target = if a_to_b {
+        amount_a_delta(sqrt_price_target, sqrt_price, liquidity, true)?
+    } else {
+        amount_b_delta(sqrt_price, sqrt_price_target, liquidity, true)?
+    };
+
+    let (sqrt_price_next, amount_in) = if remaining_less_fee >= amount_to_target {
+        (sqrt_price_target, amount_to_target)
+    } else {
+        (
+            next_sqrt_price(sqrt_price, liquidity, remaining_less_fee, a_to_b)?,
+            remaining_less_fee,
+        )
+    };
+    let amount_out = if a_to_b {
+        amount_b_delta(sqrt_price_next, sqrt_price, liquidity, false)?
+    } else {
+        amount_a_delta(sqrt_price, sqrt_price_next, liquidity, false)?
+    };
+    // A step that stops short of the target has used up the whole input
+    let fee_amount = if sqrt_price_next == sqrt_price_target {
+        std::cmp::min(
+            mul_div_ceil(amount_in, fee, 10_000 - fee)?,
+            amount_remaining - amount_in,
+        )
+    } else {
+        amount_remaining - amount_in
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+// Fee growth per unit of liquidity accrued between two ticks
+fn fee_growth_inside(
+    lower: (&Tick, i32),
+    upper: (&Tick, i32),
+    tick_current: i32,
+    fee_growth_global: (u128, u128),
+) -> (u128, u128) {
+    let (lower_outside_a, lower_outside_b) = lower.0.fee_growth_outside();
+    let (upper_outside_a, upper_outside_b) = upper.0.fee_growth_outside();
+    let (below_a, below_b) = if tick_current >= lower.1 {
+        (lower_outside_a, lower_outside_b)
+    } else {
+        (
+            fee_growth_global.0.wrapping_sub(lower_outside_a),
+            fee_growth_global.1.wrapping_sub(lower_outside_b),
+        )
+    };
+    let (above_a, above_b) = if tick_current < upper.1 {
+        (upper_outside_a, upper_outside_b)
+    } else {
+        (
+            fee_growth_global.0.wrapping_sub(upper_outside_a),
+            fee_growth_global.1.wrapping_sub(upper_outside_b),
+        )
+    };
+    (
+        fee_growth_global.0.wrapping_sub(below_a).wrapping_sub(above_a),
+        fee_growth_global.1.wrapping_sub(below_b).wrapping_sub(above_b),
+    )
+}
+
+// Apply a liquidity change to a position and its boundary ticks, crediting the
+// fees earned since its last update. Returns the token amounts the change
+// moves, rounded up when depositing and down when withdrawing.
+fn modify_position(accounts: &mut ModifyClPosition, liquidity_delta: i128) -> Result<(u64, u64)> {
+    let pool = &mut accounts.pool;
+    let position = &mut accounts.position;
+    let fee_growth_global = (pool.fee_growth_global_a, pool.fee_growth_global_b);
+
+    // The two boundaries may share an array, so each is loaded on its own
+    let (lower, clear_lower) = {
+        let mut array = accounts.tick_array_lower.load_mut()?;
+        let tick = array.tick_mut(position.tick_lower, pool.tick_spacing);
+        let empty = tick.update(position.tick_lower, pool.tick_current, liquidity_delta, fee_growth_global, false)?;
+        (*tick, empty)
+    };
+    let (upper, clear_upper) = {
+        let mut array = accounts.tick_array_upper.load_mut()?;
+        let tick = array.tick_mut(position.tick_upper, pool.tick_spacing);
+        let empty = tick.update(position.tick_upper, pool.tick_current, liquidity_delta, fee_growth_global, true)?;
+        (*tick, empty)
+    };
+
+    let (inside_a, inside_b) = fee_growth_inside(
+        (&lower, position.tick_lower),
+        (&upper, position.tick_upper),
+        pool.tick_current,
+        fee_growth_global,
+    );
+    let earned_a = mul_div_wide(
+        position.liquidity,
+        inside_a.wrapping_sub(position.fee_growth_inside_a_last),
+        Q64,
+        false,
+    )?;
+    let earned_b = mul_div_wide(
+        position.liquidity,
+        inside_b.wrapping_sub(position.fee_growth_inside_b_last),
+        Q64,
+        false,
+    )?;
+    position.tokens_owed_a = u64::try_from(earned_a)
+        .ok()
+        .and_then(|earned| position.tokens_owed_a.checked_add(earned))
+        .ok_or(PoolError::Overflow)?;
+    position.tokens_owed_b = u64::try_from(earned_b)
+        .ok()
+        .and_then(|earned| position.tokens_owed_b.checked_add(earned))
+        .ok_or(PoolError::Overflow)?;
+    position.fee_growth_inside_a_last = inside_a;
+    position.fee_growth_inside_b_last = inside_b;
+    position.liquidity = add_liquidity_delta(position.liquidity, liquidity_delta)?;
+
+    // Only now that the fees are credited may an unused boundary lose its growth
+    if clear_lower {
+        accounts.tick_array_lower.load_mut()?.tick_mut(position.tick_lower, pool.tick_spacing).clear();
+    }
+    if clear_upper {
+        accounts.tick_array_upper.load_mut()?.tick_mut(position.tick_upper, pool.tick_spacing).clear();
+    }
+
+    let round_up = liquidity_delta > 0;
+    let liquidity = liquidity_delta.unsigned_abs();
+    let sqrt_price_lower = sqrt_price_at_tick(position.tick_lower)?;
+    let sqrt_price_upper = sqrt_price_at_tick(position.tick_upper)?;
+    let amounts = if pool.tick_current < position.tick_lower {
+        (amount_a_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?, 0)
+    } else if pool.tick_current < position.tick_upper {
+        pool.liquidity = add_liquidity_delta(pool.liquidity, liquidity_delta)?;
+        (
+            amount_a_delta(pool.sqrt_price, sqrt_price_upper, liquidity, round_up)?,
+            amount_b_delta(sqrt_price_lower, pool.sqrt_price, liquidity, round_up)?,
+        )
+    } else {
+        (0, amount_b_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up)?)
+    };
+    Ok(amounts)
+}
+
+// Pay `amount` of one side out of the reserves, signed by the pool authority
+#[allow(clippy::too_many_arguments)]
+fn transfer_from_reserve<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    reserve: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: &InterfaceAccount<'info, TokenAccount>,
+    pool_authority: &UncheckedAccount<'info>,
+    pool_key: Pubkey,
+    authority_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let authority_seeds: &[&[u8]] = &[b"pool_authority", pool_key.as_ref(), &[authority_bump]];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: reserve.to_account_info(),
+                mint: mint.to_account_info(),
+                to: destination.to_account_info(),
+                authority: pool_authority.to_account_info(),
+            },
+            &[authority_seeds],
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+// Pay `amount` of one side into the reserves from a user account
+fn transfer_to_reserve<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    source: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    reserve: &InterfaceAccount<'info, TokenAccount>,
+    authority: &Signer<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    token_interface::transfer_checked(
+        CpiContext::new(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: source.to_account_info(),
+                mint: mint.to_account_info(),
+                to: reserve.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+pub fn handle_initialize_cl_pool(
+    ctx: Context<InitializeClPool>,
+    tick_spacing: u16,
+    fee: u64,
+    sqrt_price: u128,
+) -> Result<()> {
+    require!(
+        (1..=MAX_TICK_SPACING).contains(&tick_spacing),
+        PoolError::InvalidTickRange
+    );
+    require!(fee < 10_000, PoolError::InvalidFee);
+    require!(
+        sqrt_price >= sqrt_price_at_tick(MIN_TICK)? && sqrt_price < sqrt_price_at_tick(MAX_TICK)?,
+        PoolError::InvalidSqrtPrice
+    );
+    // Reserves are tracked by what was sent, so fee-on-transfer mints would leave them short
+    require!(
+        !charges_transfer_fee(&ctx.accounts.token_a)? && !charges_transfer_fee(&ctx.accounts.token_b)?,
+        PoolError::UnsupportedMint
+    );
+    check_pool_mint_extensions(&ctx.accounts.token_a)?;
+    check_pool_mint_extensions(&ctx.accounts.token_b)?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.version = ClPoolState::VERSION;
+    pool.bump = ctx.bumps.pool;
+    pool.authority_bump = ctx.bumps.pool_authority;
+    pool.token_a = ctx.accounts.token_a.key();
+    pool.token_b = ctx.accounts.token_b.key();
+    pool.token_a_reserve = ctx.accounts.token_a_reserve.key();
+    pool.token_b_reserve = ctx.accounts.token_b_reserve.key();
+    pool.tick_spacing = tick_spacing;
+    pool.fee = fee;
+    pool.sqrt_price = sqrt_price;
+    pool.tick_current = tick_at_sqrt_price(sqrt_price, MIN_TICK, MAX_TICK)?;
+    pool.liquidity = 0;
+    pool.fee_growth_global_a = 0;
+    pool.fee_growth_global_b = 0;
+
+    Ok(())
+}
+
+pub fn handle_initialize_tick_array(ctx: Context<InitializeTickArray>, start_tick_index: i32) -> Result<()> {
+    let tick_spacing = ctx.accounts.pool.tick_spacing;
+    require!(
+        start_tick_index == tick_array_start(start_tick_index, tick_spacing)
+            && tick_array_start(MIN_TICK, tick_spacing) <= start_tick_index
+            && start_tick_index <= MAX_TICK,
+        PoolError::InvalidTickArray
+    );
+
+    let mut tick_array = ctx.accounts.tick_array.load_init()?;
+    tick_array.version = TickArray::VERSION;
+    tick_array.bump = ctx.bumps.tick_array;
+    tick_array.pool = ctx.accounts.pool.key();
+    tick_array.start_tick_index = start_tick_index;
+
+    Ok(())
+}
+
+pub fn handle_open_cl_position(ctx: Context<OpenClPosition>, tick_lower: i32, tick_upper: i32) -> Result<()> {
+    let spacing = ctx.accounts.pool.tick_spacing as i32;
+    require!(
+        tick_lower < tick_upper
+            && tick_lower >= MIN_TICK
+            && tick_upper <= MAX_TICK
+            && tick_lower % spacing == 0
+            && tick_upper % spacing == 0,
+        PoolError::InvalidTickRange
+    );
+
+    let position = &mut ctx.accounts.position;
+    position.version = ClPosition::VERSION;
+    position.bump = ctx.bumps.position;
+    position.pool = ctx.accounts.pool.key();
+    position.owner = ctx.accounts.owner.key();
+    position.tick_lower = tick_lower;
+    position.tick_upper = tick_upper;
+
+    Ok(())
+}
+
+pub fn handle_increase_cl_liquidity(
+    ctx: Context<ModifyClPosition>,
+    liquidity: u128,
+    max_amount_a: u64,
+    max_amount_b: u64,
+    deadline: i64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    require!(liquidity > 0, PoolError::ZeroLiquidity);
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| error!(PoolError::Overflow))?;
+
+    let (amount_a, amount_b) = modify_position(ctx.accounts, liquidity_delta)?;
+    require!(
+        amount_a <= max_amount_a && amount_b <= max_amount_b,
+        PoolError::SlippageExceeded
+    );
+
+    let accounts = &ctx.accounts;
+    transfer_to_reserve(
+        &accounts.token_a_program,
+        &accounts.user_token_a_account,
+        &accounts.token_a,
+        &accounts.token_a_reserve,
+        &accounts.owner,
+        amount_a,
+    )?;
+    transfer_to_reserve(
+        &accounts.token_b_program,
+        &accounts.user_token_b_account,
+        &accounts.token_b,
+        &accounts.token_b_reserve,
+        &accounts.owner,
+        amount_b,
+    )?;
+
+    Ok(())
+}
+
+// Withdraws the principal straight away; earned fees stay owed until collected
+pub fn handle_decrease_cl_liquidity(
+    ctx: Context<ModifyClPosition>,
+    liquidity: u128,
+    min_amount_a: u64,
+    min_amount_b: u64,
+    deadline: i64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    require!(
+        liquidity > 0 && liquidity <= ctx.accounts.position.liquidity,
+        PoolError::ZeroLiquidity
+    );
+    let liquidity_delta = i128::try_from(liquidity).map_err(|_| error!(PoolError::Overflow))?;
+
+    let (amount_a, amount_b) = modify_position(ctx.accounts, -liquidity_delta)?;
+    require!(
+        amount_a >= min_amount_a && amount_b >= min_amount_b,
+        PoolError::SlippageExceeded
+    );
+
+    let accounts = &ctx.accounts;
+    let pool_key = accounts.pool.key();
+    transfer_from_reserve(
+        &accounts.token_a_program,
+        &accounts.token_a_reserve,
+        &accounts.token_a,
+        &accounts.user_token_a_account,
+        &accounts.pool_authority,
+        pool_key,
+        accounts.pool.authority_bump,
+        amount_a,
+    )?;
+    transfer_from_reserve(
+        &accounts.token_b_program,
+        &accounts.token_b_reserve,
+        &accounts.token_b,
+        &accounts.user_token_b_account,
+        &accounts.pool_authority,
+        pool_key,
+        accounts.pool.authority_bump,
+        amount_b,
+    )?;
+
+    Ok(())
+}
+
+pub fn handle_collect_cl_position_fees(ctx: Context<ModifyClPosition>) -> Result<()> {
+    // A zero change brings the owed fees up to date
+    modify_position(ctx.accounts, 0)?;
+
+    let accounts = ctx.accounts;
+    let (owed_a, owed_b) = (accounts.position.tokens_owed_a, accounts.position.tokens_owed_b);
+    accounts.position.tokens_owed_a = 0;
+    accounts.position.tokens_owed_b = 0;
+
+    let pool_key = accounts.pool.key();
+    transfer_from_reserve(
+        &accounts.token_a_program,
+        &accounts.token_a_reserve,
+        &accounts.token_a,
+        &accounts.user_token_a_account,
+        &accounts.pool_authority,
+        pool_key,
+        accounts.pool.authority_bump,
+        owed_a,
+    )?;
+    transfer_from_reserve(
+        &accounts.token_b_program,
+        &accounts.token_b_reserve,
+        &accounts.token_b,
+        &accounts.user_token_b_account,
+        &accounts.pool_authority,
+        pool_key,
+        accounts.pool.authority_bump,
+        owed_b,
+    )?;
+
+    Ok(())
+}
+
+// Exact-input swap that walks the price across initialized ticks. It stops
+// early at `sqrt_price_limit` or when the supplied tick arrays run out, and
+// only the input actually used is taken from the trader.
+pub fn handle_cl_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClSwap<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    sqrt_price_limit: u128,
+    deadline: i64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let pool_key = ctx.accounts.pool.key();
+    let pool = &ctx.accounts.pool;
+    let a_to_b = ctx.accounts.user_source_account.mint == pool.token_a;
+    let tick_spacing = pool.tick_spacing;
+    let span = tick_spacing as i32 * TICK_ARRAY_SIZE as i32;
+
+    let tick_arrays = ctx
+        .remaining_accounts
+        .iter()
+        .map(|info| {
+            let tick_array = AccountLoader::<TickArray>::try_from(info)?;
+            require!(tick_array.load()?.pool == pool_key, PoolError::InvalidTickArray);
+            Ok(tick_array)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    // The first array holds the current tick, or the next one up when buying A
+    let first_start = tick_arrays
+        .first()
+        .ok_or(PoolError::InvalidTickArray)?
+        .load()?
+        .start_tick_index;
+    let current_start = tick_array_start(pool.tick_current, tick_spacing);
+    require!(
+        first_start == current_start || (!a_to_b && first_start == current_start + span),
+        PoolError::InvalidTickArray
+    );
+
+    let (min_sqrt_price, max_sqrt_price) = (sqrt_price_at_tick(MIN_TICK)?, sqrt_price_at_tick(MAX_TICK)?);
+    let sqrt_price_limit = match (sqrt_price_limit, a_to_b) {
+        (0, true) => min_sqrt_price,
+        (0, false) => max_sqrt_price,
+        (limit, _) => limit,
+    };
+    require!(
+        if a_to_b {
+            sqrt_price_limit < pool.sqrt_price && sqrt_price_limit >= min_sqrt_price
+        } else {
+            sqrt_price_limit > pool.sqrt_price && sqrt_price_limit <= max_sqrt_price
+        },
+        PoolError::InvalidSqrtPrice
+    );
+
+    let mut remaining = amount_in;
+    let mut amount_out = 0u64;
+    let mut protocol_fees = 0u64;
+    let mut sqrt_price = pool.sqrt_price;
+    let mut tick = pool.tick_current;
+    let mut liquidity = pool.liquidity;
+    let mut fee_growth_global = (pool.fee_growth_global_a, pool.fee_growth_global_b);
+    let mut array_index = 0;
+
+    while remaining > 0 && sqrt_price != sqrt_price_limit {
+        let next = loop {
+            let Some(tick_array) = tick_arrays.get(array_index) else {
+                break None;
+            };
+            let array = tick_array.load()?;
+            if let Some(found) = array.next_tick(tick, tick_spacing, a_to_b) {
+                break Some(found);
+            }
+            // Arrays must be adjacent so no initialized tick is skipped
+            if let Some(following) = tick_arrays.get(array_index + 1) {
+                let expected = array.start_tick_index + if a_to_b { -span } else { span };
+                require!(
+                    following.load()?.start_tick_index == expected,
+                    PoolError::InvalidTickArray
+                );
+            }
+            array_index += 1;
+        };
+        let Some((next_tick, initialized)) = next else {
+            break;
+        };
+        let next_tick = next_tick.clamp(MIN_TICK, MAX_TICK);
+        let sqrt_price_next_tick = sqrt_price_at_tick(next_tick)?;
+        let sqrt_price_target = if a_to_b {
+            std::cmp::max(sqrt_price_next_tick, sqrt_price_limit)
+        } else {
+            std::cmp::min(sqrt_price_next_tick, sqrt_price_limit)
+        };
+
+        let step = compute_swap_step(sqrt_price, sqrt_price_target, liquidity, remaining, pool.fee, a_to_b)?;
+        remaining -= step.amount_in + step.fee_amount;
+        amount_out = amount_out.checked_add(step.amount_out).ok_or(PoolError::Overflow)?;
+
+        // The platform's cut is set aside; the rest accrues to in-range liquidity
+        let protocol_fee = fee_on(step.fee_amount, ctx.accounts.platform.swap_protocol_fee_bps)?;
+        protocol_fees += protocol_fee;
+        if liquidity > 0 {
+            let growth = mul_div_wide((step.fee_amount - protocol_fee) as u128, Q64, liquidity, false)?;
+            if a_to_b {
+                fee_growth_global.0 = fee_growth_global.0.wrapping_add(growth);
+            } else {
+                fee_growth_global.1 = fee_growth_global.1.wrapping_add(growth);
+            }
+        }
+
+        let sqrt_price_before = sqrt_price;
+        sqrt_price = step.sqrt_price_next;
+        if sqrt_price == sqrt_price_next_tick {
+            if initialized {
+                let mut array = tick_arrays[array_index].load_mut()?;
+                let liquidity_net = array.tick_mut(next_tick, tick_spacing).cross(fee_growth_global);
+                // Moving down crosses upper boundaries first, so the sign flips
+                let delta = if a_to_b { -liquidity_net } else { liquidity_net };
+                liquidity = add_liquidity_delta(liquidity, delta)?;
+            }
+            tick = if a_to_b { next_tick - 1 } else { next_tick };
+        } else if sqrt_price != sqrt_price_before {
+            tick = if a_to_b {
+                tick_at_sqrt_price(sqrt_price, next_tick, tick)?
+            } else {
+                tick_at_sqrt_price(sqrt_price, tick, next_tick - 1)?
+            };
+        }
+    }
+
+    let amount_used = amount_in - remaining;
+    require!(amount_out >= min_amount_out, PoolError::SlippageExceeded);
+
+    let pool = &mut ctx.accounts.pool;
+    pool.sqrt_price = sqrt_price;
+    pool.tick_current = tick;
+    pool.liquidity = liquidity;
+    pool.fee_growth_global_a = fee_growth_global.0;
+    pool.fee_growth_global_b = fee_growth_global.1;
+    if a_to_b {
+        pool.protocol_fees_a = pool.protocol_fees_a.checked_add(protocol_fees).ok_or(PoolError::Overflow)?;
+    } else {
+        pool.protocol_fees_b = pool.protocol_fees_b.checked_add(protocol_fees).ok_or(PoolError::Overflow)?;
+    }
+
+    let accounts = &ctx.accounts;
+    let (mint_in, mint_out, reserve_in, reserve_out) = if a_to_b {
+        (&accounts.token_a, &accounts.token_b, &accounts.token_a_reserve, &accounts.token_b_reserve)
+    } else {
+        (&accounts.token_b, &accounts.token_a, &accounts.token_b_reserve, &accounts.token_a_reserve)
+    };
+    let (program_in, program_out) = if a_to_b {
+        (&accounts.token_a_program, &accounts.token_b_program)
+    } else {
+        (&accounts.token_b_program, &accounts.token_a_program)
+    };
+    transfer_to_reserve(
+        program_in,
+        &accounts.user_source_account,
+        mint_in,
+        reserve_in,
+        &accounts.authority,
+        amount_used,
+    )?;
+    transfer_from_reserve(
+        program_out,
+        reserve_out,
+        mint_out,
+        &accounts.user_destination_account,
+        &accounts.pool_authority,
+        pool_key,
+        accounts.pool.authority_bump,
+        amount_out,
+    )?;
+
+    Ok(())
+}
+
+// Pay the accumulated protocol share of swap fees to the fee recipient
+pub fn handle_collect_cl_protocol_fees(ctx: Context<CollectClProtocolFees>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let (fees_a, fees_b) = (pool.protocol_fees_a, pool.protocol_fees_b);
+    require!(fees_a > 0 || fees_b > 0, PoolError::NoProtocolFees);
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+
+    let accounts = &ctx.accounts;
+    let pool_key = accounts.pool.key();
+    for (amount, token_program, reserve, mint, recipient) in [
+        (
+            fees_a,
+            &accounts.token_a_program,
+            &accounts.token_a_reserve,
+            &accounts.token_a,
+            &accounts.fee_recipient_token_a,
+        ),
+        (
+            fees_b,
+            &accounts.token_b_program,
+            &accounts.token_b_reserve,
+            &accounts.token_b,
+            &accounts.fee_recipient_token_b,
+        ),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        transfer_from_reserve(
+            token_program,
+            reserve,
+            mint,
+            recipient,
+            &accounts.pool_authority,
+            pool_key,
+            accounts.pool.authority_bump,
+            amount,
+        )?;
+
+        emit!(ProtocolFeesCollected {
+            source: pool_key,
+            mint: mint.key(),
+            recipient: recipient.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    Ok(())
+}
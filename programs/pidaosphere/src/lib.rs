@@ -1,8 +1,21 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Mint};
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_spl::metadata::{self, Metadata};
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+use anchor_spl::token_interface::spl_token_2022::instruction::AuthorityType;
 
 declare_id!("your_program_id");
 
+pub mod clmm;
+pub mod migration;
+pub mod oracle;
+pub mod pool;
+
+pub use clmm::*;
+pub use oracle::*;
+pub use pool::*;
+
 #[program]
 pub mod pidaosphere {
     use super::*;
@@ -11,13 +24,37 @@ pub mod pidaosphere {
     pub fn initialize_project(
         ctx: Context<InitializeProject>,
         config: ProjectConfig,
+        token_config: TokenConfig,
         initial_pi_price: u64,
         invite_code: Option<String>,
+        vesting_allocations: Vec<VestingAllocation>,
     ) -> Result<()> {
         let project = &mut ctx.accounts.project;
         let current_time = Clock::get()?.unix_timestamp;
 
         // Validate configuration
+        require!(
+            !config.slug.is_empty() && config.slug.len() <= MAX_SLUG_LEN,
+            PiDaoError::InvalidSlug
+        );
+        if let Some(code) = &invite_code {
+            require!(
+                code.len() <= MAX_INVITE_CODE_LEN,
+                PiDaoError::InviteCodeTooLong
+            );
+        }
+        require!(
+            token_config.name.len() <= MAX_TITLE_LEN,
+            PiDaoError::TitleTooLong
+        );
+        require!(
+            token_config.symbol.len() <= MAX_SYMBOL_LEN,
+            PiDaoError::SymbolTooLong
+        );
+        require!(
+            token_config.uri.len() <= MAX_URI_LEN,
+            PiDaoError::UriTooLong
+        );
         require!(
             config.min_raise <= config.max_raise,
             PiDaoError::InvalidConfig
@@ -26,10 +63,22 @@ pub mod pidaosphere {
             config.min_investment <= config.max_investment,
             PiDaoError::InvalidConfig
         );
+        if let Some(lockup) = &config.lockup {
+            require!(
+                lockup.tge_unlock_bps <= 10000 && lockup.unlock_duration >= 0,
+                PiDaoError::InvalidConfig
+            );
+        }
 
+        project.version = ProjectState::VERSION;
         project.is_initialized = true;
+        project.bump = ctx.bumps.project;
+        project.slug = config.slug.clone();
         project.authority = ctx.accounts.authority.key();
         project.project_token_mint = ctx.accounts.project_token_mint.key();
+        project.quote_mint = ctx.accounts.quote_mint.key();
+        project.treasury_vault = ctx.accounts.treasury_vault.key();
+        project.signer_bump = ctx.bumps.project_signer;
         project.total_supply = config.total_supply;
         project.initial_pi_price = initial_pi_price;
         project.current_pi_price = initial_pi_price;
@@ -44,21 +93,162 @@ pub mod pidaosphere {
         project.total_investment = 0;
         project.liquidity_amount = 0;
         project.investment_amount = 0;
+        project.guardian = config.guardian;
+        project.multisig = config.multisig;
+        project.investor_vault = ctx.accounts.investor_vault.key();
+        project.lockup = config.lockup;
         project.invite_only = invite_code.is_some();
         project.invite_code = invite_code.unwrap_or_default();
         project.refunds_enabled = false;
+        project.proposal_count = 0;
         project.status = ProjectStatus::Fundraising;
 
+        // Register the launch so it can be enumerated from the platform
+        let platform = &mut ctx.accounts.platform;
+        project.registry_index = platform.project_count;
+
+        let project_entry = &mut ctx.accounts.project_entry;
+        project_entry.version = ProjectEntry::VERSION;
+        project_entry.index = platform.project_count;
+        project_entry.project = project.key();
+        project_entry.created_at = current_time;
+
+        platform.project_count = platform.project_count
+            .checked_add(1)
+            .ok_or(PiDaoError::Overflow)?;
+
+        emit!(ProjectRegistered {
+            project: project.key(),
+            index: project.registry_index,
+            authority: project.authority,
+            timestamp: current_time,
+        });
+
+        // Creator and team allocations vest from the end of the fundraise
+        require!(
+            vesting_allocations.len() <= MAX_VESTING_SCHEDULES,
+            PiDaoError::TooManyVestingSchedules
+        );
+
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.version = VestingState::VERSION;
+        vesting.project = project.key();
+        vesting.vault = ctx.accounts.vesting_vault.key();
+        vesting.schedules = Vec::with_capacity(vesting_allocations.len());
+
+        let mut vesting_supply: u64 = 0;
+        for allocation in vesting_allocations {
+            require!(
+                allocation.amount > 0 && allocation.cliff >= 0 && allocation.duration >= 0,
+                PiDaoError::InvalidVestingSchedule
+            );
+            vesting_supply = vesting_supply
+                .checked_add(allocation.amount)
+                .ok_or(PiDaoError::Overflow)?;
+
+            let cliff_time = project.fundraise_end_time
+                .checked_add(allocation.cliff)
+                .ok_or(PiDaoError::Overflow)?;
+            vesting.schedules.push(VestingSchedule {
+                beneficiary: allocation.beneficiary,
+                total_amount: allocation.amount,
+                claimed_amount: 0,
+                cliff_time,
+                end_time: cliff_time
+                    .checked_add(allocation.duration)
+                    .ok_or(PiDaoError::Overflow)?,
+                revocable: allocation.revocable,
+                revoked: false,
+            });
+        }
+        // Vesting and sale allocations are carved out of the total supply
+        let allocated_supply = vesting_supply
+            .checked_add(config.sale_supply)
+            .ok_or(PiDaoError::Overflow)?;
+        require!(
+            allocated_supply <= config.total_supply,
+            PiDaoError::InvalidConfig
+        );
+        project.vesting_supply = vesting_supply;
+        project.sale_supply = config.sale_supply;
+        project.sale_minted = 0;
+        project.minted_supply = 0;
+
+        let project_key = project.key();
+        let signer_seeds: &[&[u8]] = &[
+            b"project_signer",
+            project_key.as_ref(),
+            &[project.signer_bump],
+        ];
+
+        // Attach token metadata under the project signer's authority
+        metadata::create_metadata_accounts_v3(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                metadata::CreateMetadataAccountsV3 {
+                    metadata: ctx.accounts.token_metadata.to_account_info(),
+                    mint: ctx.accounts.project_token_mint.to_account_info(),
+                    mint_authority: ctx.accounts.project_signer.to_account_info(),
+                    payer: ctx.accounts.authority.to_account_info(),
+                    update_authority: ctx.accounts.project_signer.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            DataV2 {
+                name: token_config.name,
+                symbol: token_config.symbol,
+                uri: token_config.uri,
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            false,
+            true,
+            None,
+        )?;
+
+        // Without a freeze authority no holder can ever be frozen
+        if token_config.revoke_freeze_authority {
+            token_interface::set_authority(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::SetAuthority {
+                        current_authority: ctx.accounts.project_signer.to_account_info(),
+                        account_or_mint: ctx.accounts.project_token_mint.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                AuthorityType::FreezeAccount,
+                None,
+            )?;
+        }
+
+        // Lock the vesting allocations in the vesting vault
+        if vesting_supply > 0 {
+            project.record_mint(vesting_supply)?;
+
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::MintTo {
+                        mint: ctx.accounts.project_token_mint.to_account_info(),
+                        to: ctx.accounts.vesting_vault.to_account_info(),
+                        authority: ctx.accounts.project_signer.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                vesting_supply,
+            )?;
+        }
+
         Ok(())
     }
 
     // Emergency pause
     pub fn pause_contract(ctx: Context<EmergencyAction>) -> Result<()> {
-        require!(
-            ctx.accounts.admin.key() == ctx.accounts.project.authority,
-            PiDaoError::UnauthorizedAccess
-        );
-        
         let project = &mut ctx.accounts.project;
         project.is_paused = true;
         
@@ -73,11 +263,6 @@ pub mod pidaosphere {
 
     // Emergency unpause
     pub fn unpause_contract(ctx: Context<EmergencyAction>) -> Result<()> {
-        require!(
-            ctx.accounts.admin.key() == ctx.accounts.project.authority,
-            PiDaoError::UnauthorizedAccess
-        );
-        
         let project = &mut ctx.accounts.project;
         project.is_paused = false;
         
@@ -132,18 +317,53 @@ pub mod pidaosphere {
             .checked_mul(project.initial_pi_price)
             .ok_or(PiDaoError::Overflow)?;
 
+        // Sale mints come out of the reserved sale allocation
+        let sale_minted = project.sale_minted
+            .checked_add(token_amount)
+            .ok_or(PiDaoError::Overflow)?;
+        require!(
+            sale_minted <= project.sale_supply,
+            PiDaoError::InvestmentCapReached
+        );
+        project.sale_minted = sale_minted;
+        project.record_mint(token_amount)?;
+
         // Transfer Pi tokens from investor
         // TODO: Implement Pi Network payment integration
 
+        // Locked-up projects hold investor tokens until they unlock
+        let destination = if project.lockup.is_some() {
+            let investor_lock = ctx.accounts.investor_lock
+                .as_mut()
+                .ok_or(PiDaoError::LockupAccountMissing)?;
+            investor_lock.version = InvestorLock::VERSION;
+            investor_lock.project = project.key();
+            investor_lock.investor = ctx.accounts.investor.key();
+            investor_lock.total_amount = investor_lock.total_amount
+                .checked_add(token_amount)
+                .ok_or(PiDaoError::Overflow)?;
+            ctx.accounts.investor_vault.to_account_info()
+        } else {
+            ctx.accounts.user_token_account.to_account_info()
+        };
+
+        let project_key = project.key();
+        let signer_seeds: &[&[u8]] = &[
+            b"project_signer",
+            project_key.as_ref(),
+            &[project.signer_bump],
+        ];
+
         // Mint project tokens to investor
-        token::mint_to(
-            CpiContext::new(
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                token::MintTo {
+                token_interface::MintTo {
                     mint: ctx.accounts.project_token_mint.to_account_info(),
-                    to: ctx.accounts.user_token_account.to_account_info(),
-                    authority: ctx.accounts.authority.to_account_info(),
+                    to: destination,
+                    authority: ctx.accounts.project_signer.to_account_info(),
                 },
+                &[signer_seeds],
             ),
             token_amount,
         )?;
@@ -171,17 +391,36 @@ pub mod pidaosphere {
         execution_data: Vec<u64>,
         voting_period: i64,
     ) -> Result<()> {
-        let proposal = &mut ctx.accounts.proposal;
-        let project = &ctx.accounts.project;
+        let mut proposal = ctx.accounts.proposal.load_init()?;
+        let project = &mut ctx.accounts.project;
+
+        require!(
+            project.status != ProjectStatus::Expired,
+            PiDaoError::GovernanceFrozen
+        );
+        require!(
+            description.len() <= MAX_PROPOSAL_DESCRIPTION_LEN,
+            PiDaoError::DescriptionTooLong
+        );
+        require!(
+            execution_data.len() <= MAX_EXECUTION_DATA_LEN,
+            PiDaoError::ExecutionDataTooLong
+        );
+
+        // The proposal address is derived from this index
+        *proposal = ProposalState::new(
+            project.key(),
+            project.proposal_count,
+            ctx.accounts.creator.key(),
+        );
+        project.proposal_count = project.proposal_count
+            .checked_add(1)
+            .ok_or(PiDaoError::Overflow)?;
 
-        proposal.creator = ctx.accounts.creator.key();
         proposal.start_time = Clock::get()?.unix_timestamp;
         proposal.end_time = proposal.start_time + voting_period;
-        proposal.description = description;
-        proposal.execution_data = execution_data;
-        proposal.for_votes = 0;
-        proposal.against_votes = 0;
-        proposal.status = ProposalStatus::Active;
+        proposal.set_description(&description);
+        proposal.set_execution_data(&execution_data);
 
         Ok(())
     }
@@ -191,18 +430,19 @@ pub mod pidaosphere {
         ctx: Context<CastVote>,
         support: bool,
     ) -> Result<()> {
-        let proposal = &mut ctx.accounts.proposal;
-        let voter = &ctx.accounts.voter;
+        let mut proposal = ctx.accounts.proposal.load_mut()?;
 
+        require!(
+            ctx.accounts.project.status != ProjectStatus::Expired,
+            PiDaoError::GovernanceFrozen
+        );
         require!(
             Clock::get()?.unix_timestamp <= proposal.end_time,
             PiDaoError::VotingEnded
         );
 
         // Calculate voting power based on token balance
-        let voting_power = token::accessor::amount(
-            &ctx.accounts.voter_token_account.to_account_info()
-        )?;
+        let voting_power = ctx.accounts.voter_token_account.amount;
 
         if support {
             proposal.for_votes = proposal.for_votes.checked_add(voting_power).unwrap();
@@ -220,9 +460,10 @@ pub mod pidaosphere {
         threshold: u64,
     ) -> Result<()> {
         require!(threshold > 0 && threshold <= owners.len() as u64, PiDaoError::InvalidThreshold);
-        require!(owners.len() <= 10, PiDaoError::TooManyOwners);
+        require!(owners.len() <= MAX_MULTISIG_OWNERS, PiDaoError::TooManyOwners);
 
         let multisig = &mut ctx.accounts.multisig;
+        multisig.version = MultisigState::VERSION;
         multisig.owners = owners;
         multisig.threshold = threshold;
         multisig.nonce = 0;
@@ -237,15 +478,23 @@ pub mod pidaosphere {
         new_program_id: Pubkey,
         description: String,
     ) -> Result<()> {
-        let upgrade = &mut ctx.accounts.upgrade_proposal;
-        let multisig = &ctx.accounts.multisig;
-
         require!(
-            multisig.owners.contains(&ctx.accounts.proposer.key()),
-            PiDaoError::UnauthorizedAccess
+            description.len() <= MAX_UPGRADE_DESCRIPTION_LEN,
+            PiDaoError::DescriptionTooLong
         );
 
+        let upgrade = &mut ctx.accounts.upgrade_proposal;
+        let multisig = &mut ctx.accounts.multisig;
+
+        // The upgrade address is derived from the multisig nonce
+        upgrade.version = UpgradeProposal::VERSION;
+        upgrade.index = multisig.nonce;
+        multisig.nonce = multisig.nonce
+            .checked_add(1)
+            .ok_or(PiDaoError::Overflow)?;
+
         upgrade.proposer = ctx.accounts.proposer.key();
+        upgrade.multisig = multisig.key();
         upgrade.new_program_id = new_program_id;
         upgrade.description = description;
         upgrade.approved_by = vec![ctx.accounts.proposer.key()];
@@ -265,14 +514,9 @@ pub mod pidaosphere {
     // Approve upgrade
     pub fn approve_upgrade(ctx: Context<ApproveUpgrade>) -> Result<()> {
         let upgrade = &mut ctx.accounts.upgrade_proposal;
-        let multisig = &ctx.accounts.multisig;
         let approver = &ctx.accounts.approver;
 
         require!(!upgrade.executed, PiDaoError::AlreadyExecuted);
-        require!(
-            multisig.owners.contains(&approver.key()),
-            PiDaoError::UnauthorizedAccess
-        );
         require!(
             !upgrade.approved_by.contains(&approver.key()),
             PiDaoError::AlreadyApproved
@@ -313,6 +557,7 @@ pub mod pidaosphere {
     }
 
     pub fn finalize_fundraise(ctx: Context<FinalizeFundraise>) -> Result<()> {
+        let fundraise_fee_bps = ctx.accounts.platform.fundraise_fee_bps;
         let project = &mut ctx.accounts.project;
         let current_time = Clock::get()?.unix_timestamp;
 
@@ -338,16 +583,21 @@ pub mod pidaosphere {
             return Ok(());
         }
 
+        // The platform fee comes off the top; it stays in the treasury until collected
+        let protocol_fee = fee_on(project.total_investment, fundraise_fee_bps)?;
+        let net_investment = project.total_investment.checked_sub(protocol_fee).unwrap();
+
         // Calculate fund split (90/10)
-        let liquidity_amount = project.total_investment
+        let liquidity_amount = net_investment
             .checked_mul(10)
             .unwrap()
             .checked_div(100)
             .unwrap();
-        let investment_amount = project.total_investment.checked_sub(liquidity_amount).unwrap();
+        let investment_amount = net_investment.checked_sub(liquidity_amount).unwrap();
 
         project.liquidity_amount = liquidity_amount;
         project.investment_amount = investment_amount;
+        project.protocol_fee_owed = protocol_fee;
         project.status = ProjectStatus::Active;
 
         emit!(FundraiseFinalized {
@@ -359,6 +609,52 @@ pub mod pidaosphere {
         Ok(())
     }
 
+    // Abort a raise, sending the project straight into refunds
+    pub fn cancel_fundraise(ctx: Context<CancelFundraise>, reason_code: u8) -> Result<()> {
+        let project = &ctx.accounts.project;
+        let canceller = ctx.accounts.canceller.key();
+
+        require!(
+            project.status == ProjectStatus::Fundraising,
+            PiDaoError::InvalidProjectState
+        );
+
+        // Guardian or multisig may cancel at any time
+        let multisig_approved = match &ctx.accounts.multisig {
+            Some(multisig) => {
+                let signers = transaction_signers(ctx.remaining_accounts, canceller);
+                multisig.signed_approvals(&signers) >= multisig.threshold
+            }
+            None => false,
+        };
+        let governance_approved = project.guardian == Some(canceller) || multisig_approved;
+
+        // The creator may only back out before anyone has invested
+        if !governance_approved {
+            require!(
+                canceller == project.authority,
+                PiDaoError::UnauthorizedAccess
+            );
+            require!(
+                project.total_investors == 0,
+                PiDaoError::InvestmentsAlreadyMade
+            );
+        }
+
+        let project = &mut ctx.accounts.project;
+        project.status = ProjectStatus::Refunding;
+        project.refunds_enabled = true;
+
+        emit!(FundraiseCancelled {
+            project: project.key(),
+            cancelled_by: canceller,
+            reason_code,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
         let project = &mut ctx.accounts.project;
         
@@ -382,28 +678,46 @@ pub mod pidaosphere {
         ctx: Context<RedeemTokens>,
         amount: u64,
     ) -> Result<()> {
-        let project = &mut ctx.accounts.project;
+        let project = &ctx.accounts.project;
         let current_time = Clock::get()?.unix_timestamp;
 
+        // Redemption opens once the project has been expired by the crank
         require!(
-            current_time > project.expiration_time,
+            project.status == ProjectStatus::Expired,
             PiDaoError::ProjectNotExpired
         );
+        require!(amount > 0, PiDaoError::InvalidRedemptionAmount);
+
+        // Pro-rata share of the treasury against the supply in holders' hands
+        // before the burn. Tokens still vesting or locked in the program's
+        // vaults take no share, and the platform's uncollected fee is not the
+        // redeemers' to share
+        let circulating_supply = ctx.accounts.project_token_mint.supply
+            .saturating_sub(ctx.accounts.vesting_vault.amount)
+            .saturating_sub(ctx.accounts.investor_vault.amount);
+        let treasury_balance = ctx.accounts.treasury_vault.amount
+            .saturating_sub(project.protocol_fee_owed);
         require!(
-            project.status == ProjectStatus::Active,
-            PiDaoError::InvalidProjectState
+            amount <= circulating_supply,
+            PiDaoError::InvalidRedemptionAmount
         );
 
-        // Calculate redemption amount
-        let redemption_amount = amount
-            .checked_mul(project.current_pi_price)
-            .ok_or(PiDaoError::Overflow)?;
+        // The final redeemer sweeps any rounding dust left in the vault
+        let redemption_amount = if amount == circulating_supply {
+            treasury_balance
+        } else {
+            (amount as u128)
+                .checked_mul(treasury_balance as u128)
+                .ok_or(PiDaoError::Overflow)?
+                .checked_div(circulating_supply as u128)
+                .ok_or(PiDaoError::Overflow)? as u64
+        };
 
         // Burn project tokens
-        token::burn(
+        token_interface::burn(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                token::Burn {
+                token_interface::Burn {
                     mint: ctx.accounts.project_token_mint.to_account_info(),
                     from: ctx.accounts.user_token_account.to_account_info(),
                     authority: ctx.accounts.investor.to_account_info(),
@@ -412,7 +726,31 @@ pub mod pidaosphere {
             amount,
         )?;
 
-        // TODO: Implement Pi token transfer for redemption
+        // Pay out the redemption share from the treasury vault
+        if redemption_amount > 0 {
+            let project_key = project.key();
+            let signer_seeds: &[&[u8]] = &[
+                b"project_signer",
+                project_key.as_ref(),
+                &[project.signer_bump],
+            ];
+
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.quote_token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: ctx.accounts.treasury_vault.to_account_info(),
+                        mint: ctx.accounts.quote_mint.to_account_info(),
+                        to: ctx.accounts.user_quote_account.to_account_info(),
+                        authority: ctx.accounts.project_signer.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                redemption_amount,
+                ctx.accounts.quote_mint.decimals,
+            )?;
+        }
+        ctx.accounts.project.record_burn(amount)?;
 
         emit!(TokensRedeemed {
             project: ctx.accounts.project.key(),
@@ -424,151 +762,1567 @@ pub mod pidaosphere {
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct InitializeProject<'info> {
-    #[account(init, payer = authority, space = 8 + ProjectState::SIZE)]
-    pub project: Account<'info, ProjectState>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub project_token_mint: Account<'info, Mint>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-}
+    // Permissionless crank that moves an expired project into redemption
+    pub fn expire_project(ctx: Context<ExpireProject>) -> Result<()> {
+        let project = &ctx.accounts.project;
+        let current_time = Clock::get()?.unix_timestamp;
 
-#[derive(Accounts)]
-pub struct Invest<'info> {
-    #[account(mut)]
-    pub project: Account<'info, ProjectState>,
-    #[account(mut)]
-    pub investor: Signer<'info>,
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub project_token_mint: Account<'info, Mint>,
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-}
+        require!(
+            current_time > project.expiration_time,
+            PiDaoError::ProjectNotExpired
+        );
+        require!(
+            project.status == ProjectStatus::Active,
+            PiDaoError::InvalidProjectState
+        );
 
-#[derive(Accounts)]
-pub struct CreateProposal<'info> {
-    #[account(init, payer = creator, space = 8 + ProposalState::SIZE)]
-    pub proposal: Account<'info, ProposalState>,
-    pub project: Account<'info, ProjectState>,
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        // LP the project holds in its own pool is withdrawn: the quote side
+        // goes back to the treasury and the project tokens are burned
+        let (liquidity_returned, project_tokens_burned) =
+            withdraw_project_liquidity(ctx.accounts)?;
 
-#[derive(Accounts)]
-pub struct CastVote<'info> {
-    #[account(mut)]
-    pub proposal: Account<'info, ProposalState>,
-    pub voter: Signer<'info>,
-    pub voter_token_account: Account<'info, TokenAccount>,
-}
+        // The liquidity allocation set aside at finalize is redeemable again
+        let project = &mut ctx.accounts.project;
+        project.record_burn(project_tokens_burned)?;
+        project.investment_amount = project.investment_amount
+            .checked_add(project.liquidity_amount)
+            .ok_or(PiDaoError::Overflow)?;
+        project.liquidity_amount = 0;
+        project.status = ProjectStatus::Expired;
 
-#[derive(Accounts)]
-pub struct EmergencyAction<'info> {
-    #[account(mut)]
-    pub project: Account<'info, ProjectState>,
-    pub admin: Signer<'info>,
-}
+        emit!(ProjectExpired {
+            project: ctx.accounts.project.key(),
+            liquidity_returned,
+            project_tokens_burned,
+            timestamp: current_time,
+        });
 
-#[derive(Accounts)]
-pub struct InitializeMultisig<'info> {
-    #[account(init, payer = payer, space = 8 + MultisigState::SIZE)]
-    pub multisig: Account<'info, MultisigState>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct ProposeUpgrade<'info> {
-    #[account(init, payer = proposer, space = 8 + UpgradeProposal::SIZE)]
-    pub upgrade_proposal: Account<'info, UpgradeProposal>,
-    pub multisig: Account<'info, MultisigState>,
-    #[account(mut)]
-    pub proposer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    // Record the account holding the project's LP in its own pool, so expiry
+    // can only go through with that LP withdrawn
+    pub fn register_project_liquidity(ctx: Context<RegisterProjectLiquidity>) -> Result<()> {
+        let project = &ctx.accounts.project;
 
-#[derive(Accounts)]
-pub struct ApproveUpgrade<'info> {
-    #[account(mut)]
-    pub upgrade_proposal: Account<'info, UpgradeProposal>,
-    pub multisig: Account<'info, MultisigState>,
-    pub approver: Signer<'info>,
-}
+        require!(
+            project.status == ProjectStatus::Fundraising || project.status == ProjectStatus::Active,
+            PiDaoError::InvalidProjectState
+        );
+        require!(
+            project.lp_account == Pubkey::default(),
+            PiDaoError::ProjectLiquidityRegistered
+        );
+        let pool = ctx.accounts.pool.load()?;
+        check_project_pool(project, ctx.accounts.pool.key(), &pool)?;
+        require!(
+            ctx.accounts.project_lp_account.mint == pool.lp_token_mint,
+            PoolError::InvalidProjectPool
+        );
 
-#[derive(Accounts)]
-pub struct ExecuteUpgrade<'info> {
-    #[account(mut)]
-    pub upgrade_proposal: Account<'info, UpgradeProposal>,
-    pub multisig: Account<'info, MultisigState>,
-    #[account(mut)]
-    pub executor: Signer<'info>,
-}
+        let project = &mut ctx.accounts.project;
+        project.lp_account = ctx.accounts.project_lp_account.key();
 
-#[derive(Accounts)]
-pub struct FinalizeFundraise<'info> {
-    #[account(mut)]
-    pub project: Account<'info, ProjectState>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        emit!(ProjectLiquidityRegistered {
+            project: project.key(),
+            pool: ctx.accounts.pool.key(),
+            lp_account: project.lp_account,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-#[derive(Accounts)]
-pub struct ClaimRefund<'info> {
-    #[account(mut)]
-    pub project: Account<'info, ProjectState>,
-    #[account(mut)]
-    pub investor: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct RedeemTokens<'info> {
-    #[account(mut)]
-    pub project: Account<'info, ProjectState>,
-    #[account(mut)]
-    pub investor: Signer<'info>,
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub project_token_mint: Account<'info, Mint>,
-    pub token_program: Program<'info, Token>,
-}
+    // Close a fully redeemed project and every account it owns, returning the
+    // rent to the authority
+    pub fn close_project(ctx: Context<CloseProject>) -> Result<()> {
+        let project = &ctx.accounts.project;
 
-#[account]
-pub struct ProjectState {
-    pub is_initialized: bool,
-    pub authority: Pubkey,
-    pub project_token_mint: Pubkey,
-    pub total_supply: u64,
-    pub initial_pi_price: u64,
-    pub current_pi_price: u64,
-    pub start_time: i64,
-    pub fundraise_end_time: i64,
-    pub expiration_time: i64,
-    pub min_raise: u64,
-    pub max_raise: u64,
-    pub min_investment: u64,
+        require!(
+            project.status == ProjectStatus::Expired,
+            PiDaoError::InvalidProjectState
+        );
+        require!(
+            ctx.accounts.project_token_mint.supply == 0
+                && ctx.accounts.treasury_vault.amount == 0,
+            PiDaoError::OutstandingRedemptions
+        );
+
+        let project_key = project.key();
+        let signer_seeds: &[&[u8]] = &[
+            b"project_signer",
+            project_key.as_ref(),
+            &[project.signer_bump],
+        ];
+
+        // With no supply left every vault is empty; the vesting state and
+        // registry entry are closed by their account constraints
+        for (token_program, vault) in [
+            (&ctx.accounts.quote_token_program, &ctx.accounts.treasury_vault),
+            (&ctx.accounts.token_program, &ctx.accounts.vesting_vault),
+            (&ctx.accounts.token_program, &ctx.accounts.investor_vault),
+        ] {
+            token_interface::close_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: vault.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: ctx.accounts.project_signer.to_account_info(),
+                },
+                &[signer_seeds],
+            ))?;
+        }
+
+        emit!(ProjectClosed {
+            project: project_key,
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Release the vested portion of a creator or team allocation
+    pub fn claim_vested(ctx: Context<ClaimVested>, schedule_index: u8) -> Result<()> {
+        let project = &ctx.accounts.project;
+        let vesting = &mut ctx.accounts.vesting;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(
+            project.status == ProjectStatus::Active || project.status == ProjectStatus::Expired,
+            PiDaoError::InvalidProjectState
+        );
+
+        let schedule = vesting
+            .schedules
+            .get_mut(schedule_index as usize)
+            .ok_or(PiDaoError::InvalidVestingSchedule)?;
+        require!(
+            schedule.beneficiary == ctx.accounts.beneficiary.key(),
+            PiDaoError::UnauthorizedAccess
+        );
+
+        let claimable = schedule
+            .vested_amount(current_time)?
+            .checked_sub(schedule.claimed_amount)
+            .ok_or(PiDaoError::Overflow)?;
+        require!(claimable > 0, PiDaoError::NothingToClaim);
+        schedule.claimed_amount = schedule.claimed_amount
+            .checked_add(claimable)
+            .ok_or(PiDaoError::Overflow)?;
+
+        let project_key = project.key();
+        let signer_seeds: &[&[u8]] = &[
+            b"project_signer",
+            project_key.as_ref(),
+            &[project.signer_bump],
+        ];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.vesting_vault.to_account_info(),
+                    mint: ctx.accounts.project_token_mint.to_account_info(),
+                    to: ctx.accounts.beneficiary_token_account.to_account_info(),
+                    authority: ctx.accounts.project_signer.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            claimable,
+            ctx.accounts.project_token_mint.decimals,
+        )?;
+
+        emit!(VestingClaimed {
+            project: project_key,
+            beneficiary: ctx.accounts.beneficiary.key(),
+            amount: claimable,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    // Revoke the unvested remainder of a revocable allocation
+    pub fn revoke_vesting(ctx: Context<RevokeVesting>, schedule_index: u8) -> Result<()> {
+        let project = &ctx.accounts.project;
+        let multisig = &ctx.accounts.multisig;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // Only the project's governance multisig may revoke
+        let signers = transaction_signers(ctx.remaining_accounts, ctx.accounts.revoker.key());
+        require!(
+            multisig.signed_approvals(&signers) >= multisig.threshold,
+            PiDaoError::InsufficientApprovals
+        );
+
+        let vesting = &mut ctx.accounts.vesting;
+        let schedule = vesting
+            .schedules
+            .get_mut(schedule_index as usize)
+            .ok_or(PiDaoError::InvalidVestingSchedule)?;
+        require!(schedule.revocable, PiDaoError::VestingNotRevocable);
+        require!(!schedule.revoked, PiDaoError::VestingNotRevocable);
+
+        // Vested tokens stay claimable, the remainder is burned
+        let vested = schedule.vested_amount(current_time)?;
+        let unvested = schedule.total_amount
+            .checked_sub(vested)
+            .ok_or(PiDaoError::Overflow)?;
+        schedule.total_amount = vested;
+        schedule.revoked = true;
+
+        let project_key = project.key();
+        let signer_seeds: &[&[u8]] = &[
+            b"project_signer",
+            project_key.as_ref(),
+            &[project.signer_bump],
+        ];
+
+        if unvested > 0 {
+            token_interface::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::Burn {
+                        mint: ctx.accounts.project_token_mint.to_account_info(),
+                        from: ctx.accounts.vesting_vault.to_account_info(),
+                        authority: ctx.accounts.project_signer.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                unvested,
+            )?;
+            ctx.accounts.project.record_burn(unvested)?;
+        }
+
+        emit!(VestingRevoked {
+            project: project_key,
+            beneficiary: schedule.beneficiary,
+            unvested_amount: unvested,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    // Release unlocked investor tokens from the lockup vault
+    pub fn claim_unlocked(ctx: Context<ClaimUnlocked>) -> Result<()> {
+        let project = &ctx.accounts.project;
+        let investor_lock = &mut ctx.accounts.investor_lock;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let lockup = project.lockup.as_ref().ok_or(PiDaoError::InvalidProjectState)?;
+        require!(
+            project.status == ProjectStatus::Active || project.status == ProjectStatus::Expired,
+            PiDaoError::InvalidProjectState
+        );
+
+        let claimable = lockup
+            .unlocked_amount(investor_lock.total_amount, project.fundraise_end_time, current_time)?
+            .checked_sub(investor_lock.claimed_amount)
+            .ok_or(PiDaoError::Overflow)?;
+        require!(claimable > 0, PiDaoError::NothingToClaim);
+        investor_lock.claimed_amount = investor_lock.claimed_amount
+            .checked_add(claimable)
+            .ok_or(PiDaoError::Overflow)?;
+
+        let project_key = project.key();
+        let signer_seeds: &[&[u8]] = &[
+            b"project_signer",
+            project_key.as_ref(),
+            &[project.signer_bump],
+        ];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.investor_vault.to_account_info(),
+                    mint: ctx.accounts.project_token_mint.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: ctx.accounts.project_signer.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            claimable,
+            ctx.accounts.project_token_mint.decimals,
+        )?;
+
+        emit!(TokensUnlocked {
+            project: project_key,
+            investor: ctx.accounts.investor.key(),
+            amount: claimable,
+            timestamp: current_time,
+        });
+
+        Ok(())
+    }
+
+    // Rewrite a project account from an older layout at the current version
+    pub fn migrate_project(ctx: Context<MigrateProject>) -> Result<()> {
+        let project = ctx.accounts.project.to_account_info();
+        let migrated = migration::upgrade_project(&project.try_borrow_data()?)?;
+        migration::write_account(
+            &project,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            8 + ProjectState::INIT_SPACE,
+            &migrated,
+        )?;
+
+        emit!(AccountMigrated {
+            account: project.key(),
+            version: ProjectState::VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Rewrite a proposal account from an older layout at the current version
+    pub fn migrate_proposal(ctx: Context<MigrateProposal>) -> Result<()> {
+        let proposal = ctx.accounts.proposal.to_account_info();
+        let migrated = migration::upgrade_proposal(&proposal.try_borrow_data()?)?;
+        migration::resize_account(
+            &proposal,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            8 + std::mem::size_of::<ProposalState>(),
+        )?;
+        migration::write_zero_copy(&proposal, &migrated)?;
+
+        emit!(AccountMigrated {
+            account: proposal.key(),
+            version: ProposalState::VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Rewrite the platform config from an older layout at the current version
+    pub fn migrate_platform_config(ctx: Context<MigratePlatformConfig>) -> Result<()> {
+        let platform = ctx.accounts.platform.to_account_info();
+        let migrated = migration::upgrade_platform_config(&platform.try_borrow_data()?)?;
+        migration::write_account(
+            &platform,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            8 + PlatformConfig::INIT_SPACE,
+            &migrated,
+        )?;
+
+        emit!(AccountMigrated {
+            account: platform.key(),
+            version: PlatformConfig::VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Create the platform singleton, administered by the given multisig
+    pub fn initialize_platform(
+        ctx: Context<InitializePlatform>,
+        fee_recipient: Pubkey,
+        fundraise_fee_bps: u16,
+        swap_protocol_fee_bps: u16,
+        allowed_quote_mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let signers = transaction_signers(ctx.remaining_accounts, ctx.accounts.payer.key());
+        require!(
+            multisig.signed_approvals(&signers) >= multisig.threshold,
+            PiDaoError::InsufficientApprovals
+        );
+        PlatformConfig::validate_fees(fundraise_fee_bps, swap_protocol_fee_bps)?;
+        require!(
+            allowed_quote_mints.len() <= MAX_QUOTE_MINTS,
+            PiDaoError::TooManyQuoteMints
+        );
+
+        let platform = &mut ctx.accounts.platform;
+        platform.version = PlatformConfig::VERSION;
+        platform.bump = ctx.bumps.platform;
+        platform.is_paused = false;
+        platform.multisig = multisig.key();
+        platform.fee_recipient = fee_recipient;
+        platform.fundraise_fee_bps = fundraise_fee_bps;
+        platform.swap_protocol_fee_bps = swap_protocol_fee_bps;
+        platform.project_count = 0;
+        platform.allowed_quote_mints = allowed_quote_mints;
+
+        emit!(PlatformFeesUpdated {
+            fee_recipient,
+            fundraise_fee_bps,
+            swap_protocol_fee_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Change the fee settings; needs the platform multisig's threshold of signers
+    pub fn update_platform_fees(
+        ctx: Context<PlatformAdmin>,
+        fee_recipient: Pubkey,
+        fundraise_fee_bps: u16,
+        swap_protocol_fee_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.require_approval(ctx.remaining_accounts)?;
+        PlatformConfig::validate_fees(fundraise_fee_bps, swap_protocol_fee_bps)?;
+
+        let platform = &mut ctx.accounts.platform;
+        platform.fee_recipient = fee_recipient;
+        platform.fundraise_fee_bps = fundraise_fee_bps;
+        platform.swap_protocol_fee_bps = swap_protocol_fee_bps;
+
+        emit!(PlatformFeesUpdated {
+            fee_recipient,
+            fundraise_fee_bps,
+            swap_protocol_fee_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Replace the set of quote mints new projects may raise in
+    pub fn set_allowed_quote_mints(
+        ctx: Context<PlatformAdmin>,
+        allowed_quote_mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.require_approval(ctx.remaining_accounts)?;
+        require!(
+            allowed_quote_mints.len() <= MAX_QUOTE_MINTS,
+            PiDaoError::TooManyQuoteMints
+        );
+
+        ctx.accounts.platform.allowed_quote_mints = allowed_quote_mints;
+
+        Ok(())
+    }
+
+    // Set the key that attests Pi-verified wallets for launch allowlists
+    pub fn set_pi_verifier(ctx: Context<PlatformAdmin>, pi_verifier: Pubkey) -> Result<()> {
+        ctx.accounts.require_approval(ctx.remaining_accounts)?;
+        ctx.accounts.platform.pi_verifier = pi_verifier;
+
+        emit!(PiVerifierUpdated {
+            pi_verifier,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn verify_pi_wallet(ctx: Context<VerifyPiWallet>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let verification = &mut ctx.accounts.pi_verification;
+        verification.version = PiVerification::VERSION;
+        verification.bump = ctx.bumps.pi_verification;
+        verification.wallet = ctx.accounts.wallet.key();
+        verification.verifier = ctx.accounts.verifier.key();
+        verification.verified_at = now;
+
+        emit!(PiWalletVerified {
+            wallet: verification.wallet,
+            verifier: verification.verifier,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    pub fn revoke_pi_verification(ctx: Context<RevokePiVerification>) -> Result<()> {
+        emit!(PiVerificationRevoked {
+            wallet: ctx.accounts.pi_verification.wallet,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Platform-wide pause; halts launches, investments and swaps
+    pub fn pause_platform(ctx: Context<PlatformAdmin>) -> Result<()> {
+        ctx.accounts.require_approval(ctx.remaining_accounts)?;
+        ctx.accounts.platform.is_paused = true;
+
+        emit!(PlatformPaused {
+            admin: ctx.accounts.signer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn unpause_platform(ctx: Context<PlatformAdmin>) -> Result<()> {
+        ctx.accounts.require_approval(ctx.remaining_accounts)?;
+        ctx.accounts.platform.is_paused = false;
+
+        emit!(PlatformUnpaused {
+            admin: ctx.accounts.signer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Permissionless crank that pays a project's fundraise fee to the fee recipient
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        let project = &ctx.accounts.project;
+
+        // Never take more than the treasury holds; the rest stays owed
+        let amount = project.protocol_fee_owed.min(ctx.accounts.treasury_vault.amount);
+        require!(amount > 0, PiDaoError::NothingToClaim);
+
+        let project_key = project.key();
+        let signer_seeds: &[&[u8]] = &[
+            b"project_signer",
+            project_key.as_ref(),
+            &[project.signer_bump],
+        ];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.quote_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.treasury_vault.to_account_info(),
+                    mint: ctx.accounts.quote_mint.to_account_info(),
+                    to: ctx.accounts.fee_recipient_account.to_account_info(),
+                    authority: ctx.accounts.project_signer.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
+            ctx.accounts.quote_mint.decimals,
+        )?;
+
+        let project = &mut ctx.accounts.project;
+        project.protocol_fee_owed -= amount;
+
+        emit!(ProtocolFeesCollected {
+            source: project_key,
+            mint: ctx.accounts.quote_mint.key(),
+            recipient: ctx.accounts.fee_recipient_account.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // Liquidity pools; the handlers live in `pool`
+    pub fn initialize_pool(ctx: Context<InitializePool>, fee: u64, curve: PoolCurve) -> Result<()> {
+        pool::handle_initialize_pool(ctx, fee, curve)
+    }
+
+    pub fn add_liquidity(
+        ctx: Context<AddLiquidity>,
+        max_amount_a: u64,
+        max_amount_b: u64,
+        min_lp_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        pool::handle_add_liquidity(ctx, max_amount_a, max_amount_b, min_lp_out, deadline)
+    }
+
+    pub fn remove_liquidity(
+        ctx: Context<RemoveLiquidity>,
+        lp_amount: u64,
+        min_a_out: u64,
+        min_b_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        pool::handle_remove_liquidity(ctx, lp_amount, min_a_out, min_b_out, deadline)
+    }
+
+    pub fn swap(
+        ctx: Context<Swap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        pool::handle_swap(ctx, amount_in, min_amount_out, deadline)
+    }
+
+    // Multi-hop swap; each hop's pool accounts go in remaining_accounts, see
+    // pool::RouteSwap for their order
+    pub fn route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        pool::handle_route_swap(ctx, amount_in, min_amount_out, deadline)
+    }
+
+    // Borsh-era pools cannot be migrated, see pool::handle_migrate_pool
+    pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+        pool::handle_migrate_pool(ctx)
+    }
+
+    pub fn collect_pool_protocol_fees(ctx: Context<CollectPoolProtocolFees>) -> Result<()> {
+        pool::handle_collect_pool_protocol_fees(ctx)
+    }
+
+    // StableSwap amplification changes; need the platform multisig's threshold of signers
+    pub fn ramp_pool_amp(ctx: Context<PoolGovernance>, target_amp: u64, ramp_end: i64) -> Result<()> {
+        pool::handle_ramp_pool_amp(ctx, target_amp, ramp_end)
+    }
+
+    pub fn stop_pool_amp_ramp(ctx: Context<PoolGovernance>) -> Result<()> {
+        pool::handle_stop_pool_amp_ramp(ctx)
+    }
+
+    // Volatility-scaled swap fees; also governed by the platform multisig
+    pub fn enable_pool_dynamic_fee(ctx: Context<PoolGovernance>, min_fee: u64, max_fee: u64) -> Result<()> {
+        pool::handle_enable_pool_dynamic_fee(ctx, min_fee, max_fee)
+    }
+
+    pub fn disable_pool_dynamic_fee(ctx: Context<PoolGovernance>) -> Result<()> {
+        pool::handle_disable_pool_dynamic_fee(ctx)
+    }
+
+    // Anti-snipe limits for a pool's first slots; set by the launched token's
+    // project before seeding, or once on a pool someone else seeded
+    pub fn configure_launch_guards(
+        ctx: Context<ConfigureLaunchGuards>,
+        params: Option<LaunchGuardParams>,
+    ) -> Result<()> {
+        pool::handle_configure_launch_guards(ctx, params)
+    }
+
+    // For pools created before the price oracle
+    pub fn initialize_pool_observations(ctx: Context<InitializePoolObservations>) -> Result<()> {
+        oracle::handle_initialize_pool_observations(ctx)
+    }
+
+    // Windows reach back at most OBSERVATION_CAPACITY observation intervals,
+    // about two hours at the default one-minute spacing
+    pub fn get_twap(ctx: Context<GetTwap>, window: u32) -> Result<Twap> {
+        oracle::handle_get_twap(ctx, window)
+    }
+
+    // Spacing between a pool's stored observations, which bounds how far back
+    // get_twap can look; governed by the platform multisig
+    pub fn set_pool_observation_interval(
+        ctx: Context<SetObservationInterval>,
+        interval_minutes: u16,
+    ) -> Result<()> {
+        oracle::handle_set_pool_observation_interval(ctx, interval_minutes)
+    }
+
+    // Concentrated-liquidity pools; `sqrt_price` is Q64.64
+    pub fn initialize_cl_pool(
+        ctx: Context<InitializeClPool>,
+        tick_spacing: u16,
+        fee: u64,
+        sqrt_price: u128,
+    ) -> Result<()> {
+        clmm::handle_initialize_cl_pool(ctx, tick_spacing, fee, sqrt_price)
+    }
+
+    pub fn initialize_tick_array(ctx: Context<InitializeTickArray>, start_tick_index: i32) -> Result<()> {
+        clmm::handle_initialize_tick_array(ctx, start_tick_index)
+    }
+
+    pub fn open_cl_position(ctx: Context<OpenClPosition>, tick_lower: i32, tick_upper: i32) -> Result<()> {
+        clmm::handle_open_cl_position(ctx, tick_lower, tick_upper)
+    }
+
+    pub fn increase_cl_liquidity(
+        ctx: Context<ModifyClPosition>,
+        liquidity: u128,
+        max_amount_a: u64,
+        max_amount_b: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        clmm::handle_increase_cl_liquidity(ctx, liquidity, max_amount_a, max_amount_b, deadline)
+    }
+
+    pub fn decrease_cl_liquidity(
+        ctx: Context<ModifyClPosition>,
+        liquidity: u128,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        clmm::handle_decrease_cl_liquidity(ctx, liquidity, min_amount_a, min_amount_b, deadline)
+    }
+
+    pub fn collect_cl_position_fees(ctx: Context<ModifyClPosition>) -> Result<()> {
+        clmm::handle_collect_cl_position_fees(ctx)
+    }
+
+    pub fn close_cl_position(_ctx: Context<CloseClPosition>) -> Result<()> {
+        Ok(())
+    }
+
+    // Tick arrays the swap may cross go in remaining_accounts, in swap order;
+    // a `sqrt_price_limit` of zero means no limit
+    pub fn cl_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClSwap<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+        sqrt_price_limit: u128,
+        deadline: i64,
+    ) -> Result<()> {
+        clmm::handle_cl_swap(ctx, amount_in, min_amount_out, sqrt_price_limit, deadline)
+    }
+
+    pub fn collect_cl_protocol_fees(ctx: Context<CollectClProtocolFees>) -> Result<()> {
+        clmm::handle_collect_cl_protocol_fees(ctx)
+    }
+}
+
+// Basis-point share of an amount, rounded down
+pub fn fee_on(amount: u64, fee_bps: u16) -> Result<u64> {
+    Ok(((amount as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(PiDaoError::Overflow)?
+        / BPS_DENOMINATOR as u128) as u64)
+}
+
+// Collect the keys of every signer on the transaction
+fn transaction_signers(remaining_accounts: &[AccountInfo], signer: Pubkey) -> Vec<Pubkey> {
+    remaining_accounts
+        .iter()
+        .filter(|account| account.is_signer)
+        .map(|account| account.key())
+        .chain(std::iter::once(signer))
+        .collect()
+}
+
+#[derive(Accounts)]
+#[instruction(config: ProjectConfig, token_config: TokenConfig)]
+pub struct InitializeProject<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProjectState::INIT_SPACE,
+        seeds = [b"project", config.slug.as_bytes()],
+        bump,
+    )]
+    pub project: Account<'info, ProjectState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        constraint = !platform.is_paused @ PiDaoError::ContractPaused,
+    )]
+    pub platform: Box<Account<'info, PlatformConfig>>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProjectEntry::INIT_SPACE,
+        seeds = [b"project_entry".as_ref(), &platform.project_count.to_le_bytes()],
+        bump,
+    )]
+    pub project_entry: Box<Account<'info, ProjectEntry>>,
+    /// CHECK: PDA that signs for project-owned token accounts
+    #[account(seeds = [b"project_signer", project.key().as_ref()], bump)]
+    pub project_signer: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"project_mint", project.key().as_ref()],
+        bump,
+        mint::decimals = token_config.decimals,
+        mint::authority = project_signer,
+        mint::freeze_authority = project_signer,
+        mint::token_program = token_program,
+    )]
+    pub project_token_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: created by the token metadata program
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            token_metadata_program.key().as_ref(),
+            project_token_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub token_metadata: UncheckedAccount<'info>,
+    #[account(
+        mint::token_program = quote_token_program,
+        constraint = platform.allowed_quote_mints.contains(&quote_mint.key())
+            @ PiDaoError::QuoteMintNotAllowed,
+    )]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"treasury", project.key().as_ref()],
+        bump,
+        token::mint = quote_mint,
+        token::authority = project_signer,
+        token::token_program = quote_token_program,
+    )]
+    pub treasury_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VestingState::INIT_SPACE,
+        seeds = [b"vesting", project.key().as_ref()],
+        bump,
+    )]
+    pub vesting: Account<'info, VestingState>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vesting_vault", project.key().as_ref()],
+        bump,
+        token::mint = project_token_mint,
+        token::authority = project_signer,
+        token::token_program = token_program,
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"investor_vault", project.key().as_ref()],
+        bump,
+        token::mint = project_token_mint,
+        token::authority = project_signer,
+        token::token_program = token_program,
+    )]
+    pub investor_vault: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Invest<'info> {
+    #[account(mut)]
+    pub project: Account<'info, ProjectState>,
+    #[account(mut)]
+    pub investor: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = project_token_mint,
+        token::authority = investor,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = project.project_token_mint @ PiDaoError::InvalidMint,
+        mint::token_program = token_program,
+    )]
+    pub project_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = project.investor_vault)]
+    pub investor_vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA that holds mint authority for the project token
+    #[account(
+        seeds = [b"project_signer", project.key().as_ref()],
+        bump = project.signer_bump
+    )]
+    pub project_signer: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = investor,
+        space = 8 + InvestorLock::INIT_SPACE,
+        seeds = [b"investor_lock", project.key().as_ref(), investor.key().as_ref()],
+        bump,
+    )]
+    pub investor_lock: Option<Account<'info, InvestorLock>>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        constraint = !platform.is_paused @ PiDaoError::ContractPaused,
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + std::mem::size_of::<ProposalState>(),
+        seeds = [
+            b"proposal",
+            project.key().as_ref(),
+            &project.proposal_count.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub proposal: AccountLoader<'info, ProposalState>,
+    #[account(
+        mut,
+        seeds = [b"project", project.slug.as_bytes()],
+        bump = project.bump,
+        constraint = project.is_initialized @ PiDaoError::InvalidProjectState
+    )]
+    pub project: Account<'info, ProjectState>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut, has_one = project)]
+    pub proposal: AccountLoader<'info, ProposalState>,
+    pub project: Account<'info, ProjectState>,
+    pub voter: Signer<'info>,
+    #[account(
+        constraint = voter_token_account.mint == project.project_token_mint @ PiDaoError::InvalidTokenAccount,
+        constraint = voter_token_account.owner == voter.key() @ PiDaoError::InvalidTokenAccount,
+    )]
+    pub voter_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyAction<'info> {
+    #[account(
+        mut,
+        constraint = project.authority == admin.key() @ PiDaoError::UnauthorizedAccess
+    )]
+    pub project: Account<'info, ProjectState>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMultisig<'info> {
+    #[account(init, payer = payer, space = 8 + MultisigState::INIT_SPACE)]
+    pub multisig: Account<'info, MultisigState>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeUpgrade<'info> {
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + UpgradeProposal::INIT_SPACE,
+        seeds = [
+            b"upgrade",
+            multisig.key().as_ref(),
+            &multisig.nonce.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub upgrade_proposal: Account<'info, UpgradeProposal>,
+    #[account(
+        mut,
+        constraint = multisig.owners.contains(&proposer.key()) @ PiDaoError::UnauthorizedAccess
+    )]
+    pub multisig: Account<'info, MultisigState>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveUpgrade<'info> {
+    #[account(mut, has_one = multisig @ PiDaoError::InvalidMultisig)]
+    pub upgrade_proposal: Account<'info, UpgradeProposal>,
+    #[account(
+        constraint = multisig.owners.contains(&approver.key()) @ PiDaoError::UnauthorizedAccess
+    )]
+    pub multisig: Account<'info, MultisigState>,
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteUpgrade<'info> {
+    #[account(mut, has_one = multisig @ PiDaoError::InvalidMultisig)]
+    pub upgrade_proposal: Account<'info, UpgradeProposal>,
+    pub multisig: Account<'info, MultisigState>,
+    #[account(mut)]
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeFundraise<'info> {
+    #[account(mut, has_one = authority @ PiDaoError::UnauthorizedAccess)]
+    pub project: Account<'info, ProjectState>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(seeds = [b"platform_config"], bump = platform.bump)]
+    pub platform: Account<'info, PlatformConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelFundraise<'info> {
+    #[account(mut)]
+    pub project: Account<'info, ProjectState>,
+    #[account(
+        constraint = project.multisig == Some(multisig.key()) @ PiDaoError::InvalidMultisig
+    )]
+    pub multisig: Option<Account<'info, MultisigState>>,
+    pub canceller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"project", project.slug.as_bytes()],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, ProjectState>,
+    #[account(mut)]
+    pub investor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemTokens<'info> {
+    #[account(mut)]
+    pub project: Account<'info, ProjectState>,
+    #[account(mut)]
+    pub investor: Signer<'info>,
+    #[account(
+        mut,
+        token::mint = project_token_mint,
+        token::authority = investor,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = project.project_token_mint @ PiDaoError::InvalidMint,
+        mint::token_program = token_program,
+    )]
+    pub project_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        address = project.quote_mint @ PiDaoError::InvalidMint,
+        mint::token_program = quote_token_program,
+    )]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = quote_mint)]
+    pub user_quote_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = project.treasury_vault)]
+    pub treasury_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(seeds = [b"vesting", project.key().as_ref()], bump, has_one = project)]
+    pub vesting: Box<Account<'info, VestingState>>,
+    #[account(address = vesting.vault)]
+    pub vesting_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(address = project.investor_vault)]
+    pub investor_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: PDA that signs for the treasury vault
+    #[account(
+        seeds = [b"project_signer", project.key().as_ref()],
+        bump = project.signer_bump
+    )]
+    pub project_signer: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
+}
+
+// A project that registered LP in the pool pairing its token with its quote
+// mint must pass that pool's accounts, which are checked in
+// withdraw_project_liquidity. Projects without pool liquidity leave them all out.
+#[derive(Accounts)]
+pub struct ExpireProject<'info> {
+    #[account(
+        mut,
+        seeds = [b"project", project.slug.as_bytes()],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, ProjectState>,
+    #[account(mut, address = project.treasury_vault)]
+    pub treasury_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: PDA that owns the project's LP tokens
+    #[account(
+        seeds = [b"project_signer", project.key().as_ref()],
+        bump = project.signer_bump
+    )]
+    pub project_signer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub pool: Option<AccountLoader<'info, PoolState>>,
+    #[account(mut)]
+    pub observations: Option<AccountLoader<'info, PoolObservations>>,
+    /// CHECK: checked against the pool's authority bump
+    pub pool_authority: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub token_a: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut)]
+    pub token_b: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut)]
+    pub token_a_reserve: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub token_b_reserve: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub lp_token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(mut, address = project.lp_account @ PoolError::InvalidProjectPool)]
+    pub project_lp_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_a_program: Option<Interface<'info, TokenInterface>>,
+    pub token_b_program: Option<Interface<'info, TokenInterface>>,
+    pub lp_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterProjectLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"project", project.slug.as_bytes()],
+        bump = project.bump,
+        has_one = authority,
+    )]
+    pub project: Account<'info, ProjectState>,
+    pub authority: Signer<'info>,
+    /// CHECK: PDA that owns the project's LP tokens
+    #[account(
+        seeds = [b"project_signer", project.key().as_ref()],
+        bump = project.signer_bump
+    )]
+    pub project_signer: UncheckedAccount<'info>,
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(token::authority = project_signer)]
+    pub project_lp_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProject<'info> {
+    #[account(mut, has_one = authority, close = authority)]
+    pub project: Account<'info, ProjectState>,
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+    #[account(address = project.project_token_mint)]
+    pub project_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, address = project.treasury_vault)]
+    pub treasury_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"vesting", project.key().as_ref()],
+        bump,
+        has_one = project,
+        close = authority,
+    )]
+    pub vesting: Box<Account<'info, VestingState>>,
+    #[account(mut, address = vesting.vault)]
+    pub vesting_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = project.investor_vault)]
+    pub investor_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"project_entry".as_ref(), &project.registry_index.to_le_bytes()],
+        bump,
+        has_one = project,
+        close = authority,
+    )]
+    pub project_entry: Box<Account<'info, ProjectEntry>>,
+    /// CHECK: PDA that signs for the project's vaults
+    #[account(
+        seeds = [b"project_signer", project.key().as_ref()],
+        bump = project.signer_bump
+    )]
+    pub project_signer: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    pub project: Account<'info, ProjectState>,
+    #[account(
+        mut,
+        seeds = [b"vesting", project.key().as_ref()],
+        bump,
+        has_one = project,
+    )]
+    pub vesting: Account<'info, VestingState>,
+    #[account(mut, address = vesting.vault)]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = project.project_token_mint)]
+    pub project_token_mint: InterfaceAccount<'info, Mint>,
+    pub beneficiary: Signer<'info>,
+    #[account(
+        mut,
+        constraint = beneficiary_token_account.mint == project.project_token_mint @ PiDaoError::InvalidTokenAccount,
+        constraint = beneficiary_token_account.owner == beneficiary.key() @ PiDaoError::InvalidTokenAccount,
+    )]
+    pub beneficiary_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA that signs for the vesting vault
+    #[account(
+        seeds = [b"project_signer", project.key().as_ref()],
+        bump = project.signer_bump
+    )]
+    pub project_signer: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimUnlocked<'info> {
+    pub project: Account<'info, ProjectState>,
+    #[account(
+        mut,
+        seeds = [b"investor_lock", project.key().as_ref(), investor.key().as_ref()],
+        bump,
+        has_one = project,
+        has_one = investor,
+    )]
+    pub investor_lock: Account<'info, InvestorLock>,
+    #[account(mut, address = project.investor_vault)]
+    pub investor_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = project.project_token_mint)]
+    pub project_token_mint: InterfaceAccount<'info, Mint>,
+    pub investor: Signer<'info>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == project.project_token_mint @ PiDaoError::InvalidTokenAccount,
+        constraint = user_token_account.owner == investor.key() @ PiDaoError::InvalidTokenAccount,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA that signs for the investor vault
+    #[account(
+        seeds = [b"project_signer", project.key().as_ref()],
+        bump = project.signer_bump
+    )]
+    pub project_signer: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    #[account(mut)]
+    pub project: Account<'info, ProjectState>,
+    #[account(
+        mut,
+        seeds = [b"vesting", project.key().as_ref()],
+        bump,
+        has_one = project,
+    )]
+    pub vesting: Account<'info, VestingState>,
+    #[account(mut, address = vesting.vault)]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = project.project_token_mint)]
+    pub project_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        constraint = project.multisig == Some(multisig.key()) @ PiDaoError::InvalidMultisig
+    )]
+    pub multisig: Account<'info, MultisigState>,
+    pub revoker: Signer<'info>,
+    /// CHECK: PDA that signs for the vesting vault
+    #[account(
+        seeds = [b"project_signer", project.key().as_ref()],
+        bump = project.signer_bump
+    )]
+    pub project_signer: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProject<'info> {
+    /// CHECK: may hold an older layout; discriminator and version are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub project: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProposal<'info> {
+    /// CHECK: may hold an older layout; discriminator and version are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub proposal: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePlatformConfig<'info> {
+    /// CHECK: may hold an older layout; discriminator and version are checked in the handler
+    #[account(mut, owner = crate::ID, seeds = [b"platform_config"], bump)]
+    pub platform: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePlatform<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PlatformConfig::INIT_SPACE,
+        seeds = [b"platform_config"],
+        bump,
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    pub multisig: Account<'info, MultisigState>,
+    // Only whoever can upgrade the program may claim the platform after deploy
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ PiDaoError::UnauthorizedAccess,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlatformAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        has_one = multisig @ PiDaoError::InvalidMultisig,
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    pub multisig: Account<'info, MultisigState>,
+    pub signer: Signer<'info>,
+}
+
+impl<'info> PlatformAdmin<'info> {
+    // Platform changes need the multisig's threshold among the transaction signers
+    fn require_approval(&self, remaining_accounts: &[AccountInfo]) -> Result<()> {
+        let signers = transaction_signers(remaining_accounts, self.signer.key());
+        require!(
+            self.multisig.signed_approvals(&signers) >= self.multisig.threshold,
+            PiDaoError::InsufficientApprovals
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct VerifyPiWallet<'info> {
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        constraint = platform.pi_verifier == verifier.key() @ PiDaoError::NotPiVerifier,
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+    /// CHECK: Only used as a seed; the verifier vouches for it off-chain
+    pub wallet: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = verifier,
+        space = 8 + PiVerification::INIT_SPACE,
+        seeds = [b"pi_verification", wallet.key().as_ref()],
+        bump,
+    )]
+    pub pi_verification: Account<'info, PiVerification>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokePiVerification<'info> {
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        constraint = platform.pi_verifier == verifier.key() @ PiDaoError::NotPiVerifier,
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+    #[account(
+        mut,
+        close = verifier,
+        seeds = [b"pi_verification", pi_verification.wallet.as_ref()],
+        bump = pi_verification.bump,
+    )]
+    pub pi_verification: Account<'info, PiVerification>,
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    #[account(mut)]
+    pub project: Account<'info, ProjectState>,
+    #[account(seeds = [b"platform_config"], bump = platform.bump)]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(mut, address = project.treasury_vault)]
+    pub treasury_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        address = project.quote_mint @ PiDaoError::InvalidMint,
+        mint::token_program = quote_token_program,
+    )]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        constraint = fee_recipient_account.mint == quote_mint.key() @ PiDaoError::InvalidTokenAccount,
+        constraint = fee_recipient_account.owner == platform.fee_recipient @ PiDaoError::InvalidTokenAccount,
+    )]
+    pub fee_recipient_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: PDA that signs for project-owned token accounts
+    #[account(seeds = [b"project_signer", project.key().as_ref()], bump = project.signer_bump)]
+    pub project_signer: UncheckedAccount<'info>,
+    pub quote_token_program: Interface<'info, TokenInterface>,
+}
+
+// Upper bounds for variable-length account fields
+pub const MAX_SLUG_LEN: usize = 32; // Seeds are capped at 32 bytes
+pub const MAX_INVITE_CODE_LEN: usize = 32;
+pub const MAX_PROPOSAL_DESCRIPTION_LEN: usize = 256;
+pub const MAX_EXECUTION_DATA_LEN: usize = 32;
+pub const MAX_VESTING_SCHEDULES: usize = 10;
+pub const MAX_MULTISIG_OWNERS: usize = 10;
+pub const MAX_UPGRADE_DESCRIPTION_LEN: usize = 200;
+
+// Metaplex's limits on token metadata, checked before the metadata CPI; the
+// token name doubles as the project's title
+pub const MAX_TITLE_LEN: usize = 32;
+pub const MAX_SYMBOL_LEN: usize = 10;
+pub const MAX_URI_LEN: usize = 200;
+
+pub const MAX_QUOTE_MINTS: usize = 8;
+
+// Fees are expressed in basis points of this
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_FUNDRAISE_FEE_BPS: u16 = 1_000;
+pub const MAX_SWAP_PROTOCOL_FEE_BPS: u16 = 5_000;
+
+// Program-wide singleton: admin multisig, fees, pause switch and the project registry
+#[account]
+#[derive(InitSpace)]
+pub struct PlatformConfig {
+    pub version: u8,
+    pub bump: u8,
+    pub multisig: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub fundraise_fee_bps: u16,
+    pub swap_protocol_fee_bps: u16, // Share of each swap fee, not of the swap
+    pub is_paused: bool,
+    pub project_count: u64,
+    pub pi_verifier: Pubkey, // Attests Pi KYC for wallets; default while unset
+    pub reserved: [u8; 23],
+    // Variable length, so it follows the fixed fields rather than taking reserved space
+    #[max_len(MAX_QUOTE_MINTS)]
+    pub allowed_quote_mints: Vec<Pubkey>,
+}
+
+impl PlatformConfig {
+    pub const VERSION: u8 = 2;
+
+    pub fn validate_fees(fundraise_fee_bps: u16, swap_protocol_fee_bps: u16) -> Result<()> {
+        require!(
+            fundraise_fee_bps <= MAX_FUNDRAISE_FEE_BPS
+                && swap_protocol_fee_bps <= MAX_SWAP_PROTOCOL_FEE_BPS,
+            PiDaoError::InvalidFeeConfig
+        );
+        Ok(())
+    }
+}
+
+// On-chain record that the platform's Pi verifier has KYC-checked a wallet
+#[account]
+#[derive(InitSpace)]
+pub struct PiVerification {
+    pub version: u8,
+    pub bump: u8,
+    pub wallet: Pubkey,
+    pub verifier: Pubkey,
+    pub verified_at: i64,
+    pub reserved: [u8; 32],
+}
+
+impl PiVerification {
+    pub const VERSION: u8 = 1;
+}
+
+// Maps a registry index to its project, so launches can be listed in order
+#[account]
+#[derive(InitSpace)]
+pub struct ProjectEntry {
+    pub version: u8,
+    pub index: u64,
+    pub project: Pubkey,
+    pub created_at: i64,
+    pub reserved: [u8; 32],
+}
+
+impl ProjectEntry {
+    pub const VERSION: u8 = 1;
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProjectState {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub is_paused: bool,
+    pub bump: u8,
+    #[max_len(MAX_SLUG_LEN)]
+    pub slug: String,
+    pub authority: Pubkey,
+    pub project_token_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub treasury_vault: Pubkey,
+    pub signer_bump: u8,
+    pub total_supply: u64,
+    pub vesting_supply: u64,
+    pub sale_supply: u64,
+    pub sale_minted: u64,
+    pub minted_supply: u64,
+    pub initial_pi_price: u64,
+    pub current_pi_price: u64,
+    pub start_time: i64,
+    pub fundraise_end_time: i64,
+    pub expiration_time: i64,
+    pub min_raise: u64,
+    pub max_raise: u64,
+    pub min_investment: u64,
     pub max_investment: u64,
     pub total_investors: u64,
     pub total_investment: u64,
     pub liquidity_amount: u64,
     pub investment_amount: u64,
+    pub guardian: Option<Pubkey>,
+    pub multisig: Option<Pubkey>,
+    pub investor_vault: Pubkey,
+    pub lockup: Option<InvestorLockup>,
     pub invite_only: bool,
+    #[max_len(MAX_INVITE_CODE_LEN)]
     pub invite_code: String,
     pub refunds_enabled: bool,
+    pub proposal_count: u64,
     pub status: ProjectStatus,
+    pub protocol_fee_owed: u64,
+    pub registry_index: u64,
+    pub lp_account: Pubkey, // Default until the project registers pool liquidity
+    pub reserved: [u8; 16],
+}
+
+impl ProjectState {
+    pub const VERSION: u8 = 1;
+
+    // Track a mint against the supply cap, failing once it would be exceeded
+    pub fn record_mint(&mut self, amount: u64) -> Result<()> {
+        let minted_supply = self.minted_supply
+            .checked_add(amount)
+            .ok_or(PiDaoError::Overflow)?;
+        require!(
+            minted_supply <= self.total_supply,
+            PiDaoError::InvestmentCapReached
+        );
+        self.minted_supply = minted_supply;
+        Ok(())
+    }
+
+    // Give burned tokens back to the supply cap
+    pub fn record_burn(&mut self, amount: u64) -> Result<()> {
+        self.minted_supply = self.minted_supply
+            .checked_sub(amount)
+            .ok_or(PiDaoError::Overflow)?;
+        Ok(())
+    }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
 pub enum ProjectStatus {
     Fundraising,
     Active,
@@ -578,15 +2332,188 @@ pub enum ProjectStatus {
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct ProjectConfig {
+    pub slug: String,
     pub total_supply: u64,
+    pub sale_supply: u64,
     pub min_raise: u64,
     pub max_raise: u64,
     pub min_investment: u64,
     pub max_investment: u64,
     pub duration: i64,
+    pub guardian: Option<Pubkey>,
+    pub multisig: Option<Pubkey>,
+    pub lockup: Option<InvestorLockup>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TokenConfig {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub decimals: u8,
+    pub revoke_freeze_authority: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct InvestorLockup {
+    pub tge_unlock_bps: u16,   // Share released at launch (1/10000)
+    pub unlock_duration: i64,  // Linear release period for the remainder
+}
+
+impl InvestorLockup {
+    // Amount released by `now` for a position of `total_amount`
+    pub fn unlocked_amount(&self, total_amount: u64, tge_time: i64, now: i64) -> Result<u64> {
+        if now < tge_time {
+            return Ok(0);
+        }
+
+        let tge_amount = (total_amount as u128)
+            .checked_mul(self.tge_unlock_bps as u128)
+            .ok_or(PiDaoError::Overflow)?
+            .checked_div(10000)
+            .ok_or(PiDaoError::Overflow)? as u64;
+        let elapsed = now - tge_time;
+        if elapsed >= self.unlock_duration {
+            return Ok(total_amount);
+        }
+
+        let linear_amount = ((total_amount - tge_amount) as u128)
+            .checked_mul(elapsed as u128)
+            .ok_or(PiDaoError::Overflow)?
+            .checked_div(self.unlock_duration as u128)
+            .ok_or(PiDaoError::Overflow)? as u64;
+        Ok(tge_amount + linear_amount)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct InvestorLock {
+    pub version: u8,
+    pub project: Pubkey,
+    pub investor: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub reserved: [u8; 32],
+}
+
+impl InvestorLock {
+    pub const VERSION: u8 = 1;
+}
+
+// Zero-copy so votes update the tallies in place without a Borsh round trip
+#[account(zero_copy)]
+pub struct ProposalState {
+    pub index: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub for_votes: u64,
+    pub against_votes: u64,
+    pub execution_data: [u64; MAX_EXECUTION_DATA_LEN],
+    pub project: Pubkey,
+    pub creator: Pubkey,
+    pub description: [u8; MAX_PROPOSAL_DESCRIPTION_LEN],
+    pub description_len: u16,
+    pub execution_data_len: u8,
+    pub status: u8, // ProposalStatus
+    pub version: u8,
+    pub padding: [u8; 3],
+    pub reserved: [u8; 64],
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+impl ProposalState {
+    pub const VERSION: u8 = 2;
+
+    pub fn new(project: Pubkey, index: u64, creator: Pubkey) -> Self {
+        ProposalState {
+            index,
+            start_time: 0,
+            end_time: 0,
+            for_votes: 0,
+            against_votes: 0,
+            execution_data: [0; MAX_EXECUTION_DATA_LEN],
+            project,
+            creator,
+            description: [0; MAX_PROPOSAL_DESCRIPTION_LEN],
+            description_len: 0,
+            execution_data_len: 0,
+            status: ProposalStatus::Active as u8,
+            version: Self::VERSION,
+            padding: [0; 3],
+            reserved: [0; 64],
+        }
+    }
+
+    // Callers bound the lengths before writing
+    pub fn set_description(&mut self, description: &str) {
+        let bytes = description.as_bytes();
+        self.description[..bytes.len()].copy_from_slice(bytes);
+        self.description_len = bytes.len() as u16;
+    }
+
+    pub fn set_execution_data(&mut self, execution_data: &[u64]) {
+        self.execution_data[..execution_data.len()].copy_from_slice(execution_data);
+        self.execution_data_len = execution_data.len() as u8;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VestingAllocation {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub cliff: i64,    // Seconds after fundraise end before release starts
+    pub duration: i64, // Linear release period after the cliff
+    pub revocable: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VestingState {
+    pub version: u8,
+    pub project: Pubkey,
+    pub vault: Pubkey,
+    #[max_len(MAX_VESTING_SCHEDULES)]
+    pub schedules: Vec<VestingSchedule>,
+    pub reserved: [u8; 32],
+}
+
+impl VestingState {
+    pub const VERSION: u8 = 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct VestingSchedule {
+    pub beneficiary: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub cliff_time: i64,
+    pub end_time: i64,
+    pub revocable: bool,
+    pub revoked: bool,
+}
+
+impl VestingSchedule {
+    // Amount released by `now`: nothing before the cliff, then linear
+    pub fn vested_amount(&self, now: i64) -> Result<u64> {
+        if now < self.cliff_time {
+            return Ok(0);
+        }
+        if now >= self.end_time || self.revoked {
+            return Ok(self.total_amount);
+        }
+
+        let elapsed = (now - self.cliff_time) as u128;
+        let duration = (self.end_time - self.cliff_time) as u128;
+        let vested = (self.total_amount as u128)
+            .checked_mul(elapsed)
+            .ok_or(PiDaoError::Overflow)?
+            .checked_div(duration)
+            .ok_or(PiDaoError::Overflow)?;
+        Ok(vested as u64)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
 pub enum ProposalStatus {
     Active,
     Succeeded,
@@ -619,37 +2546,51 @@ pub struct InvestmentMade {
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct MultisigState {
+    pub version: u8,
+    #[max_len(MAX_MULTISIG_OWNERS)]
     pub owners: Vec<Pubkey>,
     pub threshold: u64,
     pub nonce: u64,
     pub owner_set_seqno: u64,
+    pub reserved: [u8; 32],
 }
 
 #[account]
+#[derive(InitSpace)]
 pub struct UpgradeProposal {
+    pub version: u8,
+    pub index: u64,
     pub proposer: Pubkey,
+    pub multisig: Pubkey,
     pub new_program_id: Pubkey,
+    #[max_len(MAX_UPGRADE_DESCRIPTION_LEN)]
     pub description: String,
+    #[max_len(MAX_MULTISIG_OWNERS)]
     pub approved_by: Vec<Pubkey>,
     pub executed: bool,
     pub created_at: i64,
+    pub reserved: [u8; 32],
 }
 
-impl MultisigState {
-    pub const SIZE: usize = 32 * 10 + // owners (max 10)
-                           8 + // threshold
-                           8 + // nonce
-                           8; // owner_set_seqno
+impl UpgradeProposal {
+    pub const VERSION: u8 = 1;
 }
 
-impl UpgradeProposal {
-    pub const SIZE: usize = 32 + // proposer
-                           32 + // new_program_id
-                           200 + // description
-                           32 * 10 + // approved_by (max 10)
-                           1 + // executed
-                           8; // created_at
+impl MultisigState {
+    pub const VERSION: u8 = 1;
+
+    // Count distinct owners among the transaction signers
+    pub fn signed_approvals(&self, signers: &[Pubkey]) -> u64 {
+        let mut approved: Vec<Pubkey> = Vec::new();
+        for signer in signers {
+            if self.owners.contains(signer) && !approved.contains(signer) {
+                approved.push(*signer);
+            }
+        }
+        approved.len() as u64
+    }
 }
 
 #[event]
@@ -681,6 +2622,14 @@ pub struct FundraiseFinalized {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct FundraiseCancelled {
+    pub project: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub reason_code: u8,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct RefundClaimed {
     pub project: Pubkey,
@@ -697,6 +2646,148 @@ pub struct TokensRedeemed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ProjectLiquidityRegistered {
+    pub project: Pubkey,
+    pub pool: Pubkey,
+    pub lp_account: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProjectExpired {
+    pub project: Pubkey,
+    pub liquidity_returned: u64,
+    pub project_tokens_burned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProjectClosed {
+    pub project: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensUnlocked {
+    pub project: Pubkey,
+    pub investor: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlatformFeesUpdated {
+    pub fee_recipient: Pubkey,
+    pub fundraise_fee_bps: u16,
+    pub swap_protocol_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PiVerifierUpdated {
+    pub pi_verifier: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PiWalletVerified {
+    pub wallet: Pubkey,
+    pub verifier: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PiVerificationRevoked {
+    pub wallet: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlatformPaused {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlatformUnpaused {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProjectRegistered {
+    pub project: Pubkey,
+    pub index: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub source: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AmpRampStarted {
+    pub pool: Pubkey,
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start: i64,
+    pub ramp_end: i64,
+}
+
+#[event]
+pub struct AmpRampStopped {
+    pub pool: Pubkey,
+    pub amp: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ObservationIntervalChanged {
+    pub pool: Pubkey,
+    pub interval_minutes: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolFeeModeChanged {
+    pub pool: Pubkey,
+    pub dynamic: bool,
+    pub min_fee: u64, // Both bounds equal the static fee when dynamic fees are off
+    pub max_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingClaimed {
+    pub project: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingRevoked {
+    pub project: Pubkey,
+    pub beneficiary: Pubkey,
+    pub unvested_amount: u64,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum PiDaoError {
     #[msg("Contract is paused")]
@@ -741,4 +2832,56 @@ pub enum PiDaoError {
     RefundsNotEnabled,
     #[msg("Project has not expired")]
     ProjectNotExpired,
+    #[msg("Invalid redemption amount")]
+    InvalidRedemptionAmount,
+    #[msg("Governance is frozen for this project")]
+    GovernanceFrozen,
+    #[msg("Project tokens remain outstanding")]
+    OutstandingRedemptions,
+    #[msg("Investments have already been made")]
+    InvestmentsAlreadyMade,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("Too many vesting schedules")]
+    TooManyVestingSchedules,
+    #[msg("Vesting schedule is not revocable")]
+    VestingNotRevocable,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Investor lockup account missing")]
+    LockupAccountMissing,
+    #[msg("Mint does not match the project")]
+    InvalidMint,
+    #[msg("Token account does not match the expected mint or owner")]
+    InvalidTokenAccount,
+    #[msg("Multisig does not match")]
+    InvalidMultisig,
+    #[msg("Project slug must be 1-32 bytes")]
+    InvalidSlug,
+    #[msg("Description exceeds maximum length")]
+    DescriptionTooLong,
+    #[msg("Invite code exceeds maximum length")]
+    InviteCodeTooLong,
+    #[msg("Title exceeds maximum length")]
+    TitleTooLong,
+    #[msg("Token symbol exceeds maximum length")]
+    SymbolTooLong,
+    #[msg("Token URI exceeds maximum length")]
+    UriTooLong,
+    #[msg("Execution data exceeds maximum length")]
+    ExecutionDataTooLong,
+    #[msg("Account is already at the current version")]
+    AlreadyMigrated,
+    #[msg("Unsupported account version")]
+    UnsupportedAccountVersion,
+    #[msg("Fee exceeds the allowed maximum")]
+    InvalidFeeConfig,
+    #[msg("Quote mint is not allowed on this platform")]
+    QuoteMintNotAllowed,
+    #[msg("Too many allowed quote mints")]
+    TooManyQuoteMints,
+    #[msg("Signer is not the platform's Pi verifier")]
+    NotPiVerifier,
+    #[msg("Project pool liquidity is already registered")]
+    ProjectLiquidityRegistered,
 } 
\ No newline at end of file
@@ -0,0 +1,295 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+
+use crate::*;
+
+// Account layouts written by earlier program versions. When a `VERSION`
+// constant is bumped, the outgoing layout is added here with a conversion
+// into the current one.
+
+// Layouts from before accounts carried a version byte
+#[derive(AnchorDeserialize)]
+pub struct ProjectStateV0 {
+    pub is_initialized: bool,
+    pub is_paused: bool,
+    pub bump: u8,
+    pub slug: String,
+    pub authority: Pubkey,
+    pub project_token_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub treasury_vault: Pubkey,
+    pub signer_bump: u8,
+    pub total_supply: u64,
+    pub vesting_supply: u64,
+    pub sale_supply: u64,
+    pub sale_minted: u64,
+    pub minted_supply: u64,
+    pub initial_pi_price: u64,
+    pub current_pi_price: u64,
+    pub start_time: i64,
+    pub fundraise_end_time: i64,
+    pub expiration_time: i64,
+    pub min_raise: u64,
+    pub max_raise: u64,
+    pub min_investment: u64,
+    pub max_investment: u64,
+    pub total_investors: u64,
+    pub total_investment: u64,
+    pub liquidity_amount: u64,
+    pub investment_amount: u64,
+    pub guardian: Option<Pubkey>,
+    pub multisig: Option<Pubkey>,
+    pub investor_vault: Pubkey,
+    pub lockup: Option<InvestorLockup>,
+    pub invite_only: bool,
+    pub invite_code: String,
+    pub refunds_enabled: bool,
+    pub proposal_count: u64,
+    pub status: ProjectStatus,
+}
+
+impl From<ProjectStateV0> for ProjectState {
+    fn from(old: ProjectStateV0) -> Self {
+        ProjectState {
+            version: ProjectState::VERSION,
+            is_initialized: old.is_initialized,
+            is_paused: old.is_paused,
+            bump: old.bump,
+            slug: old.slug,
+            authority: old.authority,
+            project_token_mint: old.project_token_mint,
+            quote_mint: old.quote_mint,
+            treasury_vault: old.treasury_vault,
+            signer_bump: old.signer_bump,
+            total_supply: old.total_supply,
+            vesting_supply: old.vesting_supply,
+            sale_supply: old.sale_supply,
+            sale_minted: old.sale_minted,
+            minted_supply: old.minted_supply,
+            initial_pi_price: old.initial_pi_price,
+            current_pi_price: old.current_pi_price,
+            start_time: old.start_time,
+            fundraise_end_time: old.fundraise_end_time,
+            expiration_time: old.expiration_time,
+            min_raise: old.min_raise,
+            max_raise: old.max_raise,
+            min_investment: old.min_investment,
+            max_investment: old.max_investment,
+            total_investors: old.total_investors,
+            total_investment: old.total_investment,
+            liquidity_amount: old.liquidity_amount,
+            investment_amount: old.investment_amount,
+            guardian: old.guardian,
+            multisig: old.multisig,
+            investor_vault: old.investor_vault,
+            lockup: old.lockup,
+            invite_only: old.invite_only,
+            invite_code: old.invite_code,
+            refunds_enabled: old.refunds_enabled,
+            proposal_count: old.proposal_count,
+            status: old.status,
+            protocol_fee_owed: 0,
+            registry_index: 0,
+            lp_account: Pubkey::default(),
+            reserved: [0; 16],
+        }
+    }
+}
+
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct ProposalStateV0 {
+    pub project: Pubkey,
+    pub index: u64,
+    pub creator: Pubkey,
+    pub start_time: i64,
+    pub end_time: i64,
+    #[max_len(MAX_PROPOSAL_DESCRIPTION_LEN)]
+    pub description: String,
+    #[max_len(MAX_EXECUTION_DATA_LEN)]
+    pub execution_data: Vec<u64>,
+    pub for_votes: u64,
+    pub against_votes: u64,
+    pub status: ProposalStatus,
+}
+
+// Versioned Borsh proposal layout, replaced by the zero-copy one
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct ProposalStateV1 {
+    pub version: u8,
+    pub project: Pubkey,
+    pub index: u64,
+    pub creator: Pubkey,
+    pub start_time: i64,
+    pub end_time: i64,
+    #[max_len(MAX_PROPOSAL_DESCRIPTION_LEN)]
+    pub description: String,
+    #[max_len(MAX_EXECUTION_DATA_LEN)]
+    pub execution_data: Vec<u64>,
+    pub for_votes: u64,
+    pub against_votes: u64,
+    pub status: ProposalStatus,
+    pub reserved: [u8; 64],
+}
+
+impl From<ProposalStateV0> for ProposalState {
+    fn from(old: ProposalStateV0) -> Self {
+        let mut proposal = ProposalState::new(old.project, old.index, old.creator);
+        proposal.start_time = old.start_time;
+        proposal.end_time = old.end_time;
+        proposal.set_description(&old.description);
+        proposal.set_execution_data(&old.execution_data);
+        proposal.for_votes = old.for_votes;
+        proposal.against_votes = old.against_votes;
+        proposal.status = old.status as u8;
+        proposal
+    }
+}
+
+impl From<ProposalStateV1> for ProposalState {
+    fn from(old: ProposalStateV1) -> Self {
+        ProposalStateV0 {
+            project: old.project,
+            index: old.index,
+            creator: old.creator,
+            start_time: old.start_time,
+            end_time: old.end_time,
+            description: old.description,
+            execution_data: old.execution_data,
+            for_votes: old.for_votes,
+            against_votes: old.against_votes,
+            status: old.status,
+        }
+        .into()
+    }
+}
+
+// Fee-only layout from before the pause switch, project registry and Pi verifier
+#[derive(AnchorDeserialize)]
+pub struct PlatformConfigV1 {
+    pub version: u8,
+    pub bump: u8,
+    pub multisig: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub fundraise_fee_bps: u16,
+    pub swap_protocol_fee_bps: u16,
+    pub reserved: [u8; 64],
+}
+
+// Migrated platforms allow no quote mints until the multisig sets them
+impl From<PlatformConfigV1> for PlatformConfig {
+    fn from(old: PlatformConfigV1) -> Self {
+        PlatformConfig {
+            version: PlatformConfig::VERSION,
+            bump: old.bump,
+            multisig: old.multisig,
+            fee_recipient: old.fee_recipient,
+            fundraise_fee_bps: old.fundraise_fee_bps,
+            swap_protocol_fee_bps: old.swap_protocol_fee_bps,
+            is_paused: false,
+            project_count: 0,
+            pi_verifier: Pubkey::default(),
+            reserved: [0; 23],
+            allowed_quote_mints: Vec::new(),
+        }
+    }
+}
+
+// Decode a project account of any known layout into the current one
+pub fn upgrade_project(data: &[u8]) -> Result<ProjectState> {
+    let mut body = account_body(data, &ProjectState::DISCRIMINATOR)?;
+    // Unversioned accounts were sized before the version and reserved bytes
+    if data.len() < 8 + ProjectState::INIT_SPACE {
+        return Ok(ProjectStateV0::deserialize(&mut body)?.into());
+    }
+    // Versioned layouts lead with their version byte; each one older than
+    // `ProjectState::VERSION` gets an arm decoding it into the current layout
+    match data[8] {
+        ProjectState::VERSION => err!(PiDaoError::AlreadyMigrated),
+        _ => err!(PiDaoError::UnsupportedAccountVersion),
+    }
+}
+
+// Decode a platform config of any known layout into the current one
+pub fn upgrade_platform_config(data: &[u8]) -> Result<PlatformConfig> {
+    let mut body = account_body(data, &PlatformConfig::DISCRIMINATOR)?;
+    match data[8] {
+        1 => Ok(PlatformConfigV1::deserialize(&mut body)?.into()),
+        PlatformConfig::VERSION => err!(PiDaoError::AlreadyMigrated),
+        _ => err!(PiDaoError::UnsupportedAccountVersion),
+    }
+}
+
+// Decode a proposal account of any known layout into the current one
+pub fn upgrade_proposal(data: &[u8]) -> Result<ProposalState> {
+    let mut body = account_body(data, &ProposalState::DISCRIMINATOR)?;
+    // Every layout was allocated at its full size, so the length identifies it
+    match data.len() {
+        len if len == 8 + ProposalStateV0::INIT_SPACE => {
+            Ok(ProposalStateV0::deserialize(&mut body)?.into())
+        }
+        len if len == 8 + ProposalStateV1::INIT_SPACE => {
+            Ok(ProposalStateV1::deserialize(&mut body)?.into())
+        }
+        len if len == 8 + std::mem::size_of::<ProposalState>() => {
+            err!(PiDaoError::AlreadyMigrated)
+        }
+        _ => err!(PiDaoError::UnsupportedAccountVersion),
+    }
+}
+
+// Resize `account` to `space`, topping up rent from `payer`, and write `state`
+pub fn write_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+    state: &T,
+) -> Result<()> {
+    resize_account(account, payer, system_program, space)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+    state.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+// Write a zero-copy `state` behind its discriminator
+pub fn write_zero_copy<T: anchor_lang::ZeroCopy + Discriminator>(account: &AccountInfo, state: &T) -> Result<()> {
+    let mut data = account.try_borrow_mut_data()?;
+    data[..8].copy_from_slice(&T::DISCRIMINATOR);
+    data[8..8 + std::mem::size_of::<T>()].copy_from_slice(bytemuck::bytes_of(state));
+    Ok(())
+}
+
+// Resize `account` to `space`, topping up rent from `payer`
+pub fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let shortfall = rent.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.realloc(space, true)?;
+    Ok(())
+}
+
+fn account_body<'a>(data: &'a [u8], discriminator: &[u8; 8]) -> Result<&'a [u8]> {
+    require!(
+        data.len() > 8 && &data[..8] == discriminator,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(&data[8..])
+}
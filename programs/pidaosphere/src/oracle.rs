@@ -0,0 +1,251 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    transaction_signers, MultisigState, ObservationIntervalChanged, PiDaoError, PlatformConfig,
+    PoolError, PoolState,
+};
+
+// Ring buffer length; with one observation per interval at most, a full buffer
+// covers CAPACITY intervals of history, a little over two hours by default
+pub const OBSERVATION_CAPACITY: usize = 128;
+// Default minimum spacing between stored observations, in seconds
+pub const OBSERVATION_INTERVAL: i64 = 60;
+// Longest spacing governance can set; a full buffer then spans about 128 days
+pub const MAX_OBSERVATION_INTERVAL_MINUTES: u16 = 24 * 60;
+
+#[derive(Accounts)]
+pub struct InitializePoolObservations<'info> {
+    #[account(
+        seeds = [b"pool", pool.load()?.token_a.as_ref(), pool.load()?.token_b.as_ref()],
+        bump = pool.load()?.bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<PoolObservations>(),
+        seeds = [b"observations", pool.key().as_ref()],
+        bump,
+    )]
+    pub observations: AccountLoader<'info, PoolObservations>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetTwap<'info> {
+    #[account(
+        seeds = [b"pool", pool.load()?.token_a.as_ref(), pool.load()?.token_b.as_ref()],
+        bump = pool.load()?.bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(seeds = [b"observations", pool.key().as_ref()], bump = observations.load()?.bump)]
+    pub observations: AccountLoader<'info, PoolObservations>,
+}
+
+#[derive(Accounts)]
+pub struct SetObservationInterval<'info> {
+    #[account(
+        seeds = [b"pool", pool.load()?.token_a.as_ref(), pool.load()?.token_b.as_ref()],
+        bump = pool.load()?.bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(mut, seeds = [b"observations", pool.key().as_ref()], bump = observations.load()?.bump)]
+    pub observations: AccountLoader<'info, PoolObservations>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        has_one = multisig @ PiDaoError::InvalidMultisig,
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    pub multisig: Account<'info, MultisigState>,
+    pub signer: Signer<'info>,
+}
+
+#[zero_copy]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_a_cumulative: [u8; 16], // u128, little-endian
+    pub price_b_cumulative: [u8; 16],
+}
+
+#[account(zero_copy)]
+pub struct PoolObservations {
+    pub pool: Pubkey,
+    pub head: u16, // Index of the newest observation
+    pub len: u16,
+    pub version: u8,
+    pub bump: u8,
+    pub interval_minutes: u16, // Zero until governance sets one; see interval()
+    pub observations: [Observation; OBSERVATION_CAPACITY],
+}
+
+impl PoolObservations {
+    pub const VERSION: u8 = 1;
+
+    pub fn init(&mut self, pool: Pubkey, bump: u8) {
+        self.version = Self::VERSION;
+        self.pool = pool;
+        self.bump = bump;
+        self.head = 0;
+        self.len = 0;
+    }
+
+    // Minimum spacing between stored observations, in seconds
+    pub fn interval(&self) -> i64 {
+        match self.interval_minutes {
+            0 => OBSERVATION_INTERVAL,
+            minutes => minutes as i64 * 60,
+        }
+    }
+
+    // Store the pool's accumulators unless the newest observation is too recent
+    pub fn record(&mut self, pool: &PoolState, now: i64) {
+        if self.len > 0 && now < self.observations[self.head as usize].timestamp + self.interval() {
+            return;
+        }
+
+        let next = if self.len == 0 {
+            0
+        } else {
+            (self.head as usize + 1) % OBSERVATION_CAPACITY
+        };
+        self.observations[next] = Observation {
+            timestamp: now,
+            price_a_cumulative: pool.price_a_cumulative,
+            price_b_cumulative: pool.price_b_cumulative,
+        };
+        self.head = next as u16;
+        self.len = std::cmp::min(self.len as usize + 1, OBSERVATION_CAPACITY) as u16;
+    }
+
+    // Newest observation taken at or before `timestamp`
+    pub fn at_or_before(&self, timestamp: i64) -> Option<&Observation> {
+        (0..self.len as usize)
+            .map(|age| {
+                let index = (self.head as usize + OBSERVATION_CAPACITY - age) % OBSERVATION_CAPACITY;
+                &self.observations[index]
+            })
+            .find(|observation| observation.timestamp <= timestamp)
+    }
+}
+
+// Prices are Q64.64 fixed point: `price_a` is token B per token A, `price_b`
+// token A per token B
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Twap {
+    pub price_a: u128,
+    pub price_b: u128,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+impl PoolState {
+    // Accumulators as of `now`, extended by the spot price held since the last
+    // update. They wrap on overflow; only differences are meaningful. The spot
+    // price is the reserve ratio, which for StableSwap pools is not the
+    // marginal price along the curve.
+    pub fn cumulative_prices_at(&self, now: i64) -> (u128, u128) {
+        let price_a_cumulative = u128::from_le_bytes(self.price_a_cumulative);
+        let price_b_cumulative = u128::from_le_bytes(self.price_b_cumulative);
+        let elapsed = now - self.last_price_update;
+        if self.last_price_update == 0
+            || elapsed <= 0
+            || self.token_a_amount == 0
+            || self.token_b_amount == 0
+        {
+            return (price_a_cumulative, price_b_cumulative);
+        }
+
+        let spot_a = ((self.token_b_amount as u128) << 64) / self.token_a_amount as u128;
+        let spot_b = ((self.token_a_amount as u128) << 64) / self.token_b_amount as u128;
+        (
+            price_a_cumulative.wrapping_add(spot_a.wrapping_mul(elapsed as u128)),
+            price_b_cumulative.wrapping_add(spot_b.wrapping_mul(elapsed as u128)),
+        )
+    }
+}
+
+// Called before a swap or liquidity change moves the reserves, so the time
+// since the last update is credited to the price that held over it
+pub fn update_oracle(
+    pool: &mut PoolState,
+    observations: &AccountLoader<PoolObservations>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let (price_a_cumulative, price_b_cumulative) = pool.cumulative_prices_at(now);
+    pool.price_a_cumulative = price_a_cumulative.to_le_bytes();
+    pool.price_b_cumulative = price_b_cumulative.to_le_bytes();
+    pool.last_price_update = now;
+
+    observations.load_mut()?.record(pool, now);
+
+    Ok(())
+}
+
+pub fn handle_initialize_pool_observations(ctx: Context<InitializePoolObservations>) -> Result<()> {
+    ctx.accounts
+        .observations
+        .load_init()?
+        .init(ctx.accounts.pool.key(), ctx.bumps.observations);
+
+    Ok(())
+}
+
+// Wider spacing trades TWAP resolution for a longer history. Observations
+// already stored are kept; the new spacing applies from the next one.
+pub fn handle_set_pool_observation_interval(
+    ctx: Context<SetObservationInterval>,
+    interval_minutes: u16,
+) -> Result<()> {
+    let signers = transaction_signers(ctx.remaining_accounts, ctx.accounts.signer.key());
+    require!(
+        ctx.accounts.multisig.signed_approvals(&signers) >= ctx.accounts.multisig.threshold,
+        PiDaoError::InsufficientApprovals
+    );
+    require!(
+        (1..=MAX_OBSERVATION_INTERVAL_MINUTES).contains(&interval_minutes),
+        PoolError::InvalidObservationInterval
+    );
+
+    ctx.accounts.observations.load_mut()?.interval_minutes = interval_minutes;
+
+    emit!(ObservationIntervalChanged {
+        pool: ctx.accounts.pool.key(),
+        interval_minutes,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Average price from the newest observation at least `window` seconds old up
+// to now; the window actually used is returned alongside the prices. The
+// longest window served is the age of the oldest stored observation, at most
+// OBSERVATION_CAPACITY times the pool's interval; longer ones fail with
+// InsufficientObservationHistory.
+pub fn handle_get_twap(ctx: Context<GetTwap>, window: u32) -> Result<Twap> {
+    require!(window > 0, PoolError::InvalidTwapWindow);
+
+    let pool = ctx.accounts.pool.load()?;
+    let observations = ctx.accounts.observations.load()?;
+    let now = Clock::get()?.unix_timestamp;
+
+    let start = observations
+        .at_or_before(now - window as i64)
+        .ok_or(PoolError::InsufficientObservationHistory)?;
+    let (price_a_cumulative, price_b_cumulative) = pool.cumulative_prices_at(now);
+    let elapsed = (now - start.timestamp) as u128;
+
+    Ok(Twap {
+        price_a: price_a_cumulative
+            .wrapping_sub(u128::from_le_bytes(start.price_a_cumulative))
+            / elapsed,
+        price_b: price_b_cumulative
+            .wrapping_sub(u128::from_le_bytes(start.price_b_cumulative))
+            / elapsed,
+        start_time: start.timestamp,
+        end_time: now,
+    })
+}
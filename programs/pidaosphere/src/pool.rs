@@ -0,0 +1,1906 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_spl::token_interface::spl_token_2022::state::Mint as MintState;
+
+use crate::{
+    fee_on, migration, mul_div_wide, transaction_signers, update_oracle, AmpRampStarted,
+    AmpRampStopped, ExpireProject, MultisigState, PiDaoError, PiVerification, PlatformConfig,
+    PoolFeeModeChanged, PoolObservations, ProjectState, ProtocolFeesCollected,
+};
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<PoolState>(),
+        seeds = [b"pool", token_a.key().as_ref(), token_b.key().as_ref()],
+        bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(mint::token_program = token_a_program)]
+    pub token_a: InterfaceAccount<'info, Mint>,
+    // Mints are passed sorted so each pair maps to a single pool address. The
+    // curve is not part of the seeds, so a pair can only have one pool of either
+    // curve type
+    #[account(
+        mint::token_program = token_b_program,
+        constraint = token_a.key() < token_b.key() @ PoolError::InvalidMint,
+    )]
+    pub token_b: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA that owns the reserves and the LP mint
+    #[account(seeds = [b"pool_authority", pool.key().as_ref()], bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"reserve_a", pool.key().as_ref()],
+        bump,
+        token::mint = token_a,
+        token::authority = pool_authority,
+        token::token_program = token_a_program,
+    )]
+    pub token_a_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"reserve_b", pool.key().as_ref()],
+        bump,
+        token::mint = token_b,
+        token::authority = pool_authority,
+        token::token_program = token_b_program,
+    )]
+    pub token_b_reserve: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"lp_mint", pool.key().as_ref()],
+        bump,
+        mint::decimals = LP_TOKEN_DECIMALS,
+        mint::authority = pool_authority,
+        mint::token_program = token_program,
+    )]
+    pub lp_token_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<PoolObservations>(),
+        seeds = [b"observations", pool.key().as_ref()],
+        bump,
+    )]
+    pub observations: AccountLoader<'info, PoolObservations>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    // Each side of the pair may live under either token program; the LP mint
+    // is created under `token_program`
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.load()?.token_a.as_ref(), pool.load()?.token_b.as_ref()],
+        bump = pool.load()?.bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(address = pool.load()?.token_a, mint::token_program = token_a_program)]
+    pub token_a: InterfaceAccount<'info, Mint>,
+    #[account(address = pool.load()?.token_b, mint::token_program = token_b_program)]
+    pub token_b: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA that owns the reserves and the LP mint
+    #[account(seeds = [b"pool_authority", pool.key().as_ref()], bump = pool.load()?.authority_bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    #[account(mut, address = pool.load()?.token_a_reserve)]
+    pub token_a_reserve: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = pool.load()?.token_b_reserve)]
+    pub token_b_reserve: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = pool.load()?.lp_token_mint, mint::token_program = token_program)]
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = token_a, token::authority = authority)]
+    pub user_token_a_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = token_b, token::authority = authority)]
+    pub user_token_b_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = lp_token_mint)]
+    pub user_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, seeds = [b"observations", pool.key().as_ref()], bump = observations.load()?.bump)]
+    pub observations: AccountLoader<'info, PoolObservations>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.load()?.token_a.as_ref(), pool.load()?.token_b.as_ref()],
+        bump = pool.load()?.bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(address = pool.load()?.token_a, mint::token_program = token_a_program)]
+    pub token_a: InterfaceAccount<'info, Mint>,
+    #[account(address = pool.load()?.token_b, mint::token_program = token_b_program)]
+    pub token_b: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA that owns the reserves and the LP mint
+    #[account(seeds = [b"pool_authority", pool.key().as_ref()], bump = pool.load()?.authority_bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    #[account(mut, address = pool.load()?.token_a_reserve)]
+    pub token_a_reserve: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = pool.load()?.token_b_reserve)]
+    pub token_b_reserve: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = pool.load()?.lp_token_mint, mint::token_program = token_program)]
+    pub lp_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = token_a, token::authority = authority)]
+    pub user_token_a_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = token_b, token::authority = authority)]
+    pub user_token_b_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = lp_token_mint, token::authority = authority)]
+    pub user_lp_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, seeds = [b"observations", pool.key().as_ref()], bump = observations.load()?.bump)]
+    pub observations: AccountLoader<'info, PoolObservations>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.load()?.token_a.as_ref(), pool.load()?.token_b.as_ref()],
+        bump = pool.load()?.bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(address = pool.load()?.token_a, mint::token_program = token_a_program)]
+    pub token_a: InterfaceAccount<'info, Mint>,
+    #[account(address = pool.load()?.token_b, mint::token_program = token_b_program)]
+    pub token_b: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA that owns the reserves and the LP mint
+    #[account(seeds = [b"pool_authority", pool.key().as_ref()], bump = pool.load()?.authority_bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    #[account(mut, address = pool.load()?.token_a_reserve)]
+    pub token_a_reserve: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = pool.load()?.token_b_reserve)]
+    pub token_b_reserve: InterfaceAccount<'info, TokenAccount>,
+    // Either side of the pair may be sold; the destination must hold the other
+    #[account(
+        mut,
+        token::authority = authority,
+        constraint = user_source_account.mint == token_a.key()
+            || user_source_account.mint == token_b.key() @ PoolError::InvalidSwapAccounts,
+    )]
+    pub user_source_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_destination_account.mint != user_source_account.mint
+            && (user_destination_account.mint == token_a.key()
+                || user_destination_account.mint == token_b.key()) @ PoolError::InvalidSwapAccounts,
+    )]
+    pub user_destination_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        constraint = !platform.is_paused @ crate::PiDaoError::ContractPaused,
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(mut, seeds = [b"observations", pool.key().as_ref()], bump = observations.load()?.bump)]
+    pub observations: AccountLoader<'info, PoolObservations>,
+    // Only needed to buy while launch guards are active
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + LaunchPurchases::INIT_SPACE,
+        seeds = [b"launch_purchases", pool.key().as_ref(), authority.key().as_ref()],
+        bump,
+    )]
+    pub launch_purchases: Option<Account<'info, LaunchPurchases>>,
+    #[account(seeds = [b"pi_verification", authority.key().as_ref()], bump = pi_verification.bump)]
+    pub pi_verification: Option<Account<'info, PiVerification>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Each hop's accounts follow in remaining_accounts, ROUTE_HOP_ACCOUNTS per hop
+// in the order: pool, observations, pool authority, input reserve, output
+// reserve, input mint, output mint, output mint's token program
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mut, token::authority = authority)]
+    pub user_source_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub user_destination_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        constraint = !platform.is_paused @ crate::PiDaoError::ContractPaused,
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    // Owner of the first hop's input mint
+    pub source_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CollectPoolProtocolFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.load()?.token_a.as_ref(), pool.load()?.token_b.as_ref()],
+        bump = pool.load()?.bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(address = pool.load()?.token_a, mint::token_program = token_a_program)]
+    pub token_a: InterfaceAccount<'info, Mint>,
+    #[account(address = pool.load()?.token_b, mint::token_program = token_b_program)]
+    pub token_b: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA that owns the reserves and the LP mint
+    #[account(seeds = [b"pool_authority", pool.key().as_ref()], bump = pool.load()?.authority_bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    #[account(mut, address = pool.load()?.token_a_reserve)]
+    pub token_a_reserve: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = pool.load()?.token_b_reserve)]
+    pub token_b_reserve: InterfaceAccount<'info, TokenAccount>,
+    #[account(seeds = [b"platform_config"], bump = platform.bump)]
+    pub platform: Account<'info, PlatformConfig>,
+    #[account(mut, token::mint = token_a, token::authority = platform.fee_recipient)]
+    pub fee_recipient_token_a: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = token_b, token::authority = platform.fee_recipient)]
+    pub fee_recipient_token_b: InterfaceAccount<'info, TokenAccount>,
+    pub token_a_program: Interface<'info, TokenInterface>,
+    pub token_b_program: Interface<'info, TokenInterface>,
+}
+
+// Anyone may create a pool, so the guards on a project token's launch are the
+// project's to set, not the pool creator's
+#[derive(Accounts)]
+pub struct ConfigureLaunchGuards<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.load()?.token_a.as_ref(), pool.load()?.token_b.as_ref()],
+        bump = pool.load()?.bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(has_one = authority @ PiDaoError::UnauthorizedAccess)]
+    pub project: Account<'info, ProjectState>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PoolGovernance<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.load()?.token_a.as_ref(), pool.load()?.token_b.as_ref()],
+        bump = pool.load()?.bump,
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform.bump,
+        has_one = multisig @ PiDaoError::InvalidMultisig,
+    )]
+    pub platform: Account<'info, PlatformConfig>,
+    pub multisig: Account<'info, MultisigState>,
+    pub signer: Signer<'info>,
+}
+
+impl<'info> PoolGovernance<'info> {
+    // Pool parameters are governed by the platform multisig
+    fn require_approval(&self, remaining_accounts: &[AccountInfo]) -> Result<()> {
+        let signers = transaction_signers(remaining_accounts, self.signer.key());
+        require!(
+            self.multisig.signed_approvals(&signers) >= self.multisig.threshold,
+            PiDaoError::InsufficientApprovals
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    /// CHECK: may hold an older layout; the discriminator is checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub pool: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account(zero_copy)]
+pub struct PoolState {
+    pub total_liquidity: u64,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub fee: u64, // Fee in basis points (1/10000)
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub token_a_reserve: Pubkey,
+    pub token_b_reserve: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub protocol_fees_a: u64, // Held in the reserves but owed to the platform
+    pub protocol_fees_b: u64,
+    pub version: u8,
+    pub bump: u8,
+    pub authority_bump: u8,
+    pub curve: u8, // CURVE_CONSTANT_PRODUCT or CURVE_STABLE_SWAP
+    pub fee_mode: u8, // FEE_MODE_STATIC or FEE_MODE_DYNAMIC
+    pub padding: [u8; 3],
+    // TWAP accumulators, see `oracle`; u128s kept as bytes to leave the layout unpadded
+    pub price_a_cumulative: [u8; 16],
+    pub price_b_cumulative: [u8; 16],
+    pub last_price_update: i64,
+    // StableSwap amplification, moved linearly from initial to target over the ramp
+    pub amp_initial: u64,
+    pub amp_target: u64,
+    pub amp_ramp_start: i64,
+    pub amp_ramp_end: i64,
+    // Dynamic fee bounds in basis points, and the recent price movement that
+    // places the fee between them
+    pub dynamic_fee_min: u64,
+    pub dynamic_fee_max: u64,
+    pub volatility: u64, // Basis points, decaying over time
+    pub volatility_updated_at: i64,
+    pub launch: LaunchGuards,
+    pub reserved: [u8; 32],
+}
+
+impl PoolState {
+    pub const VERSION: u8 = 4;
+
+    pub fn current_amp(&self, now: i64) -> u64 {
+        if now >= self.amp_ramp_end {
+            return self.amp_target;
+        }
+        let elapsed = now.saturating_sub(self.amp_ramp_start).max(0) as u128;
+        let duration = (self.amp_ramp_end - self.amp_ramp_start) as u128;
+        if self.amp_target >= self.amp_initial {
+            let change = (self.amp_target - self.amp_initial) as u128 * elapsed / duration;
+            self.amp_initial + change as u64
+        } else {
+            let change = (self.amp_initial - self.amp_target) as u128 * elapsed / duration;
+            self.amp_initial - change as u64
+        }
+    }
+
+    // Recorded volatility after halving once per VOLATILITY_HALF_LIFE since the last swap
+    pub fn current_volatility(&self, now: i64) -> u64 {
+        let halvings = (now - self.volatility_updated_at).max(0) / VOLATILITY_HALF_LIFE;
+        if halvings >= 64 {
+            0
+        } else {
+            self.volatility >> halvings
+        }
+    }
+
+    // Fee in basis points for a swap at `now`. Dynamic pools scale linearly from
+    // the minimum at rest to the maximum at VOLATILITY_FOR_MAX_FEE, and any
+    // launch fee still decaying at `slot` applies on top.
+    pub fn current_fee(&self, now: i64, slot: u64) -> u64 {
+        let fee = if self.fee_mode == FEE_MODE_DYNAMIC {
+            let volatility = std::cmp::min(self.current_volatility(now), VOLATILITY_FOR_MAX_FEE);
+            self.dynamic_fee_min
+                + (self.dynamic_fee_max - self.dynamic_fee_min) * volatility / VOLATILITY_FOR_MAX_FEE
+        } else {
+            self.fee
+        };
+        self.launch.fee(fee, slot)
+    }
+
+    // Output for selling `amount_received` of one side, and the platform's cut
+    // of the fee taken from it. The fee rounds up and the output rounds down.
+    pub fn quote_swap(
+        &self,
+        amount_received: u64,
+        a_to_b: bool,
+        protocol_fee_bps: u16,
+        now: i64,
+        slot: u64,
+    ) -> Result<(u64, u64)> {
+        let (reserve_in_amount, reserve_out_amount) = if a_to_b {
+            (self.token_a_amount, self.token_b_amount)
+        } else {
+            (self.token_b_amount, self.token_a_amount)
+        };
+
+        let fee_amount = mul_div_ceil(amount_received, self.current_fee(now, slot), 10000)?;
+        let amount_in_with_fee = amount_received - fee_amount;
+        // The platform's cut of the fee is set aside rather than left to LPs
+        let protocol_fee = fee_on(fee_amount, protocol_fee_bps)?;
+
+        let amount_out = if self.curve == CURVE_STABLE_SWAP {
+            stable_swap_out(
+                amount_in_with_fee,
+                reserve_in_amount,
+                reserve_out_amount,
+                self.current_amp(now),
+            )?
+        } else {
+            mul_div_floor(
+                amount_in_with_fee,
+                reserve_out_amount,
+                reserve_in_amount.checked_add(amount_in_with_fee).ok_or(PoolError::Overflow)?,
+            )?
+        };
+        Ok((amount_out, protocol_fee))
+    }
+
+    pub fn apply_swap(
+        &mut self,
+        a_to_b: bool,
+        amount_received: u64,
+        amount_out: u64,
+        protocol_fee: u64,
+        now: i64,
+    ) -> Result<()> {
+        let (a_before, b_before) = (self.token_a_amount, self.token_b_amount);
+        if a_to_b {
+            self.token_a_amount += amount_received - protocol_fee;
+            self.token_b_amount -= amount_out;
+            self.protocol_fees_a += protocol_fee;
+        } else {
+            self.token_b_amount += amount_received - protocol_fee;
+            self.token_a_amount -= amount_out;
+            self.protocol_fees_b += protocol_fee;
+        }
+
+        // Relative move in the reserve price B/A, added to what is left of
+        // earlier moves. Tracked in every fee mode so a switch to dynamic fees
+        // starts from real history.
+        let before = b_before as u128 * self.token_a_amount as u128;
+        let after = self.token_b_amount as u128 * a_before as u128;
+        let movement = if before == 0 {
+            0
+        } else {
+            mul_div_wide(before.abs_diff(after), 10_000, before, false)?
+        };
+        self.volatility = std::cmp::min(
+            (self.current_volatility(now) as u128).saturating_add(movement),
+            VOLATILITY_FOR_MAX_FEE as u128,
+        ) as u64;
+        self.volatility_updated_at = now;
+
+        Ok(())
+    }
+}
+
+pub const CURVE_CONSTANT_PRODUCT: u8 = 0;
+pub const CURVE_STABLE_SWAP: u8 = 1;
+
+// Bounds on the amplification coefficient and how fast governance may move it
+pub const MAX_AMP: u64 = 1_000_000;
+pub const MAX_AMP_CHANGE: u64 = 10;
+pub const MIN_AMP_RAMP_DURATION: i64 = 24 * 60 * 60;
+
+// Pricing curve chosen when the pool is created
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PoolCurve {
+    ConstantProduct,
+    StableSwap { amp: u64 },
+}
+
+pub const FEE_MODE_STATIC: u8 = 0;
+pub const FEE_MODE_DYNAMIC: u8 = 1;
+
+// Dynamic fees: the upper bound governance may set, the volatility at which
+// the fee reaches its maximum, and how quickly volatility fades
+pub const MAX_DYNAMIC_FEE: u64 = 1_000;
+pub const VOLATILITY_FOR_MAX_FEE: u64 = 1_000;
+pub const VOLATILITY_HALF_LIFE: i64 = 5 * 60;
+
+pub const LAUNCHED_TOKEN_NONE: u8 = 0;
+pub const LAUNCHED_TOKEN_A: u8 = 1;
+pub const LAUNCHED_TOKEN_B: u8 = 2;
+
+// Launch guard bounds: about a day of slots, and a 50% starting fee
+pub const MAX_LAUNCH_GUARD_SLOTS: u64 = 216_000;
+pub const MAX_LAUNCH_FEE: u64 = 5_000;
+
+// Anti-snipe restrictions on buying a freshly seeded pool's launched token.
+// Every window counts from `launch_slot`: the slot of the first deposit, or of
+// the guards being set if someone else seeded the pool first.
+#[zero_copy]
+#[derive(Default)]
+pub struct LaunchGuards {
+    pub launch_slot: u64,
+    pub limit_slots: u64, // Buy limits apply for this many slots
+    pub max_buy_per_tx: u64, // In the launched token; zero for no limit
+    pub max_buy_per_wallet: u64,
+    pub initial_fee: u64, // Basis points, decaying linearly to the pool fee
+    pub fee_decay_slots: u64,
+    pub allowlist_slots: u64, // Only Pi-verified wallets may buy in this window
+    pub launched_token: u8, // LAUNCHED_TOKEN_NONE, LAUNCHED_TOKEN_A or LAUNCHED_TOKEN_B
+    pub padding: [u8; 7],
+}
+
+impl LaunchGuards {
+    // Whether a swap in this direction buys the launched token
+    pub fn is_buy(&self, a_to_b: bool) -> bool {
+        match self.launched_token {
+            LAUNCHED_TOKEN_A => !a_to_b,
+            LAUNCHED_TOKEN_B => a_to_b,
+            _ => false,
+        }
+    }
+
+    fn slots_since_launch(&self, slot: u64) -> u64 {
+        slot.saturating_sub(self.launch_slot)
+    }
+
+    pub fn limits_buys(&self, slot: u64) -> bool {
+        self.launched_token != LAUNCHED_TOKEN_NONE && self.slots_since_launch(slot) < self.limit_slots
+    }
+
+    pub fn requires_verification(&self, slot: u64) -> bool {
+        self.launched_token != LAUNCHED_TOKEN_NONE
+            && self.slots_since_launch(slot) < self.allowlist_slots
+    }
+
+    // Fee at `slot`: the launch fee falling linearly to `base_fee` over the decay window
+    pub fn fee(&self, base_fee: u64, slot: u64) -> u64 {
+        let elapsed = self.slots_since_launch(slot);
+        if self.launched_token == LAUNCHED_TOKEN_NONE
+            || elapsed >= self.fee_decay_slots
+            || self.initial_fee <= base_fee
+        {
+            return base_fee;
+        }
+        let remaining = self.fee_decay_slots - elapsed;
+        base_fee + (self.initial_fee - base_fee) * remaining / self.fee_decay_slots
+    }
+}
+
+// Launch guard settings chosen by the project before the first deposit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LaunchGuardParams {
+    pub limit_slots: u64,
+    pub max_buy_per_tx: u64,
+    pub max_buy_per_wallet: u64,
+    pub initial_fee: u64,
+    pub fee_decay_slots: u64,
+    pub allowlist_slots: u64,
+}
+
+// Launched token bought by one wallet from one pool during its buy limits
+#[account]
+#[derive(InitSpace)]
+pub struct LaunchPurchases {
+    pub version: u8,
+    pub pool: Pubkey,
+    pub wallet: Pubkey,
+    pub amount_bought: u64,
+    pub reserved: [u8; 32],
+}
+
+impl LaunchPurchases {
+    pub const VERSION: u8 = 1;
+}
+
+pub const LP_TOKEN_DECIMALS: u8 = 9;
+
+pub const ROUTE_HOP_ACCOUNTS: usize = 8;
+pub const MAX_ROUTE_HOPS: usize = 4;
+
+// LP shares counted on the first deposit but never minted, so the supply can
+// never be drained back to a handful of units and the share price inflated
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+// Zero-copy pool layout from before launch guards
+#[zero_copy]
+pub struct PoolStateV3 {
+    pub total_liquidity: u64,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub fee: u64,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub token_a_reserve: Pubkey,
+    pub token_b_reserve: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub protocol_fees_a: u64,
+    pub protocol_fees_b: u64,
+    pub version: u8,
+    pub bump: u8,
+    pub authority_bump: u8,
+    pub curve: u8,
+    pub fee_mode: u8,
+    pub padding: [u8; 3],
+    pub price_a_cumulative: [u8; 16],
+    pub price_b_cumulative: [u8; 16],
+    pub last_price_update: i64,
+    pub amp_initial: u64,
+    pub amp_target: u64,
+    pub amp_ramp_start: i64,
+    pub amp_ramp_end: i64,
+    pub dynamic_fee_min: u64,
+    pub dynamic_fee_max: u64,
+    pub volatility: u64,
+    pub volatility_updated_at: i64,
+    pub reserved: [u8; 32],
+}
+
+// Zero-copy pool layout from before the StableSwap curve
+#[zero_copy]
+pub struct PoolStateV2 {
+    pub total_liquidity: u64,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub fee: u64,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub token_a_reserve: Pubkey,
+    pub token_b_reserve: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub protocol_fees_a: u64,
+    pub protocol_fees_b: u64,
+    pub version: u8,
+    pub bump: u8,
+    pub authority_bump: u8,
+    pub padding: [u8; 5],
+    pub price_a_cumulative: [u8; 16],
+    pub price_b_cumulative: [u8; 16],
+    pub last_price_update: i64,
+    pub reserved: [u8; 8],
+}
+
+// Borsh pool layouts, kept only so migrate_pool can recognise and reject them
+
+// From before accounts carried a version byte
+#[derive(InitSpace)]
+pub struct PoolStateV0 {
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub token_a_reserve: Pubkey,
+    pub token_b_reserve: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub bump: u8,
+    pub total_liquidity: u64,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub fee: u64,
+}
+
+// Versioned, replaced by the zero-copy layout
+#[derive(InitSpace)]
+pub struct PoolStateV1 {
+    pub version: u8,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub token_a_reserve: Pubkey,
+    pub token_b_reserve: Pubkey,
+    pub lp_token_mint: Pubkey,
+    pub bump: u8,
+    pub total_liquidity: u64,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub fee: u64,
+    pub reserved: [u8; 64],
+}
+
+impl From<PoolStateV2> for PoolState {
+    fn from(old: PoolStateV2) -> Self {
+        PoolState {
+            total_liquidity: old.total_liquidity,
+            token_a_amount: old.token_a_amount,
+            token_b_amount: old.token_b_amount,
+            fee: old.fee,
+            token_a: old.token_a,
+            token_b: old.token_b,
+            token_a_reserve: old.token_a_reserve,
+            token_b_reserve: old.token_b_reserve,
+            lp_token_mint: old.lp_token_mint,
+            protocol_fees_a: old.protocol_fees_a,
+            protocol_fees_b: old.protocol_fees_b,
+            version: PoolState::VERSION,
+            bump: old.bump,
+            authority_bump: old.authority_bump,
+            curve: CURVE_CONSTANT_PRODUCT,
+            fee_mode: FEE_MODE_STATIC,
+            padding: [0; 3],
+            price_a_cumulative: old.price_a_cumulative,
+            price_b_cumulative: old.price_b_cumulative,
+            last_price_update: old.last_price_update,
+            amp_initial: 0,
+            amp_target: 0,
+            amp_ramp_start: 0,
+            amp_ramp_end: 0,
+            dynamic_fee_min: 0,
+            dynamic_fee_max: 0,
+            volatility: 0,
+            volatility_updated_at: 0,
+            launch: LaunchGuards::default(),
+            reserved: [0; 32],
+        }
+    }
+}
+
+// Pools that were live before launch guards keep trading without them
+impl From<PoolStateV3> for PoolState {
+    fn from(old: PoolStateV3) -> Self {
+        PoolState {
+            total_liquidity: old.total_liquidity,
+            token_a_amount: old.token_a_amount,
+            token_b_amount: old.token_b_amount,
+            fee: old.fee,
+            token_a: old.token_a,
+            token_b: old.token_b,
+            token_a_reserve: old.token_a_reserve,
+            token_b_reserve: old.token_b_reserve,
+            lp_token_mint: old.lp_token_mint,
+            protocol_fees_a: old.protocol_fees_a,
+            protocol_fees_b: old.protocol_fees_b,
+            version: PoolState::VERSION,
+            bump: old.bump,
+            authority_bump: old.authority_bump,
+            curve: old.curve,
+            fee_mode: old.fee_mode,
+            padding: [0; 3],
+            price_a_cumulative: old.price_a_cumulative,
+            price_b_cumulative: old.price_b_cumulative,
+            last_price_update: old.last_price_update,
+            amp_initial: old.amp_initial,
+            amp_target: old.amp_target,
+            amp_ramp_start: old.amp_ramp_start,
+            amp_ramp_end: old.amp_ramp_end,
+            dynamic_fee_min: old.dynamic_fee_min,
+            dynamic_fee_max: old.dynamic_fee_max,
+            volatility: old.volatility,
+            volatility_updated_at: old.volatility_updated_at,
+            launch: LaunchGuards::default(),
+            reserved: [0; 32],
+        }
+    }
+}
+
+// Floor of the square root, by Newton's method
+pub fn integer_sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+// a * b / c, rounded down
+pub fn mul_div_floor(a: u64, b: u64, c: u64) -> Result<u64> {
+    require!(c > 0, PoolError::Overflow);
+    u64::try_from(a as u128 * b as u128 / c as u128).map_err(|_| error!(PoolError::Overflow))
+}
+
+// a * b / c, rounded up
+pub fn mul_div_ceil(a: u64, b: u64, c: u64) -> Result<u64> {
+    require!(c > 0, PoolError::Overflow);
+    u64::try_from((a as u128 * b as u128).div_ceil(c as u128))
+        .map_err(|_| error!(PoolError::Overflow))
+}
+
+// StableSwap invariant D of a two-token pool, by Newton's method:
+// 4A(x + y) + D = 4AD + D^3 / 4xy
+pub fn stable_invariant(x: u64, y: u64, amp: u64) -> Result<u128> {
+    require!(x > 0 && y > 0, PoolError::EmptyReserves);
+    let (x, y) = (x as u128, y as u128);
+    let sum = x + y;
+    let ann = amp as u128 * 4;
+
+    let mut d = sum;
+    for _ in 0..255 {
+        // D^3 / 4xy, one factor at a time to keep the intermediates small
+        let d_p = d.checked_mul(d).ok_or(PoolError::Overflow)? / (x * 2);
+        let d_p = d_p.checked_mul(d).ok_or(PoolError::Overflow)? / (y * 2);
+        let previous = d;
+        let numerator = ann
+            .checked_mul(sum)
+            .and_then(|value| value.checked_add(d_p * 2))
+            .and_then(|value| value.checked_mul(d))
+            .ok_or(PoolError::Overflow)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)
+            .and_then(|value| value.checked_add(d_p * 3))
+            .ok_or(PoolError::Overflow)?;
+        d = numerator / denominator;
+        if d.abs_diff(previous) <= 1 {
+            return Ok(d);
+        }
+    }
+    err!(PoolError::CurveDidNotConverge)
+}
+
+// Balance of one side that keeps the invariant at `d` when the other holds `x`
+pub fn stable_balance(x: u128, d: u128, amp: u64) -> Result<u128> {
+    let ann = amp as u128 * 4;
+    let c = d.checked_mul(d).ok_or(PoolError::Overflow)? / (x * 2);
+    let c = c.checked_mul(d).ok_or(PoolError::Overflow)? / (ann * 2);
+    let b = x + d / ann;
+
+    let mut y = d;
+    for _ in 0..255 {
+        let previous = y;
+        let numerator = y
+            .checked_mul(y)
+            .and_then(|value| value.checked_add(c))
+            .ok_or(PoolError::Overflow)?;
+        let denominator = (y * 2 + b).checked_sub(d).ok_or(PoolError::Overflow)?;
+        y = numerator / denominator;
+        if y.abs_diff(previous) <= 1 {
+            return Ok(y);
+        }
+    }
+    err!(PoolError::CurveDidNotConverge)
+}
+
+// Output of a StableSwap trade, one unit short of the exact curve so rounding
+// in the Newton iterations never favours the trader
+pub fn stable_swap_out(amount_in: u64, reserve_in: u64, reserve_out: u64, amp: u64) -> Result<u64> {
+    let d = stable_invariant(reserve_in, reserve_out, amp)?;
+    let new_reserve_out = stable_balance(reserve_in as u128 + amount_in as u128, d, amp)?;
+    Ok((reserve_out as u128)
+        .saturating_sub(new_reserve_out)
+        .saturating_sub(1) as u64)
+}
+
+// Rejects a transaction that sat unprocessed past the caller's deadline
+pub fn check_deadline(deadline: i64) -> Result<()> {
+    require!(
+        Clock::get()?.unix_timestamp <= deadline,
+        PoolError::DeadlineExceeded
+    );
+    Ok(())
+}
+
+// Amount that actually arrives after any Token-2022 transfer fee
+pub fn amount_after_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+
+    let transfer_fee = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(PoolError::Overflow)?,
+        Err(_) => 0,
+    };
+
+    Ok(amount.checked_sub(transfer_fee).ok_or(PoolError::Overflow)?)
+}
+
+// Whether the mint carries the Token-2022 transfer fee extension at all
+pub fn charges_transfer_fee(mint: &InterfaceAccount<Mint>) -> Result<bool> {
+    let mint_info = mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+
+    Ok(mint_state.get_extension::<TransferFeeConfig>().is_ok())
+}
+
+// Rejects Token-2022 mints whose extensions let someone other than the pool
+// move or freeze its reserves, or that need accounts a swap doesn't pass.
+// Transfer fees are netted out of the pool math; metadata and interest only
+// change how amounts are displayed.
+pub fn check_pool_mint_extensions(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+
+    let supported = mint_state.get_extension_types()?.iter().all(|extension| {
+        matches!(
+            extension,
+            ExtensionType::TransferFeeConfig
+                | ExtensionType::MetadataPointer
+                | ExtensionType::TokenMetadata
+                | ExtensionType::InterestBearingConfig
+        )
+    });
+    require!(supported, PoolError::UnsupportedMintExtension);
+    Ok(())
+}
+
+pub fn handle_initialize_pool(ctx: Context<InitializePool>, fee: u64, curve: PoolCurve) -> Result<()> {
+    require!(fee < 10_000, PoolError::InvalidFee);
+    check_pool_mint_extensions(&ctx.accounts.token_a)?;
+    check_pool_mint_extensions(&ctx.accounts.token_b)?;
+    let mut pool = ctx.accounts.pool.load_init()?;
+    let now = Clock::get()?.unix_timestamp;
+
+    pool.version = PoolState::VERSION;
+    pool.token_a = ctx.accounts.token_a.key();
+    pool.token_b = ctx.accounts.token_b.key();
+    pool.token_a_reserve = ctx.accounts.token_a_reserve.key();
+    pool.token_b_reserve = ctx.accounts.token_b_reserve.key();
+    pool.lp_token_mint = ctx.accounts.lp_token_mint.key();
+    pool.bump = ctx.bumps.pool;
+    pool.authority_bump = ctx.bumps.pool_authority;
+    pool.total_liquidity = 0;
+    pool.token_a_amount = 0;
+    pool.token_b_amount = 0;
+    pool.fee = fee;
+
+    match curve {
+        PoolCurve::ConstantProduct => pool.curve = CURVE_CONSTANT_PRODUCT,
+        PoolCurve::StableSwap { amp } => {
+            require!((1..=MAX_AMP).contains(&amp), PoolError::InvalidAmp);
+            pool.curve = CURVE_STABLE_SWAP;
+            pool.amp_initial = amp;
+            pool.amp_target = amp;
+            pool.amp_ramp_start = now;
+            pool.amp_ramp_end = now;
+        }
+    }
+
+    ctx.accounts
+        .observations
+        .load_init()?
+        .init(ctx.accounts.pool.key(), ctx.bumps.observations);
+
+    Ok(())
+}
+
+// Deposits at most `max_amount_a`/`max_amount_b` in the pool's current ratio.
+// Every rounding step favours the pool over the depositor.
+pub fn handle_add_liquidity(
+    ctx: Context<AddLiquidity>,
+    max_amount_a: u64,
+    max_amount_b: u64,
+    min_lp_out: u64,
+    deadline: i64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let pool_key = ctx.accounts.pool.key();
+    let authority_seeds: &[&[u8]] = &[
+        b"pool_authority",
+        pool_key.as_ref(),
+        &[pool.authority_bump],
+    ];
+    update_oracle(&mut pool, &ctx.accounts.observations)?;
+
+    // The first depositor sets the price; later ones match it, paying the
+    // rounded-up side of the ratio
+    let (amount_a, amount_b) = if pool.total_liquidity == 0 {
+        (max_amount_a, max_amount_b)
+    } else {
+        let optimal_b = mul_div_ceil(max_amount_a, pool.token_b_amount, pool.token_a_amount)?;
+        if optimal_b <= max_amount_b {
+            (max_amount_a, optimal_b)
+        } else {
+            let optimal_a = mul_div_ceil(max_amount_b, pool.token_a_amount, pool.token_b_amount)?;
+            require!(optimal_a <= max_amount_a, PoolError::SlippageExceeded);
+            (optimal_a, max_amount_b)
+        }
+    };
+
+    // Transfer tokens to pool reserves
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_a_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_token_a_account.to_account_info(),
+                mint: ctx.accounts.token_a.to_account_info(),
+                to: ctx.accounts.token_a_reserve.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        amount_a,
+        ctx.accounts.token_a.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_b_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_token_b_account.to_account_info(),
+                mint: ctx.accounts.token_b.to_account_info(),
+                to: ctx.accounts.token_b_reserve.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        amount_b,
+        ctx.accounts.token_b.decimals,
+    )?;
+
+    // Only what actually reached the reserves counts towards liquidity
+    let received_a = amount_after_transfer_fee(&ctx.accounts.token_a, amount_a)?;
+    let received_b = amount_after_transfer_fee(&ctx.accounts.token_b, amount_b)?;
+
+    // Calculate and mint LP tokens, holding back the locked minimum on the first deposit
+    let (minted_liquidity, lp_amount) = if pool.total_liquidity == 0 {
+        let liquidity = u64::try_from(integer_sqrt(received_a as u128 * received_b as u128))
+            .map_err(|_| error!(PoolError::Overflow))?;
+        require!(liquidity > MINIMUM_LIQUIDITY, PoolError::InsufficientInitialLiquidity);
+        (liquidity, liquidity - MINIMUM_LIQUIDITY)
+    } else {
+        let liquidity = std::cmp::min(
+            mul_div_floor(received_a, pool.total_liquidity, pool.token_a_amount)?,
+            mul_div_floor(received_b, pool.total_liquidity, pool.token_b_amount)?,
+        );
+        (liquidity, liquidity)
+    };
+    require!(lp_amount > 0, PoolError::InsufficientLiquidityMinted);
+    require!(lp_amount >= min_lp_out, PoolError::SlippageExceeded);
+    if pool.total_liquidity == 0 {
+        pool.launch.launch_slot = Clock::get()?.slot;
+    }
+
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::MintTo {
+                mint: ctx.accounts.lp_token_mint.to_account_info(),
+                to: ctx.accounts.user_lp_token_account.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            &[authority_seeds],
+        ),
+        lp_amount,
+    )?;
+
+    // Update pool state
+    pool.token_a_amount += received_a;
+    pool.token_b_amount += received_b;
+    pool.total_liquidity += minted_liquidity;
+
+    Ok(())
+}
+
+pub fn handle_remove_liquidity(
+    ctx: Context<RemoveLiquidity>,
+    lp_amount: u64,
+    min_a_out: u64,
+    min_b_out: u64,
+    deadline: i64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let pool_key = ctx.accounts.pool.key();
+    let authority_seeds: &[&[u8]] = &[
+        b"pool_authority",
+        pool_key.as_ref(),
+        &[pool.authority_bump],
+    ];
+    update_oracle(&mut pool, &ctx.accounts.observations)?;
+    
+    // Calculate token amounts to return, rounded down in the pool's favour
+    let amount_a = mul_div_floor(lp_amount, pool.token_a_amount, pool.total_liquidity)?;
+    let amount_b = mul_div_floor(lp_amount, pool.token_b_amount, pool.total_liquidity)?;
+    require!(
+        amount_after_transfer_fee(&ctx.accounts.token_a, amount_a)? >= min_a_out
+            && amount_after_transfer_fee(&ctx.accounts.token_b, amount_b)? >= min_b_out,
+        PoolError::SlippageExceeded
+    );
+
+    // Burn LP tokens
+    token_interface::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::Burn {
+                mint: ctx.accounts.lp_token_mint.to_account_info(),
+                from: ctx.accounts.user_lp_token_account.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        lp_amount,
+    )?;
+
+    // Transfer tokens back to user
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_a_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.token_a_reserve.to_account_info(),
+                mint: ctx.accounts.token_a.to_account_info(),
+                to: ctx.accounts.user_token_a_account.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            &[authority_seeds],
+        ),
+        amount_a,
+        ctx.accounts.token_a.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_b_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.token_b_reserve.to_account_info(),
+                mint: ctx.accounts.token_b.to_account_info(),
+                to: ctx.accounts.user_token_b_account.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            &[authority_seeds],
+        ),
+        amount_b,
+        ctx.accounts.token_b.decimals,
+    )?;
+
+    // Update pool state
+    pool.token_a_amount -= amount_a;
+    pool.token_b_amount -= amount_b;
+    pool.total_liquidity -= lp_amount;
+
+    Ok(())
+}
+
+// Check `pool` is the canonical pool pairing the project's token with its
+// quote mint, returning whether the project token is side A
+pub fn check_project_pool(project: &ProjectState, pool_key: Pubkey, pool: &PoolState) -> Result<bool> {
+    let expected_pool = Pubkey::create_program_address(
+        &[b"pool", pool.token_a.as_ref(), pool.token_b.as_ref(), &[pool.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| error!(PoolError::InvalidProjectPool))?;
+    let project_is_a = pool.token_a == project.project_token_mint;
+    let pair = if project_is_a { (pool.token_a, pool.token_b) } else { (pool.token_b, pool.token_a) };
+    require!(
+        pool_key == expected_pool && pair == (project.project_token_mint, project.quote_mint),
+        PoolError::InvalidProjectPool
+    );
+    Ok(project_is_a)
+}
+
+// Withdraw all of an expiring project's registered LP from the pool pairing
+// its token with its quote mint. The quote side is paid into the treasury and
+// the project tokens are burned in the reserve. Returns the quote received and
+// the project tokens burned, both zero when the project registered no LP.
+pub fn withdraw_project_liquidity(accounts: &ExpireProject) -> Result<(u64, u64)> {
+    let project = &accounts.project;
+    // Without registered LP there is nothing to withdraw
+    if project.lp_account == Pubkey::default() {
+        return Ok((0, 0));
+    }
+    let (
+        Some(pool_loader),
+        Some(observations),
+        Some(pool_authority),
+        Some(token_a),
+        Some(token_b),
+        Some(token_a_reserve),
+        Some(token_b_reserve),
+        Some(lp_token_mint),
+        Some(project_lp_account),
+        Some(token_a_program),
+        Some(token_b_program),
+        Some(lp_token_program),
+    ) = (
+        &accounts.pool,
+        &accounts.observations,
+        &accounts.pool_authority,
+        &accounts.token_a,
+        &accounts.token_b,
+        &accounts.token_a_reserve,
+        &accounts.token_b_reserve,
+        &accounts.lp_token_mint,
+        &accounts.project_lp_account,
+        &accounts.token_a_program,
+        &accounts.token_b_program,
+        &accounts.lp_token_program,
+    )
+    else {
+        return err!(PoolError::ProjectLiquidityMissing);
+    };
+    let pool_key = pool_loader.key();
+    let mut pool = pool_loader.load_mut()?;
+
+    // Only the project's own pool, with its own accounts
+    let project_is_a = check_project_pool(project, pool_key, &pool)?;
+    let expected_authority = Pubkey::create_program_address(
+        &[b"pool_authority", pool_key.as_ref(), &[pool.authority_bump]],
+        &crate::ID,
+    )
+    .map_err(|_| error!(PoolError::InvalidProjectPool))?;
+    require!(
+        pool_authority.key() == expected_authority
+            && observations.load()?.pool == pool_key
+            && (token_a.key(), token_b.key()) == (pool.token_a, pool.token_b)
+            && (token_a_reserve.key(), token_b_reserve.key())
+                == (pool.token_a_reserve, pool.token_b_reserve)
+            && lp_token_mint.key() == pool.lp_token_mint
+            && project_lp_account.mint == pool.lp_token_mint,
+        PoolError::InvalidProjectPool
+    );
+
+    let lp_amount = project_lp_account.amount;
+    if lp_amount == 0 {
+        return Ok((0, 0));
+    }
+    update_oracle(&mut pool, observations)?;
+    let amount_a = mul_div_floor(lp_amount, pool.token_a_amount, pool.total_liquidity)?;
+    let amount_b = mul_div_floor(lp_amount, pool.token_b_amount, pool.total_liquidity)?;
+
+    let project_key = project.key();
+    let project_seeds: &[&[u8]] = &[b"project_signer", project_key.as_ref(), &[project.signer_bump]];
+    let authority_seeds: &[&[u8]] = &[b"pool_authority", pool_key.as_ref(), &[pool.authority_bump]];
+
+    token_interface::burn(
+        CpiContext::new_with_signer(
+            lp_token_program.to_account_info(),
+            token_interface::Burn {
+                mint: lp_token_mint.to_account_info(),
+                from: project_lp_account.to_account_info(),
+                authority: accounts.project_signer.to_account_info(),
+            },
+            &[project_seeds],
+        ),
+        lp_amount,
+    )?;
+
+    let (project_program, project_mint, project_reserve, project_amount) = if project_is_a {
+        (token_a_program, token_a, token_a_reserve, amount_a)
+    } else {
+        (token_b_program, token_b, token_b_reserve, amount_b)
+    };
+    let (quote_program, quote_mint, quote_reserve, quote_amount) = if project_is_a {
+        (token_b_program, token_b, token_b_reserve, amount_b)
+    } else {
+        (token_a_program, token_a, token_a_reserve, amount_a)
+    };
+
+    token_interface::burn(
+        CpiContext::new_with_signer(
+            project_program.to_account_info(),
+            token_interface::Burn {
+                mint: project_mint.to_account_info(),
+                from: project_reserve.to_account_info(),
+                authority: pool_authority.to_account_info(),
+            },
+            &[authority_seeds],
+        ),
+        project_amount,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            quote_program.to_account_info(),
+            TransferChecked {
+                from: quote_reserve.to_account_info(),
+                mint: quote_mint.to_account_info(),
+                to: accounts.treasury_vault.to_account_info(),
+                authority: pool_authority.to_account_info(),
+            },
+            &[authority_seeds],
+        ),
+        quote_amount,
+        quote_mint.decimals,
+    )?;
+
+    pool.token_a_amount -= amount_a;
+    pool.token_b_amount -= amount_b;
+    pool.total_liquidity -= lp_amount;
+
+    Ok((amount_after_transfer_fee(quote_mint, quote_amount)?, project_amount))
+}
+
+pub fn handle_swap(
+    ctx: Context<Swap>,
+    amount_in: u64,
+    min_amount_out: u64,
+    deadline: i64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let pool_key = ctx.accounts.pool.key();
+    let authority_seeds: &[&[u8]] = &[
+        b"pool_authority",
+        pool_key.as_ref(),
+        &[pool.authority_bump],
+    ];
+    update_oracle(&mut pool, &ctx.accounts.observations)?;
+
+    // Direction follows the mint of the account being sold
+    let a_to_b = ctx.accounts.user_source_account.mint == pool.token_a;
+    let (mint_in, mint_out, reserve_in, reserve_out, program_in, program_out) = if a_to_b {
+        (
+            &ctx.accounts.token_a,
+            &ctx.accounts.token_b,
+            &ctx.accounts.token_a_reserve,
+            &ctx.accounts.token_b_reserve,
+            &ctx.accounts.token_a_program,
+            &ctx.accounts.token_b_program,
+        )
+    } else {
+        (
+            &ctx.accounts.token_b,
+            &ctx.accounts.token_a,
+            &ctx.accounts.token_b_reserve,
+            &ctx.accounts.token_a_reserve,
+            &ctx.accounts.token_b_program,
+            &ctx.accounts.token_a_program,
+        )
+    };
+
+    // Price against what the reserve receives net of transfer fees
+    let amount_received = amount_after_transfer_fee(mint_in, amount_in)?;
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let (amount_out, protocol_fee) = pool.quote_swap(
+        amount_received,
+        a_to_b,
+        ctx.accounts.platform.swap_protocol_fee_bps,
+        now,
+        clock.slot,
+    )?;
+    require!(
+        amount_after_transfer_fee(mint_out, amount_out)? >= min_amount_out,
+        PoolError::SlippageExceeded
+    );
+
+    if pool.launch.is_buy(a_to_b) {
+        let launch = pool.launch;
+        // The verification account only loads for the signer's own PDA
+        require!(
+            !launch.requires_verification(clock.slot) || ctx.accounts.pi_verification.is_some(),
+            PoolError::NotPiVerified
+        );
+        if launch.limits_buys(clock.slot) {
+            require!(
+                launch.max_buy_per_tx == 0 || amount_out <= launch.max_buy_per_tx,
+                PoolError::LaunchBuyLimitExceeded
+            );
+            if launch.max_buy_per_wallet > 0 {
+                let purchases = ctx.accounts.launch_purchases
+                    .as_mut()
+                    .ok_or(PoolError::LaunchPurchasesMissing)?;
+                purchases.version = LaunchPurchases::VERSION;
+                purchases.pool = pool_key;
+                purchases.wallet = ctx.accounts.authority.key();
+                purchases.amount_bought = purchases.amount_bought
+                    .checked_add(amount_out)
+                    .ok_or(PoolError::Overflow)?;
+                require!(
+                    purchases.amount_bought <= launch.max_buy_per_wallet,
+                    PoolError::LaunchBuyLimitExceeded
+                );
+            }
+        }
+    }
+
+    // Transfer tokens
+    token_interface::transfer_checked(
+        CpiContext::new(
+            program_in.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_source_account.to_account_info(),
+                mint: mint_in.to_account_info(),
+                to: reserve_in.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        amount_in,
+        mint_in.decimals,
+    )?;
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            program_out.to_account_info(),
+            TransferChecked {
+                from: reserve_out.to_account_info(),
+                mint: mint_out.to_account_info(),
+                to: ctx.accounts.user_destination_account.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            &[authority_seeds],
+        ),
+        amount_out,
+        mint_out.decimals,
+    )?;
+
+    pool.apply_swap(a_to_b, amount_received, amount_out, protocol_fee, now)?;
+
+    Ok(())
+}
+
+// One leg of a routed swap, checked against the pool it trades through
+struct RouteHop<'info> {
+    pool: AccountLoader<'info, PoolState>,
+    observations: AccountLoader<'info, PoolObservations>,
+    pool_authority: AccountInfo<'info>,
+    reserve_in: InterfaceAccount<'info, TokenAccount>,
+    reserve_out: InterfaceAccount<'info, TokenAccount>,
+    mint_in: InterfaceAccount<'info, Mint>,
+    mint_out: InterfaceAccount<'info, Mint>,
+    token_program_out: Interface<'info, TokenInterface>,
+    a_to_b: bool,
+}
+
+impl<'info> RouteHop<'info> {
+    fn load(accounts: &'info [AccountInfo<'info>]) -> Result<Self> {
+        let pool = AccountLoader::<PoolState>::try_from(&accounts[0])?;
+        let observations = AccountLoader::<PoolObservations>::try_from(&accounts[1])?;
+        let pool_authority = accounts[2].clone();
+        let reserve_in = InterfaceAccount::<TokenAccount>::try_from(&accounts[3])?;
+        let reserve_out = InterfaceAccount::<TokenAccount>::try_from(&accounts[4])?;
+        let mint_in = InterfaceAccount::<Mint>::try_from(&accounts[5])?;
+        let mint_out = InterfaceAccount::<Mint>::try_from(&accounts[6])?;
+        let token_program_out = Interface::<TokenInterface>::try_from(&accounts[7])?;
+
+        let state = pool.load()?;
+        // Direction follows the reserve being paid into
+        let a_to_b = reserve_in.key() == state.token_a_reserve;
+        let (expected_reserves, expected_mints) = if a_to_b {
+            ((state.token_a_reserve, state.token_b_reserve), (state.token_a, state.token_b))
+        } else {
+            ((state.token_b_reserve, state.token_a_reserve), (state.token_b, state.token_a))
+        };
+        let authority = Pubkey::create_program_address(
+            &[b"pool_authority", pool.key().as_ref(), &[state.authority_bump]],
+            &crate::ID,
+        )
+        .map_err(|_| error!(PoolError::InvalidRoute))?;
+        require!(
+            (reserve_in.key(), reserve_out.key()) == expected_reserves
+                && (mint_in.key(), mint_out.key()) == expected_mints
+                && pool_authority.key() == authority
+                && observations.load()?.pool == pool.key()
+                && *mint_out.to_account_info().owner == token_program_out.key(),
+            PoolError::InvalidRoute
+        );
+        drop(state);
+
+        Ok(Self {
+            pool,
+            observations,
+            pool_authority,
+            reserve_in,
+            reserve_out,
+            mint_in,
+            mint_out,
+            token_program_out,
+            a_to_b,
+        })
+    }
+}
+
+// Sell through a chain of pools in one transaction. Each hop's output is paid
+// straight into the next pool's reserve, and only the final output is held to
+// `min_amount_out`.
+pub fn handle_route_swap<'info>(
+    ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+    deadline: i64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    let remaining_accounts = ctx.remaining_accounts;
+    let hop_count = remaining_accounts.len() / ROUTE_HOP_ACCOUNTS;
+    require!(
+        (1..=MAX_ROUTE_HOPS).contains(&hop_count)
+            && hop_count * ROUTE_HOP_ACCOUNTS == remaining_accounts.len(),
+        PoolError::InvalidRoute
+    );
+    let hops = remaining_accounts
+        .chunks(ROUTE_HOP_ACCOUNTS)
+        .map(RouteHop::load)
+        .collect::<Result<Vec<_>>>()?;
+    // Each hop sells what the one before it bought
+    require!(
+        hops.windows(2)
+            .all(|pair| pair[0].mint_out.key() == pair[1].mint_in.key()),
+        PoolError::InvalidRoute
+    );
+    let (first, last) = (&hops[0], &hops[hops.len() - 1]);
+    require!(
+        ctx.accounts.user_source_account.mint == first.mint_in.key()
+            && ctx.accounts.user_destination_account.mint == last.mint_out.key()
+            && *first.mint_in.to_account_info().owner == ctx.accounts.source_token_program.key(),
+        PoolError::InvalidSwapAccounts
+    );
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.source_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_source_account.to_account_info(),
+                mint: first.mint_in.to_account_info(),
+                to: first.reserve_in.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        amount_in,
+        first.mint_in.decimals,
+    )?;
+
+    let clock = Clock::get()?;
+    let mut amount_sent = amount_in;
+    for (index, hop) in hops.iter().enumerate() {
+        let pool_key = hop.pool.key();
+        let mut pool = hop.pool.load_mut()?;
+        // Guarded launches are bought directly, where the limits are enforced
+        require!(
+            !pool.launch.is_buy(hop.a_to_b)
+                || !(pool.launch.limits_buys(clock.slot) || pool.launch.requires_verification(clock.slot)),
+            PoolError::LaunchGuardActive
+        );
+        update_oracle(&mut pool, &hop.observations)?;
+
+        let amount_received = amount_after_transfer_fee(&hop.mint_in, amount_sent)?;
+        let (amount_out, protocol_fee) = pool.quote_swap(
+            amount_received,
+            hop.a_to_b,
+            ctx.accounts.platform.swap_protocol_fee_bps,
+            clock.unix_timestamp,
+            clock.slot,
+        )?;
+        pool.apply_swap(hop.a_to_b, amount_received, amount_out, protocol_fee, clock.unix_timestamp)?;
+        let authority_bump = pool.authority_bump;
+        // The same pool may appear again later in the route
+        drop(pool);
+
+        let destination = match hops.get(index + 1) {
+            Some(next) => next.reserve_in.to_account_info(),
+            None => ctx.accounts.user_destination_account.to_account_info(),
+        };
+        let authority_seeds: &[&[u8]] = &[b"pool_authority", pool_key.as_ref(), &[authority_bump]];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                hop.token_program_out.to_account_info(),
+                TransferChecked {
+                    from: hop.reserve_out.to_account_info(),
+                    mint: hop.mint_out.to_account_info(),
+                    to: destination,
+                    authority: hop.pool_authority.clone(),
+                },
+                &[authority_seeds],
+            ),
+            amount_out,
+            hop.mint_out.decimals,
+        )?;
+        amount_sent = amount_out;
+    }
+
+    require!(
+        amount_after_transfer_fee(&last.mint_out, amount_sent)? >= min_amount_out,
+        PoolError::SlippageExceeded
+    );
+
+    Ok(())
+}
+
+// Rewrite a pool account from an older layout at the current version
+pub fn handle_migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+    let pool = ctx.accounts.pool.to_account_info();
+    let migrated = {
+        let data = pool.try_borrow_data()?;
+        require!(
+            data.len() > 8 && data[..8] == PoolState::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        // Every layout was allocated at its full size, so the length identifies it
+        let body = &data[8..];
+        match data.len() {
+            // Borsh pools held their reserves in accounts the pool authority
+            // does not own, so they could never trade once migrated
+            len if len == 8 + PoolStateV0::INIT_SPACE || len == 8 + PoolStateV1::INIT_SPACE => {
+                return err!(PoolError::UnsupportedPoolVersion)
+            }
+            len if len == 8 + std::mem::size_of::<PoolStateV2>() => {
+                PoolState::from(bytemuck::pod_read_unaligned::<PoolStateV2>(body))
+            }
+            len if len == 8 + std::mem::size_of::<PoolStateV3>() => {
+                PoolState::from(bytemuck::pod_read_unaligned::<PoolStateV3>(body))
+            }
+            len if len == 8 + std::mem::size_of::<PoolState>() => {
+                return err!(PoolError::AlreadyMigrated)
+            }
+            _ => return err!(PoolError::UnsupportedPoolVersion),
+        }
+    };
+    migration::resize_account(
+        &pool,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        8 + std::mem::size_of::<PoolState>(),
+    )?;
+    migration::write_zero_copy(&pool, &migrated)?;
+
+    Ok(())
+}
+
+// Start moving a StableSwap pool's amplification towards `target_amp`
+pub fn handle_ramp_pool_amp(ctx: Context<PoolGovernance>, target_amp: u64, ramp_end: i64) -> Result<()> {
+    ctx.accounts.require_approval(ctx.remaining_accounts)?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(pool.curve == CURVE_STABLE_SWAP, PoolError::NotStableSwapPool);
+    require!((1..=MAX_AMP).contains(&target_amp), PoolError::InvalidAmp);
+    // One ramp at a time, each long enough for LPs to react
+    require!(now >= pool.amp_ramp_end, PoolError::InvalidAmpRamp);
+    require!(
+        ramp_end >= now.checked_add(MIN_AMP_RAMP_DURATION).ok_or(PoolError::Overflow)?,
+        PoolError::InvalidAmpRamp
+    );
+    let current_amp = pool.current_amp(now);
+    require!(
+        target_amp <= current_amp * MAX_AMP_CHANGE && target_amp * MAX_AMP_CHANGE >= current_amp,
+        PoolError::InvalidAmpRamp
+    );
+
+    pool.amp_initial = current_amp;
+    pool.amp_target = target_amp;
+    pool.amp_ramp_start = now;
+    pool.amp_ramp_end = ramp_end;
+
+    emit!(AmpRampStarted {
+        pool: ctx.accounts.pool.key(),
+        initial_amp: current_amp,
+        target_amp,
+        ramp_start: now,
+        ramp_end,
+    });
+
+    Ok(())
+}
+
+// Freeze the amplification at its current value
+pub fn handle_stop_pool_amp_ramp(ctx: Context<PoolGovernance>) -> Result<()> {
+    ctx.accounts.require_approval(ctx.remaining_accounts)?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(pool.curve == CURVE_STABLE_SWAP, PoolError::NotStableSwapPool);
+    let current_amp = pool.current_amp(now);
+    pool.amp_initial = current_amp;
+    pool.amp_target = current_amp;
+    pool.amp_ramp_start = now;
+    pool.amp_ramp_end = now;
+
+    emit!(AmpRampStopped {
+        pool: ctx.accounts.pool.key(),
+        amp: current_amp,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+// Set or clear launch guards before the first deposit, so buyers can see the
+// rules before trading opens. Anyone may seed a pool, so a pool seeded without
+// guards can still have them set once, starting the launch window there.
+pub fn handle_configure_launch_guards(
+    ctx: Context<ConfigureLaunchGuards>,
+    params: Option<LaunchGuardParams>,
+) -> Result<()> {
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let seeded = pool.total_liquidity > 0;
+    require!(
+        !seeded || pool.launch.launched_token == LAUNCHED_TOKEN_NONE,
+        PoolError::PoolAlreadyLaunched
+    );
+
+    // The project's own token is the one being launched
+    let launched_mint = ctx.accounts.project.project_token_mint;
+    let launched_token = if launched_mint == pool.token_a {
+        LAUNCHED_TOKEN_A
+    } else if launched_mint == pool.token_b {
+        LAUNCHED_TOKEN_B
+    } else {
+        return err!(PoolError::InvalidMint);
+    };
+    let Some(params) = params else {
+        pool.launch = LaunchGuards {
+            launch_slot: pool.launch.launch_slot,
+            ..LaunchGuards::default()
+        };
+        return Ok(());
+    };
+    require!(
+        params.limit_slots <= MAX_LAUNCH_GUARD_SLOTS
+            && params.fee_decay_slots <= MAX_LAUNCH_GUARD_SLOTS
+            && params.allowlist_slots <= MAX_LAUNCH_GUARD_SLOTS
+            && params.initial_fee <= MAX_LAUNCH_FEE,
+        PoolError::InvalidLaunchGuards
+    );
+
+    pool.launch = LaunchGuards {
+        launch_slot: if seeded { Clock::get()?.slot } else { 0 },
+        limit_slots: params.limit_slots,
+        max_buy_per_tx: params.max_buy_per_tx,
+        max_buy_per_wallet: params.max_buy_per_wallet,
+        initial_fee: params.initial_fee,
+        fee_decay_slots: params.fee_decay_slots,
+        allowlist_slots: params.allowlist_slots,
+        launched_token,
+        padding: [0; 7],
+    };
+
+    Ok(())
+}
+
+// Switch the pool to volatility-scaled fees between the given bounds
+pub fn handle_enable_pool_dynamic_fee(ctx: Context<PoolGovernance>, min_fee: u64, max_fee: u64) -> Result<()> {
+    ctx.accounts.require_approval(ctx.remaining_accounts)?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
+
+    require!(min_fee <= max_fee && max_fee <= MAX_DYNAMIC_FEE, PoolError::InvalidFee);
+    pool.fee_mode = FEE_MODE_DYNAMIC;
+    pool.dynamic_fee_min = min_fee;
+    pool.dynamic_fee_max = max_fee;
+
+    emit!(PoolFeeModeChanged {
+        pool: ctx.accounts.pool.key(),
+        dynamic: true,
+        min_fee,
+        max_fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Return the pool to its static `fee`
+pub fn handle_disable_pool_dynamic_fee(ctx: Context<PoolGovernance>) -> Result<()> {
+    ctx.accounts.require_approval(ctx.remaining_accounts)?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
+
+    pool.fee_mode = FEE_MODE_STATIC;
+
+    emit!(PoolFeeModeChanged {
+        pool: ctx.accounts.pool.key(),
+        dynamic: false,
+        min_fee: pool.fee,
+        max_fee: pool.fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Pay the accumulated protocol share of swap fees to the fee recipient
+pub fn handle_collect_pool_protocol_fees(ctx: Context<CollectPoolProtocolFees>) -> Result<()> {
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let pool_key = ctx.accounts.pool.key();
+    let authority_seeds: &[&[u8]] = &[
+        b"pool_authority",
+        pool_key.as_ref(),
+        &[pool.authority_bump],
+    ];
+    let (fees_a, fees_b) = (pool.protocol_fees_a, pool.protocol_fees_b);
+    require!(fees_a > 0 || fees_b > 0, PoolError::NoProtocolFees);
+
+    if fees_a > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_a_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_a_reserve.to_account_info(),
+                    mint: ctx.accounts.token_a.to_account_info(),
+                    to: ctx.accounts.fee_recipient_token_a.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            fees_a,
+            ctx.accounts.token_a.decimals,
+        )?;
+
+        emit!(ProtocolFeesCollected {
+            source: ctx.accounts.pool.key(),
+            mint: ctx.accounts.token_a.key(),
+            recipient: ctx.accounts.fee_recipient_token_a.key(),
+            amount: fees_a,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    if fees_b > 0 {
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_b_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.token_b_reserve.to_account_info(),
+                    mint: ctx.accounts.token_b.to_account_info(),
+                    to: ctx.accounts.fee_recipient_token_b.to_account_info(),
+                    authority: ctx.accounts.pool_authority.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            fees_b,
+            ctx.accounts.token_b.decimals,
+        )?;
+
+        emit!(ProtocolFeesCollected {
+            source: ctx.accounts.pool.key(),
+            mint: ctx.accounts.token_b.key(),
+            recipient: ctx.accounts.fee_recipient_token_b.key(),
+            amount: fees_b,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+    }
+
+    pool.protocol_fees_a = 0;
+    pool.protocol_fees_b = 0;
+
+    Ok(())
+}
+
+#[error_code(offset = 7000)]
+pub enum PoolError {
+    #[msg("Amount out less than minimum")]
+    SlippageExceeded,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Pool mints must be distinct and sorted")]
+    InvalidMint,
+    #[msg("Pool is already at the current version")]
+    AlreadyMigrated,
+    #[msg("Unsupported pool version")]
+    UnsupportedPoolVersion,
+    #[msg("No protocol fees to collect")]
+    NoProtocolFees,
+    #[msg("Swap accounts must hold opposite sides of the pool")]
+    InvalidSwapAccounts,
+    #[msg("Initial deposit must exceed the locked minimum liquidity")]
+    InsufficientInitialLiquidity,
+    #[msg("Deposit too small to mint any LP tokens")]
+    InsufficientLiquidityMinted,
+    #[msg("Transaction deadline has passed")]
+    DeadlineExceeded,
+    #[msg("TWAP window must be positive")]
+    InvalidTwapWindow,
+    #[msg("Not enough observation history for the requested window")]
+    InsufficientObservationHistory,
+    #[msg("Observation interval must be between one minute and one day")]
+    InvalidObservationInterval,
+    #[msg("Amplification coefficient out of range")]
+    InvalidAmp,
+    #[msg("Amplification ramp too fast, too short or already in progress")]
+    InvalidAmpRamp,
+    #[msg("Pool does not use the StableSwap curve")]
+    NotStableSwapPool,
+    #[msg("StableSwap curve did not converge")]
+    CurveDidNotConverge,
+    #[msg("Pool has no liquidity")]
+    EmptyReserves,
+    #[msg("Pool fee out of range")]
+    InvalidFee,
+    #[msg("Square-root price out of range")]
+    InvalidSqrtPrice,
+    #[msg("Ticks must be ordered, in range and multiples of the tick spacing")]
+    InvalidTickRange,
+    #[msg("Tick array does not match the pool or the expected range")]
+    InvalidTickArray,
+    #[msg("Mints with transfer fees are not supported by this pool type")]
+    UnsupportedMint,
+    #[msg("Liquidity amount must be positive and within the position")]
+    ZeroLiquidity,
+    #[msg("Signer does not own the position")]
+    NotPositionOwner,
+    #[msg("Position still holds liquidity or uncollected fees")]
+    PositionNotEmpty,
+    #[msg("Route hops must be linked pools, at most MAX_ROUTE_HOPS of them")]
+    InvalidRoute,
+    #[msg("Launch guards can only change before the launch")]
+    PoolAlreadyLaunched,
+    #[msg("Launch guard windows or fee out of range")]
+    InvalidLaunchGuards,
+    #[msg("Buy exceeds the launch limit")]
+    LaunchBuyLimitExceeded,
+    #[msg("Launch purchases account required while per-wallet limits apply")]
+    LaunchPurchasesMissing,
+    #[msg("Only Pi-verified wallets may buy during the launch allowlist period")]
+    NotPiVerified,
+    #[msg("Pool launch guards only allow direct swaps")]
+    LaunchGuardActive,
+    #[msg("Mint uses a Token-2022 extension pools cannot hold")]
+    UnsupportedMintExtension,
+    #[msg("Accounts do not match the pool for the project's own token pair")]
+    InvalidProjectPool,
+    #[msg("The project's registered pool liquidity accounts are required")]
+    ProjectLiquidityMissing,
+}
@@ -6,18 +6,23 @@ import {
 } from '@project-serum/anchor';
 import {
     TOKEN_PROGRAM_ID,
-    createMint,
-    getMint,
     getOrCreateAssociatedTokenAccount,
 } from '@solana/spl-token';
 import {
+    PlatformConfig,
     ProjectState,
     ProjectConfig,
     ProposalState,
     ProposalStatus,
     InvestmentInfo,
+    VestingAllocation,
+    TokenConfig,
 } from './types';
 
+const TOKEN_METADATA_PROGRAM_ID = new web3.PublicKey(
+    'metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s'
+);
+
 export class PiDaosFun {
     private program: Program;
     private provider: AnchorProvider;
@@ -29,37 +34,64 @@ export class PiDaosFun {
 
     async initializeProject(
         config: ProjectConfig,
+        tokenConfig: TokenConfig,
         authority: web3.PublicKey,
+        quoteMint: web3.PublicKey,
         inviteCode?: string,
+        vestingAllocations: VestingAllocation[] = [],
+        tokenProgram: web3.PublicKey = TOKEN_PROGRAM_ID,
     ): Promise<web3.PublicKey> {
-        const [projectAccount] = await this.findProjectAddress(authority);
+        const [projectAccount] = await this.findProjectAddress(config.slug);
+        const [projectSigner] = await this.findProjectSignerAddress(projectAccount);
+        const [treasuryVault] = await this.findTreasuryAddress(projectAccount);
+        const [vesting] = await this.findVestingAddress(projectAccount);
+        const [vestingVault] = await this.findVestingVaultAddress(projectAccount);
+        const [investorVault] = await this.findInvestorVaultAddress(projectAccount);
+
+        // Each launch is registered under the platform at the next index
+        const [platform] = await this.findPlatformAddress();
+        const { projectCount } = await this.program.account.platformConfig.fetch(platform) as PlatformConfig;
+        const [projectEntry] = await this.findProjectEntryAddress(projectCount);
         
-        // Create project token mint
-        const projectTokenMint = await createMint(
-            this.provider.connection,
-            this.provider.wallet.payer,
-            authority,
-            null, // Freeze authority
-            9, // Decimals
-            TOKEN_PROGRAM_ID
+        // The program creates the project token mint and its metadata
+        const [projectTokenMint] = await this.findProjectMintAddress(projectAccount);
+        const [tokenMetadata] = await web3.PublicKey.findProgramAddress(
+            [
+                Buffer.from('metadata'),
+                TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+                projectTokenMint.toBuffer(),
+            ],
+            TOKEN_METADATA_PROGRAM_ID
         );
 
         // Calculate initial token price in Pi
         const initialPiPrice = config.totalSupply.div(config.targetRaise);
 
-        await this.program.methods.initializeProject({
-            ...config,
+        await this.program.methods.initializeProject(
+            config,
+            tokenConfig,
             initialPiPrice,
-            fundraiseEndTime: new BN(Date.now() / 1000 + 7 * 24 * 60 * 60), // 1 week
-            inviteOnly: !!inviteCode,
-            inviteCode: inviteCode || '',
-        })
+            inviteCode ?? null,
+            vestingAllocations,
+        )
             .accounts({
                 project: projectAccount,
                 authority,
+                platform,
+                projectEntry,
+                projectSigner,
                 projectTokenMint,
+                tokenMetadata,
+                quoteMint,
+                treasuryVault,
+                vesting,
+                vestingVault,
+                investorVault,
                 systemProgram: web3.SystemProgram.programId,
-                tokenProgram: TOKEN_PROGRAM_ID,
+                tokenProgram,
+                quoteTokenProgram: await this.getTokenProgramId(quoteMint),
+                tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+                rent: web3.SYSVAR_RENT_PUBKEY,
             })
             .rpc();
 
@@ -114,22 +146,30 @@ export class PiDaosFun {
             const tokenAmount = amount.mul(project.initialPiPrice);
 
             // Get or create user token account
-            const userTokenAccount = await getOrCreateAssociatedTokenAccount(
-                this.provider.connection,
-                this.provider.wallet.payer,
+            const userTokenAccount = await this.getOrCreateTokenAccount(
                 project.projectTokenMint,
                 this.provider.wallet.publicKey
             );
 
+            const [investorLock] = await this.findInvestorLockAddress(
+                projectAccount,
+                this.provider.wallet.publicKey
+            );
+            const [projectSigner] = await this.findProjectSignerAddress(projectAccount);
+
             // Execute investment transaction
             const tx = await this.program.methods.invest(amount)
                 .accounts({
                     project: projectAccount,
                     investor: this.provider.wallet.publicKey,
-                    userTokenAccount: userTokenAccount.address,
+                    userTokenAccount,
                     projectTokenMint: project.projectTokenMint,
+                    investorVault: project.investorVault,
+                    investorLock: project.lockup ? investorLock : null,
+                    projectSigner,
+                    platform: (await this.findPlatformAddress())[0],
                     systemProgram: web3.SystemProgram.programId,
-                    tokenProgram: TOKEN_PROGRAM_ID,
+                    tokenProgram: await this.getTokenProgramId(project.projectTokenMint),
                 })
                 .rpc();
 
@@ -170,6 +210,7 @@ export class PiDaosFun {
                 .accounts({
                     project: projectAccount,
                     authority: this.provider.wallet.publicKey,
+                    platform: (await this.findPlatformAddress())[0],
                     projectTokenMint: project.projectTokenMint,
                     systemProgram: web3.SystemProgram.programId,
                     tokenProgram: TOKEN_PROGRAM_ID,
@@ -183,6 +224,34 @@ export class PiDaosFun {
         }
     }
 
+    async cancelFundraise(
+        projectAccount: web3.PublicKey,
+        reasonCode: number,
+        multisig?: web3.PublicKey,
+        cosigners: web3.Keypair[] = [],
+    ): Promise<string> {
+        try {
+            const tx = await this.program.methods.cancelFundraise(reasonCode)
+                .accounts({
+                    project: projectAccount,
+                    multisig: multisig ?? null,
+                    canceller: this.provider.wallet.publicKey,
+                })
+                .remainingAccounts(cosigners.map((cosigner) => ({
+                    pubkey: cosigner.publicKey,
+                    isSigner: true,
+                    isWritable: false,
+                })))
+                .signers(cosigners)
+                .rpc();
+
+            return tx;
+        } catch (error) {
+            console.error('Fundraise cancellation failed:', error);
+            throw error;
+        }
+    }
+
     async claimRefund(
         projectAccount: web3.PublicKey,
     ): Promise<string> {
@@ -226,13 +295,33 @@ export class PiDaosFun {
                 throw new Error('Project has not expired yet');
             }
 
+            const [projectSigner] = await this.findProjectSignerAddress(projectAccount);
+
+            const userTokenAccount = await this.getOrCreateTokenAccount(
+                project.projectTokenMint,
+                this.provider.wallet.publicKey
+            );
+
+            // Redemption is paid out in the project's quote token
+            const userQuoteAccount = await this.getOrCreateTokenAccount(
+                project.quoteMint,
+                this.provider.wallet.publicKey
+            );
+
             const tx = await this.program.methods.redeemTokens(amount)
                 .accounts({
                     project: projectAccount,
                     investor: this.provider.wallet.publicKey,
+                    userTokenAccount,
                     projectTokenMint: project.projectTokenMint,
-                    systemProgram: web3.SystemProgram.programId,
-                    tokenProgram: TOKEN_PROGRAM_ID,
+                    userQuoteAccount,
+                    treasuryVault: project.treasuryVault,
+                    vesting: (await this.findVestingAddress(projectAccount))[0],
+                    vestingVault: (await this.findVestingVaultAddress(projectAccount))[0],
+                    investorVault: project.investorVault,
+                    projectSigner,
+                    tokenProgram: await this.getTokenProgramId(project.projectTokenMint),
+                    quoteTokenProgram: await this.getTokenProgramId(project.quoteMint),
                 })
                 .rpc();
 
@@ -243,6 +332,222 @@ export class PiDaosFun {
         }
     }
 
+    // Anyone may crank the fundraise fee out to the platform fee recipient
+    async collectProtocolFees(
+        projectAccount: web3.PublicKey,
+    ): Promise<string> {
+        try {
+            const project = await this.program.account.project.fetch(
+                projectAccount
+            ) as ProjectState;
+            const [platform] = await this.findPlatformAddress();
+            const config = await this.program.account.platformConfig.fetch(platform);
+            const [projectSigner] = await this.findProjectSignerAddress(projectAccount);
+
+            const feeRecipientAccount = await this.getOrCreateTokenAccount(
+                project.quoteMint,
+                config.feeRecipient
+            );
+
+            const tx = await this.program.methods.collectProtocolFees()
+                .accounts({
+                    project: projectAccount,
+                    platform,
+                    treasuryVault: project.treasuryVault,
+                    quoteMint: project.quoteMint,
+                    feeRecipientAccount,
+                    projectSigner,
+                    quoteTokenProgram: await this.getTokenProgramId(project.quoteMint),
+                })
+                .rpc();
+
+            return tx;
+        } catch (error) {
+            console.error('Protocol fee collection failed:', error);
+            throw error;
+        }
+    }
+
+    async claimVested(
+        projectAccount: web3.PublicKey,
+        scheduleIndex: number,
+    ): Promise<string> {
+        try {
+            const project = await this.program.account.project.fetch(
+                projectAccount
+            ) as ProjectState;
+
+            const [projectSigner] = await this.findProjectSignerAddress(projectAccount);
+            const [vesting] = await this.findVestingAddress(projectAccount);
+            const [vestingVault] = await this.findVestingVaultAddress(projectAccount);
+
+            const beneficiaryTokenAccount = await this.getOrCreateTokenAccount(
+                project.projectTokenMint,
+                this.provider.wallet.publicKey
+            );
+
+            const tx = await this.program.methods.claimVested(scheduleIndex)
+                .accounts({
+                    project: projectAccount,
+                    vesting,
+                    vestingVault,
+                    projectTokenMint: project.projectTokenMint,
+                    beneficiary: this.provider.wallet.publicKey,
+                    beneficiaryTokenAccount,
+                    projectSigner,
+                    tokenProgram: await this.getTokenProgramId(project.projectTokenMint),
+                })
+                .rpc();
+
+            return tx;
+        } catch (error) {
+            console.error('Vesting claim failed:', error);
+            throw error;
+        }
+    }
+
+    async claimUnlocked(
+        projectAccount: web3.PublicKey,
+    ): Promise<string> {
+        try {
+            const project = await this.program.account.project.fetch(
+                projectAccount
+            ) as ProjectState;
+
+            const [projectSigner] = await this.findProjectSignerAddress(projectAccount);
+            const [investorLock] = await this.findInvestorLockAddress(
+                projectAccount,
+                this.provider.wallet.publicKey
+            );
+
+            const userTokenAccount = await this.getOrCreateTokenAccount(
+                project.projectTokenMint,
+                this.provider.wallet.publicKey
+            );
+
+            const tx = await this.program.methods.claimUnlocked()
+                .accounts({
+                    project: projectAccount,
+                    investorLock,
+                    investorVault: project.investorVault,
+                    projectTokenMint: project.projectTokenMint,
+                    investor: this.provider.wallet.publicKey,
+                    userTokenAccount,
+                    projectSigner,
+                    tokenProgram: await this.getTokenProgramId(project.projectTokenMint),
+                })
+                .rpc();
+
+            return tx;
+        } catch (error) {
+            console.error('Unlock claim failed:', error);
+            throw error;
+        }
+    }
+
+    // Projects holding LP in their own pool also pass that pool's accounts,
+    // so the crank can withdraw the liquidity back into the treasury
+    async expireProject(
+        projectAccount: web3.PublicKey,
+        projectLiquidity?: {
+            pool: web3.PublicKey;
+            projectLpAccount: web3.PublicKey;
+        },
+    ): Promise<string> {
+        try {
+            const project = await this.program.account.project.fetch(
+                projectAccount
+            ) as ProjectState;
+            const [projectSigner] = await this.findProjectSignerAddress(projectAccount);
+
+            const tx = await this.program.methods.expireProject()
+                .accounts({
+                    project: projectAccount,
+                    treasuryVault: project.treasuryVault,
+                    projectSigner,
+                    ...(await this.projectLiquidityAccounts(projectLiquidity)),
+                })
+                .rpc();
+
+            return tx;
+        } catch (error) {
+            console.error('Project expiry failed:', error);
+            throw error;
+        }
+    }
+
+    async closeProject(
+        projectAccount: web3.PublicKey,
+    ): Promise<string> {
+        try {
+            const project = await this.program.account.project.fetch(
+                projectAccount
+            ) as ProjectState;
+
+            const [projectSigner] = await this.findProjectSignerAddress(projectAccount);
+
+            const tx = await this.program.methods.closeProject()
+                .accounts({
+                    project: projectAccount,
+                    authority: project.authority,
+                    projectTokenMint: project.projectTokenMint,
+                    treasuryVault: project.treasuryVault,
+                    vesting: (await this.findVestingAddress(projectAccount))[0],
+                    vestingVault: (await this.findVestingVaultAddress(projectAccount))[0],
+                    investorVault: project.investorVault,
+                    projectEntry: (await this.findProjectEntryAddress(project.registryIndex))[0],
+                    projectSigner,
+                    tokenProgram: await this.getTokenProgramId(project.projectTokenMint),
+                    quoteTokenProgram: await this.getTokenProgramId(project.quoteMint),
+                })
+                .rpc();
+
+            return tx;
+        } catch (error) {
+            console.error('Project close failed:', error);
+            throw error;
+        }
+    }
+
+    // Accounts written by an older program version must be migrated before use
+    async migrateProject(
+        projectAccount: web3.PublicKey,
+    ): Promise<string> {
+        try {
+            const tx = await this.program.methods.migrateProject()
+                .accounts({
+                    project: projectAccount,
+                    payer: this.provider.wallet.publicKey,
+                    systemProgram: web3.SystemProgram.programId,
+                })
+                .rpc();
+
+            return tx;
+        } catch (error) {
+            console.error('Project migration failed:', error);
+            throw error;
+        }
+    }
+
+    async migrateProposal(
+        proposalAccount: web3.PublicKey,
+    ): Promise<string> {
+        try {
+            const tx = await this.program.methods.migrateProposal()
+                .accounts({
+                    proposal: proposalAccount,
+                    payer: this.provider.wallet.publicKey,
+                    systemProgram: web3.SystemProgram.programId,
+                })
+                .rpc();
+
+            return tx;
+        } catch (error) {
+            console.error('Proposal migration failed:', error);
+            throw error;
+        }
+    }
+
     async createProposal(
         projectAccount: web3.PublicKey,
         description: string,
@@ -250,9 +555,11 @@ export class PiDaosFun {
         votingPeriod: BN,
     ): Promise<web3.PublicKey> {
         try {
+            // Proposals are indexed per project
+            const project = await this.program.account.projectState.fetch(projectAccount);
             const [proposalAccount] = await this.findProposalAddress(
                 projectAccount,
-                this.provider.wallet.publicKey
+                project.proposalCount as BN
             );
 
             await this.program.methods.createProposal(
@@ -280,9 +587,14 @@ export class PiDaosFun {
         support: boolean,
     ): Promise<string> {
         try {
+            const proposal = await this.program.account.proposal.fetch(
+                proposalAccount
+            ) as ProposalState;
+
             const tx = await this.program.methods.castVote(support)
                 .accounts({
                     proposal: proposalAccount,
+                    project: proposal.project,
                     voter: this.provider.wallet.publicKey,
                 })
                 .rpc();
@@ -339,32 +651,198 @@ export class PiDaosFun {
         }
     }
 
-    private async findProjectAddress(
-        authority: web3.PublicKey,
+    // Optional accounts of expire_project, all null when the project holds no LP
+    private async projectLiquidityAccounts(
+        projectLiquidity?: { pool: web3.PublicKey; projectLpAccount: web3.PublicKey },
+    ) {
+        if (!projectLiquidity) {
+            return {
+                pool: null, observations: null, poolAuthority: null, tokenA: null, tokenB: null,
+                tokenAReserve: null, tokenBReserve: null, lpTokenMint: null, projectLpAccount: null,
+                tokenAProgram: null, tokenBProgram: null, lpTokenProgram: null,
+            };
+        }
+        const { pool, projectLpAccount } = projectLiquidity;
+        const state = await this.program.account.poolState.fetch(pool);
+        const poolPda = (seed: string) => web3.PublicKey.findProgramAddressSync(
+            [Buffer.from(seed), pool.toBuffer()],
+            this.program.programId
+        )[0];
+        return {
+            pool,
+            observations: poolPda('observations'),
+            poolAuthority: poolPda('pool_authority'),
+            tokenA: state.tokenA,
+            tokenB: state.tokenB,
+            tokenAReserve: state.tokenAReserve,
+            tokenBReserve: state.tokenBReserve,
+            lpTokenMint: state.lpTokenMint,
+            projectLpAccount,
+            tokenAProgram: await this.getTokenProgramId(state.tokenA),
+            tokenBProgram: await this.getTokenProgramId(state.tokenB),
+            lpTokenProgram: await this.getTokenProgramId(state.lpTokenMint),
+        };
+    }
+
+    async findProjectAddress(
+        slug: string,
     ): Promise<[web3.PublicKey, number]> {
         return await web3.PublicKey.findProgramAddress(
             [
                 Buffer.from('project'),
-                authority.toBuffer(),
+                Buffer.from(slug),
+            ],
+            this.program.programId
+        );
+    }
+
+    async findPlatformAddress(): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [Buffer.from('platform_config')],
+            this.program.programId
+        );
+    }
+
+    async findProjectEntryAddress(
+        index: BN,
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [
+                Buffer.from('project_entry'),
+                index.toArrayLike(Buffer, 'le', 8),
+            ],
+            this.program.programId
+        );
+    }
+
+    private async findProjectMintAddress(
+        project: web3.PublicKey,
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [
+                Buffer.from('project_mint'),
+                project.toBuffer(),
+            ],
+            this.program.programId
+        );
+    }
+
+    private async findProjectSignerAddress(
+        project: web3.PublicKey,
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [
+                Buffer.from('project_signer'),
+                project.toBuffer(),
             ],
             this.program.programId
         );
     }
 
-    private async findProposalAddress(
+    private async findTreasuryAddress(
         project: web3.PublicKey,
-        creator: web3.PublicKey,
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [
+                Buffer.from('treasury'),
+                project.toBuffer(),
+            ],
+            this.program.programId
+        );
+    }
+
+    private async findVestingAddress(
+        project: web3.PublicKey,
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [
+                Buffer.from('vesting'),
+                project.toBuffer(),
+            ],
+            this.program.programId
+        );
+    }
+
+    private async findVestingVaultAddress(
+        project: web3.PublicKey,
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [
+                Buffer.from('vesting_vault'),
+                project.toBuffer(),
+            ],
+            this.program.programId
+        );
+    }
+
+    private async findInvestorVaultAddress(
+        project: web3.PublicKey,
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [
+                Buffer.from('investor_vault'),
+                project.toBuffer(),
+            ],
+            this.program.programId
+        );
+    }
+
+    private async findInvestorLockAddress(
+        project: web3.PublicKey,
+        investor: web3.PublicKey,
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [
+                Buffer.from('investor_lock'),
+                project.toBuffer(),
+                investor.toBuffer(),
+            ],
+            this.program.programId
+        );
+    }
+
+    async findProposalAddress(
+        project: web3.PublicKey,
+        index: BN,
     ): Promise<[web3.PublicKey, number]> {
         return await web3.PublicKey.findProgramAddress(
             [
                 Buffer.from('proposal'),
                 project.toBuffer(),
-                creator.toBuffer(),
+                index.toArrayLike(Buffer, 'le', 8),
             ],
             this.program.programId
         );
     }
 
+    private async getTokenProgramId(
+        mint: web3.PublicKey,
+    ): Promise<web3.PublicKey> {
+        // A mint is owned by whichever token program created it
+        const mintInfo = await this.provider.connection.getAccountInfo(mint);
+        if (!mintInfo) {
+            throw new Error(`Mint ${mint.toBase58()} not found`);
+        }
+        return mintInfo.owner;
+    }
+
+    private async getOrCreateTokenAccount(
+        mint: web3.PublicKey,
+        owner: web3.PublicKey,
+    ): Promise<web3.PublicKey> {
+        const tokenAccount = await getOrCreateAssociatedTokenAccount(
+            this.provider.connection,
+            this.provider.wallet.payer,
+            mint,
+            owner,
+            false,
+            undefined,
+            undefined,
+            await this.getTokenProgramId(mint)
+        );
+        return tokenAccount.address;
+    }
+
     private async verifyPiNetworkSignature(
         signature: string,
     ): Promise<boolean> {
@@ -1,10 +1,21 @@
 import { web3, BN } from '@project-serum/anchor';
 
 export interface ProjectState {
+    version: number;
     isInitialized: boolean;
+    isPaused: boolean;
+    bump: number;
+    slug: string;
     authority: web3.PublicKey;
     projectTokenMint: web3.PublicKey;
+    quoteMint: web3.PublicKey;
+    treasuryVault: web3.PublicKey;
+    signerBump: number;
     totalSupply: BN;
+    vestingSupply: BN;
+    saleSupply: BN;
+    saleMinted: BN;
+    mintedSupply: BN;
     initialPiPrice: BN;
     currentPiPrice: BN;
     startTime: BN;
@@ -18,10 +29,37 @@ export interface ProjectState {
     totalInvestment: BN;
     liquidityAmount: BN;
     investmentAmount: BN;
+    guardian: web3.PublicKey | null;
+    multisig: web3.PublicKey | null;
+    investorVault: web3.PublicKey;
+    lockup: InvestorLockup | null;
     inviteOnly: boolean;
     inviteCode: string;
     refundsEnabled: boolean;
+    proposalCount: BN;
     status: ProjectStatus;
+    protocolFeeOwed: BN; // Fundraise fee still held in the treasury
+    registryIndex: BN; // Position in the platform's project registry
+}
+
+export interface PlatformConfig {
+    version: number;
+    bump: number;
+    multisig: web3.PublicKey; // Only this multisig may change the platform
+    feeRecipient: web3.PublicKey;
+    fundraiseFeeBps: number; // Charged on the raise at finalization (1/10000)
+    swapProtocolFeeBps: number; // Platform share of each swap fee (1/10000)
+    isPaused: boolean; // Halts launches, investments and swaps
+    projectCount: BN;
+    piVerifier: web3.PublicKey; // Default while unset
+    allowedQuoteMints: web3.PublicKey[];
+}
+
+export interface ProjectEntry {
+    version: number;
+    index: BN;
+    project: web3.PublicKey;
+    createdAt: BN;
 }
 
 export enum ProjectStatus {
@@ -32,12 +70,55 @@ export enum ProjectStatus {
 }
 
 export interface ProjectConfig {
+    slug: string; // Unique, up to 32 bytes; seeds the project address
     totalSupply: BN;
+    saleSupply: BN; // Portion of total supply reserved for the sale
     minRaise: BN;
     maxRaise: BN;
     minInvestment: BN;
     maxInvestment: BN;
     duration: BN; // Project duration in seconds after fundraise
+    guardian: web3.PublicKey | null; // May cancel the raise at any time
+    multisig: web3.PublicKey | null; // May cancel the raise at any time
+    lockup: InvestorLockup | null; // Optional investor lockup after launch
+}
+
+export interface TokenConfig {
+    name: string;
+    symbol: string;
+    uri: string;
+    decimals: number;
+    revokeFreezeAuthority: boolean;
+}
+
+export interface InvestorLockup {
+    tgeUnlockBps: number; // Share released at launch (1/10000)
+    unlockDuration: BN; // Linear release period in seconds
+}
+
+export interface InvestorLock {
+    project: web3.PublicKey;
+    investor: web3.PublicKey;
+    totalAmount: BN;
+    claimedAmount: BN;
+}
+
+export interface VestingAllocation {
+    beneficiary: web3.PublicKey;
+    amount: BN;
+    cliff: BN; // Seconds after fundraise end before release starts
+    duration: BN; // Linear release period after the cliff
+    revocable: boolean;
+}
+
+export interface VestingSchedule {
+    beneficiary: web3.PublicKey;
+    totalAmount: BN;
+    claimedAmount: BN;
+    cliffTime: BN;
+    endTime: BN;
+    revocable: boolean;
+    revoked: boolean;
 }
 
 export interface InvestmentInfo {
@@ -49,6 +130,9 @@ export interface InvestmentInfo {
 
 export interface ProposalState {
     id: BN;
+    version: number;
+    project: web3.PublicKey;
+    index: BN;
     creator: web3.PublicKey;
     startTime: BN;
     endTime: BN;
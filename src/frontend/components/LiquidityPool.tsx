@@ -21,7 +21,7 @@ import {
 } from '@chakra-ui/react';
 import { useWallet } from '@solana/wallet-adapter-react';
 import { BN } from '@project-serum/anchor';
-import { LiquidityPoolService, PoolInfo } from '../../services/LiquidityPoolService';
+import { LiquidityPoolService, MINIMUM_LIQUIDITY, PoolInfo } from '../../services/LiquidityPoolService';
 import { useProgram } from '../hooks/useProgram';
 
 interface LiquidityPoolProps {
@@ -71,6 +71,7 @@ export const LiquidityPool: React.FC<LiquidityPoolProps> = ({ poolAddress }) =>
                 // Add user token accounts
                 new BN(amountA),
                 new BN(amountB),
+                new BN(calculateMinLpOut(amountA, amountB)),
                 // Add authority
             );
 
@@ -104,6 +105,7 @@ export const LiquidityPool: React.FC<LiquidityPoolProps> = ({ poolAddress }) =>
                 new PublicKey(poolAddress),
                 // Add user token accounts
                 new BN(lpAmount),
+                ...calculateMinWithdrawal(lpAmount).map((amount) => new BN(amount)),
                 // Add authority
             );
 
@@ -169,6 +171,30 @@ export const LiquidityPool: React.FC<LiquidityPoolProps> = ({ poolAddress }) =>
         return expectedOut * (1 - slippage / 100);
     };
 
+    // The first deposit mints sqrt(a * b) less the locked minimum; later ones
+    // mint in proportion to the smaller side
+    const calculateMinLpOut = (maxA: number, maxB: number): number => {
+        if (!poolInfo) return 0;
+        const total = poolInfo.totalLiquidity.toNumber();
+        const expectedLp = total === 0
+            ? Math.max(Math.sqrt(maxA * maxB) - MINIMUM_LIQUIDITY, 0)
+            : Math.min(
+                (maxA * total) / poolInfo.tokenAAmount.toNumber(),
+                (maxB * total) / poolInfo.tokenBAmount.toNumber()
+            );
+        return Math.floor(expectedLp * (1 - slippage / 100));
+    };
+
+    // Pro-rata share of each reserve, less the slippage allowance
+    const calculateMinWithdrawal = (lp: number): [number, number] => {
+        if (!poolInfo || poolInfo.totalLiquidity.isZero()) return [0, 0];
+        const share = lp / poolInfo.totalLiquidity.toNumber();
+        return [
+            Math.floor(poolInfo.tokenAAmount.toNumber() * share * (1 - slippage / 100)),
+            Math.floor(poolInfo.tokenBAmount.toNumber() * share * (1 - slippage / 100)),
+        ];
+    };
+
     if (!poolInfo) {
         return <Text>Loading pool information...</Text>;
     }
@@ -1,4 +1,4 @@
-import React, { useState } from 'react';
+import React, { useEffect, useState } from 'react';
 import {
   Box,
   Container,
@@ -72,6 +72,34 @@ interface Project {
   tags: string[];
 }
 
+// Map an on-chain ProjectState onto the card model; descriptive metadata lives off-chain
+const toProject = (id: string, state: any): Project => {
+  const status: Project['status'] =
+    state.status.fundraising ? 'active' :
+    state.status.refunding ? 'cancelled' :
+    'completed';
+
+  return {
+    id,
+    name: state.slug,
+    description: '',
+    status,
+    totalSupply: state.totalSupply.toNumber(),
+    currentPrice: state.currentPiPrice.toNumber(),
+    startTime: new Date(state.startTime.toNumber() * 1000),
+    endTime: new Date(state.fundraiseEndTime.toNumber() * 1000),
+    minInvestment: state.minInvestment.toNumber(),
+    maxInvestment: state.maxInvestment.toNumber(),
+    piHolderMultiplier: 1,
+    totalInvestors: state.totalInvestors.toNumber(),
+    totalInvestment: state.totalInvestment.toNumber(),
+    targetRaise: state.maxRaise.toNumber(),
+    creator: state.authority.toBase58(),
+    category: 'uncategorized',
+    tags: [],
+  };
+};
+
 export const Projects: React.FC = () => {
   const navigate = useNavigate();
   const toast = useToast();
@@ -99,29 +127,25 @@ export const Projects: React.FC = () => {
     tags: '',
   });
 
-  // Mock data - replace with actual API call
-  const projects: Project[] = [
-    {
-      id: '1',
-      name: 'DeFi Lending Protocol',
-      description: 'Decentralized lending platform with unique features for Pi holders',
-      status: 'active',
-      totalSupply: 1000000,
-      currentPrice: 0.5,
-      startTime: new Date('2024-02-01'),
-      endTime: new Date('2024-02-08'),
-      minInvestment: 0.1,
-      maxInvestment: 100,
-      piHolderMultiplier: 2,
-      totalInvestors: 150,
-      totalInvestment: 50000,
-      targetRaise: 100000,
-      creator: 'team@defilending.com',
-      category: 'defi',
-      tags: ['lending', 'yield', 'staking'],
-    },
-    // Add more projects...
-  ];
+  const [projects, setProjects] = useState<Project[]>([]);
+
+  useEffect(() => {
+    fetchProjects();
+  }, [projectService]);
+
+  // Launches are enumerated from the on-chain platform registry
+  const fetchProjects = async () => {
+    if (!projectService) return;
+    try {
+      setLoading(true);
+      const launches = await projectService.listProjects();
+      setProjects(launches.map(({ address, state }) => toProject(address.toBase58(), state)));
+    } catch (error) {
+      setError(error.message);
+    } finally {
+      setLoading(false);
+    }
+  };
 
   const handleCreateProject = async () => {
     try {
@@ -137,6 +161,7 @@ export const Projects: React.FC = () => {
         isClosable: true,
       });
       onClose();
+      fetchProjects();
     } catch (error) {
       toast({
         title: 'Error',
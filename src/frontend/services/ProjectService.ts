@@ -17,6 +17,7 @@ export class ProjectService {
     async createProject(
         authority: web3.PublicKey,
         config: {
+            slug: string;
             totalSupply: number;
             startPrice: number;
             duration: number;
@@ -26,10 +27,14 @@ export class ProjectService {
         }
     ): Promise<string> {
         try {
-            const [projectAccount] = await this.findProjectAddress(authority);
+            const [projectAccount] = await this.findProjectAddress(config.slug);
+            const [platform] = await this.findPlatformAddress();
+            const { projectCount } = await this.program.account.platformConfig.fetch(platform);
+            const [projectEntry] = await this.findProjectEntryAddress(projectCount as BN);
 
             const tx = await this.program.methods
                 .initializeProject({
+                    slug: config.slug,
                     totalSupply: new BN(config.totalSupply),
                     startPrice: new BN(config.startPrice),
                     duration: new BN(config.duration),
@@ -40,6 +45,8 @@ export class ProjectService {
                 .accounts({
                     project: projectAccount,
                     authority,
+                    platform,
+                    projectEntry,
                     systemProgram: web3.SystemProgram.programId,
                     tokenProgram: TOKEN_PROGRAM_ID,
                 })
@@ -69,6 +76,7 @@ export class ProjectService {
                 .accounts({
                     project: projectAccount,
                     investor: this.program.provider.publicKey,
+                    platform: (await this.findPlatformAddress())[0],
                     systemProgram: web3.SystemProgram.programId,
                     tokenProgram: TOKEN_PROGRAM_ID,
                 })
@@ -88,9 +96,10 @@ export class ProjectService {
         votingPeriod: number
     ): Promise<string> {
         try {
+            const project = await this.program.account.projectState.fetch(projectAccount);
             const [proposalAccount] = await this.findProposalAddress(
                 projectAccount,
-                this.program.provider.publicKey!
+                project.proposalCount as BN
             );
 
             const tx = await this.program.methods
@@ -140,6 +149,33 @@ export class ProjectService {
         }
     }
 
+    // Every launch in registry order, read from the platform's project entries
+    async listProjects(): Promise<{ address: web3.PublicKey; state: any }[]> {
+        try {
+            const [platform] = await this.findPlatformAddress();
+            const { projectCount } = await this.program.account.platformConfig.fetch(platform);
+
+            const entryAddresses = await Promise.all(
+                Array.from({ length: (projectCount as BN).toNumber() }, async (_, index) =>
+                    (await this.findProjectEntryAddress(new BN(index)))[0]
+                )
+            );
+            const entries = await this.program.account.projectEntry.fetchMultiple(entryAddresses);
+            const addresses = entries
+                .filter((entry) => entry !== null)
+                .map((entry: any) => entry.project as web3.PublicKey);
+            const states = await this.program.account.projectState.fetchMultiple(addresses);
+
+            // Closing a project also closes its entry, leaving a gap in the indices
+            return addresses
+                .map((address, i) => ({ address, state: states[i] }))
+                .filter((project) => project.state !== null);
+        } catch (error) {
+            console.error('Failed to list projects:', error);
+            throw error;
+        }
+    }
+
     async getProposal(proposalAccount: web3.PublicKey): Promise<any> {
         try {
             const proposal = await this.program.account.proposal.fetch(proposalAccount);
@@ -150,21 +186,100 @@ export class ProjectService {
         }
     }
 
+    async getUnlockSchedule(
+        projectAccount: web3.PublicKey,
+        investor: web3.PublicKey
+    ): Promise<{
+        totalAmount: BN;
+        claimedAmount: BN;
+        unlockedAmount: BN;
+        tgeTime: number;
+        fullUnlockTime: number;
+    } | null> {
+        try {
+            const project = await this.program.account.project.fetch(projectAccount);
+            if (!project.lockup) {
+                return null;
+            }
+
+            const [investorLockAccount] = await this.findInvestorLockAddress(projectAccount, investor);
+            const investorLock = await this.program.account.investorLock.fetchNullable(investorLockAccount);
+            if (!investorLock) {
+                return null;
+            }
+
+            // Mirrors InvestorLockup::unlocked_amount in the program
+            const totalAmount: BN = investorLock.totalAmount;
+            const tgeTime: number = project.fundraiseEndTime.toNumber();
+            const unlockDuration: number = project.lockup.unlockDuration.toNumber();
+            const now = Math.floor(Date.now() / 1000);
+
+            let unlockedAmount = new BN(0);
+            if (now >= tgeTime + unlockDuration) {
+                unlockedAmount = totalAmount;
+            } else if (now >= tgeTime) {
+                const tgeAmount = totalAmount.mul(new BN(project.lockup.tgeUnlockBps)).div(new BN(10000));
+                const linearAmount = totalAmount
+                    .sub(tgeAmount)
+                    .mul(new BN(now - tgeTime))
+                    .div(new BN(unlockDuration));
+                unlockedAmount = tgeAmount.add(linearAmount);
+            }
+
+            return {
+                totalAmount,
+                claimedAmount: investorLock.claimedAmount,
+                unlockedAmount,
+                tgeTime,
+                fullUnlockTime: tgeTime + unlockDuration,
+            };
+        } catch (error) {
+            console.error('Failed to fetch unlock schedule:', error);
+            throw error;
+        }
+    }
+
     private async findProjectAddress(
-        authority: web3.PublicKey
+        slug: string
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [Buffer.from('project'), Buffer.from(slug)],
+            this.program.programId
+        );
+    }
+
+    private async findPlatformAddress(): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [Buffer.from('platform_config')],
+            this.program.programId
+        );
+    }
+
+    private async findProjectEntryAddress(
+        index: BN
+    ): Promise<[web3.PublicKey, number]> {
+        return await web3.PublicKey.findProgramAddress(
+            [Buffer.from('project_entry'), index.toArrayLike(Buffer, 'le', 8)],
+            this.program.programId
+        );
+    }
+
+    private async findInvestorLockAddress(
+        project: web3.PublicKey,
+        investor: web3.PublicKey
     ): Promise<[web3.PublicKey, number]> {
         return await web3.PublicKey.findProgramAddress(
-            [Buffer.from('project'), authority.toBuffer()],
+            [Buffer.from('investor_lock'), project.toBuffer(), investor.toBuffer()],
             this.program.programId
         );
     }
 
     private async findProposalAddress(
         project: web3.PublicKey,
-        creator: web3.PublicKey
+        index: BN
     ): Promise<[web3.PublicKey, number]> {
         return await web3.PublicKey.findProgramAddress(
-            [Buffer.from('proposal'), project.toBuffer(), creator.toBuffer()],
+            [Buffer.from('proposal'), project.toBuffer(), index.toArrayLike(Buffer, 'le', 8)],
             this.program.programId
         );
     }
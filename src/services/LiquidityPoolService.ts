@@ -1,7 +1,13 @@
 import { Program, web3, BN } from '@project-serum/anchor';
-import { TOKEN_PROGRAM_ID, Token } from '@solana/spl-token';
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
 import { Connection, PublicKey } from '@solana/web3.js';
 
+// Mirrors pool::MINIMUM_LIQUIDITY, locked out of the first deposit
+export const MINIMUM_LIQUIDITY = 1_000;
+
+// How long a signed pool transaction stays valid before the program rejects it
+const DEADLINE_SECONDS = 120;
+
 export interface PoolInfo {
     poolAddress: PublicKey;
     tokenAReserve: PublicKey;
@@ -13,6 +19,13 @@ export interface PoolInfo {
     fee: number;
 }
 
+export interface TwapInfo {
+    priceA: number; // Token B per token A
+    priceB: number; // Token A per token B
+    startTime: number;
+    endTime: number;
+}
+
 export class LiquidityPoolService {
     private program: Program;
     private connection: Connection;
@@ -25,52 +38,38 @@ export class LiquidityPoolService {
     async createPool(
         tokenA: PublicKey,
         tokenB: PublicKey,
-        authority: web3.Keypair
+        authority: web3.Keypair,
+        amp?: number // Pegged pairs pass an amplification to use the StableSwap curve
     ): Promise<PoolInfo> {
         try {
-            // Create LP token mint
-            const lpTokenMint = await Token.createMint(
-                this.connection,
-                authority,
-                authority.publicKey,
-                null,
-                9,
-                TOKEN_PROGRAM_ID
-            );
+            // The program only accepts the pair in sorted order
+            [tokenA, tokenB] = LiquidityPoolService.sortMints(tokenA, tokenB);
 
             // Find pool address
             const [poolAddress] = await this.findPoolAddress(tokenA, tokenB);
 
-            // Create token accounts for pool reserves
-            const tokenAReserve = await Token.createAccount(
-                this.connection,
-                authority,
-                tokenA,
-                poolAddress,
-                TOKEN_PROGRAM_ID
-            );
-
-            const tokenBReserve = await Token.createAccount(
-                this.connection,
-                authority,
-                tokenB,
-                poolAddress,
-                TOKEN_PROGRAM_ID
-            );
+            // The program creates the reserves and the LP mint under its pool authority
+            const tokenAReserve = this.findPoolPda('reserve_a', poolAddress);
+            const tokenBReserve = this.findPoolPda('reserve_b', poolAddress);
+            const lpTokenMint = this.findPoolPda('lp_mint', poolAddress);
 
             // Initialize pool
             await this.program.methods
-                .initializePool({
-                    fee: new BN(30), // 0.3% fee
-                })
+                .initializePool(
+                    new BN(30), // 0.3% fee
+                    amp ? { stableSwap: { amp: new BN(amp) } } : { constantProduct: {} }
+                )
                 .accounts({
                     pool: poolAddress,
                     tokenA: tokenA,
                     tokenB: tokenB,
                     tokenAReserve: tokenAReserve,
                     tokenBReserve: tokenBReserve,
-                    lpTokenMint: lpTokenMint.publicKey,
+                    poolAuthority: this.findPoolPda('pool_authority', poolAddress),
+                    lpTokenMint: lpTokenMint,
+                    observations: this.findPoolPda('observations', poolAddress),
                     authority: authority.publicKey,
+                    ...(await this.mintPrograms(tokenA, tokenB)),
                     tokenProgram: TOKEN_PROGRAM_ID,
                     systemProgram: web3.SystemProgram.programId,
                 })
@@ -81,7 +80,7 @@ export class LiquidityPoolService {
                 poolAddress,
                 tokenAReserve,
                 tokenBReserve,
-                lpTokenMint: lpTokenMint.publicKey,
+                lpTokenMint,
                 totalLiquidity: new BN(0),
                 tokenAAmount: new BN(0),
                 tokenBAmount: new BN(0),
@@ -98,19 +97,29 @@ export class LiquidityPoolService {
         userTokenAAccount: PublicKey,
         userTokenBAccount: PublicKey,
         userLpTokenAccount: PublicKey,
-        amountA: BN,
-        amountB: BN,
+        maxAmountA: BN,
+        maxAmountB: BN,
+        minLpOut: BN,
         authority: web3.Keypair
     ): Promise<string> {
         try {
+            const state = await this.program.account.poolState.fetch(pool);
             const tx = await this.program.methods
-                .addLiquidity(amountA, amountB)
+                .addLiquidity(maxAmountA, maxAmountB, minLpOut, LiquidityPoolService.deadline())
                 .accounts({
                     pool,
+                    tokenA: state.tokenA,
+                    tokenB: state.tokenB,
+                    tokenAReserve: state.tokenAReserve,
+                    tokenBReserve: state.tokenBReserve,
+                    poolAuthority: this.findPoolPda('pool_authority', pool),
+                    observations: this.findPoolPda('observations', pool),
+                    lpTokenMint: state.lpTokenMint,
                     userTokenAAccount,
                     userTokenBAccount,
                     userLpTokenAccount,
                     authority: authority.publicKey,
+                    ...(await this.mintPrograms(state.tokenA, state.tokenB)),
                     tokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([authority])
@@ -129,17 +138,28 @@ export class LiquidityPoolService {
         userTokenBAccount: PublicKey,
         userLpTokenAccount: PublicKey,
         lpAmount: BN,
+        minAOut: BN,
+        minBOut: BN,
         authority: web3.Keypair
     ): Promise<string> {
         try {
+            const state = await this.program.account.poolState.fetch(pool);
             const tx = await this.program.methods
-                .removeLiquidity(lpAmount)
+                .removeLiquidity(lpAmount, minAOut, minBOut, LiquidityPoolService.deadline())
                 .accounts({
                     pool,
+                    tokenA: state.tokenA,
+                    tokenB: state.tokenB,
+                    tokenAReserve: state.tokenAReserve,
+                    tokenBReserve: state.tokenBReserve,
+                    poolAuthority: this.findPoolPda('pool_authority', pool),
+                    observations: this.findPoolPda('observations', pool),
+                    lpTokenMint: state.lpTokenMint,
                     userTokenAAccount,
                     userTokenBAccount,
                     userLpTokenAccount,
                     authority: authority.publicKey,
+                    ...(await this.mintPrograms(state.tokenA, state.tokenB)),
                     tokenProgram: TOKEN_PROGRAM_ID,
                 })
                 .signers([authority])
@@ -161,14 +181,25 @@ export class LiquidityPoolService {
         authority: web3.Keypair
     ): Promise<string> {
         try {
+            const state = await this.program.account.poolState.fetch(pool);
+            const launchAccounts = await this.launchGuardAccounts(pool, state, authority.publicKey);
             const tx = await this.program.methods
-                .swap(amount, minAmountOut)
+                .swap(amount, minAmountOut, LiquidityPoolService.deadline())
                 .accounts({
                     pool,
+                    tokenA: state.tokenA,
+                    tokenB: state.tokenB,
+                    tokenAReserve: state.tokenAReserve,
+                    tokenBReserve: state.tokenBReserve,
+                    poolAuthority: this.findPoolPda('pool_authority', pool),
+                    observations: this.findPoolPda('observations', pool),
+                    platform: this.findPlatformAddress(),
                     userSourceAccount,
                     userDestinationAccount,
                     authority: authority.publicKey,
-                    tokenProgram: TOKEN_PROGRAM_ID,
+                    ...(await this.mintPrograms(state.tokenA, state.tokenB)),
+                    systemProgram: web3.SystemProgram.programId,
+                    ...launchAccounts,
                 })
                 .signers([authority])
                 .rpc();
@@ -180,9 +211,60 @@ export class LiquidityPoolService {
         }
     }
 
+    // Swaps along `path`, a list of mints from the one sold to the one bought,
+    // through the pool for each consecutive pair in a single transaction
+    async routeSwap(
+        path: PublicKey[],
+        userSourceAccount: PublicKey,
+        userDestinationAccount: PublicKey,
+        amount: BN,
+        minAmountOut: BN,
+        authority: web3.Keypair
+    ): Promise<string> {
+        try {
+            const hops: web3.AccountMeta[] = [];
+            for (let i = 0; i + 1 < path.length; i++) {
+                const [mintIn, mintOut] = [path[i], path[i + 1]];
+                const [pool] = await this.findPoolAddress(mintIn, mintOut);
+                const state = await this.program.account.poolState.fetch(pool);
+                const aToB = state.tokenA.equals(mintIn);
+                const [reserveIn, reserveOut] = aToB
+                    ? [state.tokenAReserve, state.tokenBReserve]
+                    : [state.tokenBReserve, state.tokenAReserve];
+                // Same order as pool::RouteSwap expects
+                hops.push(
+                    { pubkey: pool, isSigner: false, isWritable: true },
+                    { pubkey: this.findPoolPda('observations', pool), isSigner: false, isWritable: true },
+                    { pubkey: this.findPoolPda('pool_authority', pool), isSigner: false, isWritable: false },
+                    { pubkey: reserveIn, isSigner: false, isWritable: true },
+                    { pubkey: reserveOut, isSigner: false, isWritable: true },
+                    { pubkey: mintIn, isSigner: false, isWritable: false },
+                    { pubkey: mintOut, isSigner: false, isWritable: false },
+                    { pubkey: await this.tokenProgramOf(mintOut), isSigner: false, isWritable: false },
+                );
+            }
+
+            return await this.program.methods
+                .routeSwap(amount, minAmountOut, LiquidityPoolService.deadline())
+                .accounts({
+                    userSourceAccount,
+                    userDestinationAccount,
+                    platform: this.findPlatformAddress(),
+                    authority: authority.publicKey,
+                    sourceTokenProgram: await this.tokenProgramOf(path[0]),
+                })
+                .remainingAccounts(hops)
+                .signers([authority])
+                .rpc();
+        } catch (error) {
+            console.error('Failed to route swap:', error);
+            throw error;
+        }
+    }
+
     async getPoolInfo(pool: PublicKey): Promise<PoolInfo> {
         try {
-            const poolAccount = await this.program.account.pool.fetch(pool);
+            const poolAccount = await this.program.account.poolState.fetch(pool);
             return {
                 poolAddress: pool,
                 tokenAReserve: poolAccount.tokenAReserve,
@@ -199,15 +281,129 @@ export class LiquidityPoolService {
         }
     }
 
+    // Time-weighted prices over at least the last `window` seconds, as plain
+    // ratios of the pool's base units. The pool keeps 128 observations, so
+    // windows past 128 observation intervals (about two hours by default) fail
+    async getTwap(pool: PublicKey, window: number): Promise<TwapInfo> {
+        try {
+            const twap = await this.program.methods
+                .getTwap(window)
+                .accounts({
+                    pool,
+                    observations: this.findPoolPda('observations', pool),
+                })
+                .view();
+            return {
+                priceA: LiquidityPoolService.fromQ64(twap.priceA),
+                priceB: LiquidityPoolService.fromQ64(twap.priceB),
+                startTime: twap.startTime.toNumber(),
+                endTime: twap.endTime.toNumber(),
+            };
+        } catch (error) {
+            console.error('Failed to get TWAP:', error);
+            throw error;
+        }
+    }
+
+    // Rewrites a pool from an older zero-copy layout in place; the Borsh-era
+    // pools, whose reserves the pool authority does not own, are rejected
+    async migratePool(pool: PublicKey, payer: web3.Keypair): Promise<string> {
+        try {
+            return await this.program.methods
+                .migratePool()
+                .accounts({
+                    pool,
+                    payer: payer.publicKey,
+                    systemProgram: web3.SystemProgram.programId,
+                })
+                .signers([payer])
+                .rpc();
+        } catch (error) {
+            console.error('Failed to migrate pool:', error);
+            throw error;
+        }
+    }
+
+    // Accounts a buy needs while the pool's launch guards may apply; the
+    // program ignores them once the guards have lapsed
+    private async launchGuardAccounts(
+        pool: PublicKey,
+        state: any,
+        wallet: PublicKey
+    ): Promise<{ launchPurchases: PublicKey | null; piVerification: PublicKey | null }> {
+        if (state.launch.launchedToken === 0) {
+            return { launchPurchases: null, piVerification: null };
+        }
+        const [launchPurchases] = PublicKey.findProgramAddressSync(
+            [Buffer.from('launch_purchases'), pool.toBuffer(), wallet.toBuffer()],
+            this.program.programId
+        );
+        const [piVerification] = PublicKey.findProgramAddressSync(
+            [Buffer.from('pi_verification'), wallet.toBuffer()],
+            this.program.programId
+        );
+        const verified = (await this.connection.getAccountInfo(piVerification)) !== null;
+        return { launchPurchases, piVerification: verified ? piVerification : null };
+    }
+
+    // Either side of a pool may be a legacy SPL or a Token-2022 mint
+    private async tokenProgramOf(mint: PublicKey): Promise<PublicKey> {
+        const info = await this.connection.getAccountInfo(mint);
+        if (!info) {
+            throw new Error(`Mint ${mint.toBase58()} not found`);
+        }
+        return info.owner;
+    }
+
+    private async mintPrograms(
+        tokenA: PublicKey,
+        tokenB: PublicKey
+    ): Promise<{ tokenAProgram: PublicKey; tokenBProgram: PublicKey }> {
+        return {
+            tokenAProgram: await this.tokenProgramOf(tokenA),
+            tokenBProgram: await this.tokenProgramOf(tokenB),
+        };
+    }
+
+    private findPoolPda(seed: string, pool: PublicKey): PublicKey {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from(seed), pool.toBuffer()],
+            this.program.programId
+        )[0];
+    }
+
+    private findPlatformAddress(): PublicKey {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from('platform_config')],
+            this.program.programId
+        )[0];
+    }
+
+    static fromQ64(value: BN): number {
+        return Number(value.toString()) / 2 ** 64;
+    }
+
+    static deadline(): BN {
+        return new BN(Math.floor(Date.now() / 1000) + DEADLINE_SECONDS);
+    }
+
+    // Pools are keyed by the sorted mint pair, so either order finds the same pool
+    static sortMints(tokenA: PublicKey, tokenB: PublicKey): [PublicKey, PublicKey] {
+        return Buffer.compare(tokenA.toBuffer(), tokenB.toBuffer()) < 0
+            ? [tokenA, tokenB]
+            : [tokenB, tokenA];
+    }
+
     private async findPoolAddress(
         tokenA: PublicKey,
         tokenB: PublicKey
     ): Promise<[PublicKey, number]> {
+        const [mintA, mintB] = LiquidityPoolService.sortMints(tokenA, tokenB);
         return await web3.PublicKey.findProgramAddress(
             [
                 Buffer.from('pool'),
-                tokenA.toBuffer(),
-                tokenB.toBuffer(),
+                mintA.toBuffer(),
+                mintB.toBuffer(),
             ],
             this.program.programId
         );
@@ -0,0 +1,647 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  createMint,
+  createAccount,
+} from '@solana/spl-token';
+import { expect } from 'chai';
+import { allowQuoteMint, findPlatform, registryAccounts } from './utils/platform';
+import { expectRejected } from './utils/assert';
+import {
+  ProjectAccounts,
+  ProjectEnv,
+  createProject,
+  createTokenAccount,
+  fund,
+  invest,
+  noProjectLiquidity,
+  startProjectEnv,
+} from './utils/project';
+
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
+
+// Each test swaps a single account for one the program must reject
+describe('account validation', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+
+  const authority = Keypair.generate();
+  const investor = Keypair.generate();
+  const attacker = Keypair.generate();
+  const multisig = Keypair.generate();
+  const otherMultisig = Keypair.generate();
+  const slug = `validation-${Date.now()}`;
+
+  let project: PublicKey;
+  let proposal: PublicKey;
+  let upgradeProposal: PublicKey;
+  let quoteMint: PublicKey;
+  let fakeMint: PublicKey;
+  let projectSigner: PublicKey;
+  let projectTokenMint: PublicKey;
+  let investorVault: PublicKey;
+  let investorTokenAccount: PublicKey;
+  let investorQuoteAccount: PublicKey;
+  let attackerTokenAccount: PublicKey;
+  let fakeMintAccount: PublicKey;
+
+  const pda = (seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  before(async () => {
+    for (const wallet of [authority, investor, attacker]) {
+      const sig = await provider.connection.requestAirdrop(
+        wallet.publicKey,
+        10 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    quoteMint = await createMint(provider.connection, authority, authority.publicKey, null, 9);
+    await allowQuoteMint(program, provider, quoteMint);
+    fakeMint = await createMint(provider.connection, attacker, attacker.publicKey, null, 9);
+
+    project = pda([Buffer.from('project'), Buffer.from(slug)]);
+    proposal = pda([Buffer.from('proposal'), project.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, 'le', 8)]);
+    upgradeProposal = pda([
+      Buffer.from('upgrade'),
+      multisig.publicKey.toBuffer(),
+      new anchor.BN(0).toArrayLike(Buffer, 'le', 8),
+    ]);
+    projectSigner = pda([Buffer.from('project_signer'), project.toBuffer()]);
+    projectTokenMint = pda([Buffer.from('project_mint'), project.toBuffer()]);
+    investorVault = pda([Buffer.from('investor_vault'), project.toBuffer()]);
+    const [tokenMetadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from('metadata'), TOKEN_METADATA_PROGRAM_ID.toBuffer(), projectTokenMint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+
+    await program.methods
+      .initializeMultisig([authority.publicKey], new anchor.BN(1))
+      .accounts({ multisig: multisig.publicKey, payer: authority.publicKey, systemProgram: SystemProgram.programId })
+      .signers([authority, multisig])
+      .rpc();
+    await program.methods
+      .initializeMultisig([attacker.publicKey], new anchor.BN(1))
+      .accounts({ multisig: otherMultisig.publicKey, payer: attacker.publicKey, systemProgram: SystemProgram.programId })
+      .signers([attacker, otherMultisig])
+      .rpc();
+
+    await program.methods
+      .initializeProject(
+        {
+          slug,
+          totalSupply: new anchor.BN(1_000_000),
+          saleSupply: new anchor.BN(1_000_000),
+          minRaise: new anchor.BN(1),
+          maxRaise: new anchor.BN(1_000_000),
+          minInvestment: new anchor.BN(1),
+          maxInvestment: new anchor.BN(1_000_000),
+          duration: new anchor.BN(7 * 24 * 60 * 60),
+          guardian: null,
+          multisig: multisig.publicKey,
+          lockup: null,
+        },
+        { name: 'Validation', symbol: 'VAL', uri: '', decimals: 6, revokeFreezeAuthority: false },
+        new anchor.BN(1),
+        null,
+        []
+      )
+      .accounts({
+        project,
+        authority: authority.publicKey,
+        ...(await registryAccounts(program)),
+        projectSigner,
+        projectTokenMint,
+        tokenMetadata,
+        quoteMint,
+        treasuryVault: pda([Buffer.from('treasury'), project.toBuffer()]),
+        vesting: pda([Buffer.from('vesting'), project.toBuffer()]),
+        vestingVault: pda([Buffer.from('vesting_vault'), project.toBuffer()]),
+        investorVault,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([authority])
+      .rpc();
+
+    investorTokenAccount = await createAccount(provider.connection, investor, projectTokenMint, investor.publicKey);
+    attackerTokenAccount = await createAccount(provider.connection, attacker, projectTokenMint, attacker.publicKey);
+    fakeMintAccount = await createAccount(provider.connection, attacker, fakeMint, attacker.publicKey);
+    investorQuoteAccount = await createAccount(provider.connection, investor, quoteMint, investor.publicKey);
+
+    await program.methods
+      .createProposal('Validation proposal', [], new anchor.BN(3 * 24 * 60 * 60))
+      .accounts({
+        proposal,
+        project,
+        creator: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    await program.methods
+      .proposeUpgrade(Keypair.generate().publicKey, 'Validation upgrade')
+      .accounts({
+        upgradeProposal,
+        multisig: multisig.publicKey,
+        proposer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+  });
+
+  const investAccounts = (overrides: Record<string, PublicKey>) => ({
+    project,
+    investor: investor.publicKey,
+    userTokenAccount: investorTokenAccount,
+    projectTokenMint,
+    investorVault,
+    projectSigner,
+    investorLock: null,
+    platform: findPlatform(program),
+    systemProgram: SystemProgram.programId,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    ...overrides,
+  });
+
+  it('rejects invest with a substituted project mint', async () => {
+    await expectRejected(
+      program.methods
+        .invest(new anchor.BN(10), null)
+        .accounts(investAccounts({ projectTokenMint: fakeMint, userTokenAccount: fakeMintAccount }))
+        .signers([investor])
+        .rpc(),
+      'InvalidMint'
+    );
+  });
+
+  it("rejects invest into another wallet's token account", async () => {
+    await expectRejected(
+      program.methods
+        .invest(new anchor.BN(10), null)
+        .accounts(investAccounts({ userTokenAccount: attackerTokenAccount }))
+        .signers([investor])
+        .rpc(),
+      'ConstraintTokenOwner'
+    );
+  });
+
+  it('rejects invest with a substituted project signer', async () => {
+    await expectRejected(
+      program.methods
+        .invest(new anchor.BN(10), null)
+        .accounts(investAccounts({ projectSigner: attacker.publicKey }))
+        .signers([investor])
+        .rpc(),
+      'ConstraintSeeds'
+    );
+  });
+
+  it('rejects a vote counted from a foreign-mint token account', async () => {
+    await expectRejected(
+      program.methods
+        .castVote(true)
+        .accounts({
+          proposal,
+          project,
+          voter: attacker.publicKey,
+          voterTokenAccount: fakeMintAccount,
+        })
+        .signers([attacker])
+        .rpc(),
+      'InvalidTokenAccount'
+    );
+  });
+
+  it("rejects a vote counted from someone else's token account", async () => {
+    await expectRejected(
+      program.methods
+        .castVote(true)
+        .accounts({
+          proposal,
+          project,
+          voter: attacker.publicKey,
+          voterTokenAccount: investorTokenAccount,
+        })
+        .signers([attacker])
+        .rpc(),
+      'InvalidTokenAccount'
+    );
+  });
+
+  it('rejects invest while the platform is paused', async () => {
+    const platform = findPlatform(program);
+    const { multisig: platformMultisig } = await program.account.platformConfig.fetch(platform);
+    const admin = { platform, multisig: platformMultisig, signer: provider.wallet.publicKey };
+
+    await program.methods.pausePlatform().accounts(admin).rpc();
+    try {
+      await expectRejected(
+        program.methods
+          .invest(new anchor.BN(10), null)
+          .accounts(investAccounts({}))
+          .signers([investor])
+          .rpc(),
+        'ContractPaused'
+      );
+    } finally {
+      await program.methods.unpausePlatform().accounts(admin).rpc();
+    }
+  });
+
+  // A fresh launch by the attacker, used to probe InitializeProject's input
+  // checks. Slugs are capped at 32 bytes, so suffixes stay within 7
+  const launch = async (
+    suffix: string,
+    launchQuoteMint: PublicKey,
+    quoteTokenProgram: PublicKey,
+    token: { name?: string; symbol?: string; uri?: string } = {},
+    inviteCode: string | null = null
+  ) => {
+    const rejectedSlug = `${slug}-${suffix}`;
+    const rejectedProject = pda([Buffer.from('project'), Buffer.from(rejectedSlug)]);
+    const rejectedMint = pda([Buffer.from('project_mint'), rejectedProject.toBuffer()]);
+    const [rejectedMetadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from('metadata'), TOKEN_METADATA_PROGRAM_ID.toBuffer(), rejectedMint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+
+    return program.methods
+      .initializeProject(
+        {
+          slug: rejectedSlug,
+          totalSupply: new anchor.BN(1_000_000),
+          saleSupply: new anchor.BN(1_000_000),
+          minRaise: new anchor.BN(1),
+          maxRaise: new anchor.BN(1_000_000),
+          minInvestment: new anchor.BN(1),
+          maxInvestment: new anchor.BN(1_000_000),
+          duration: new anchor.BN(7 * 24 * 60 * 60),
+          guardian: null,
+          multisig: null,
+          lockup: null,
+        },
+        { name: 'Rejected', symbol: 'REJ', uri: '', decimals: 6, revokeFreezeAuthority: false, ...token },
+        new anchor.BN(1),
+        inviteCode,
+        []
+      )
+      .accounts({
+        project: rejectedProject,
+        authority: attacker.publicKey,
+        ...(await registryAccounts(program)),
+        projectSigner: pda([Buffer.from('project_signer'), rejectedProject.toBuffer()]),
+        projectTokenMint: rejectedMint,
+        tokenMetadata: rejectedMetadata,
+        quoteMint: launchQuoteMint,
+        treasuryVault: pda([Buffer.from('treasury'), rejectedProject.toBuffer()]),
+        vesting: pda([Buffer.from('vesting'), rejectedProject.toBuffer()]),
+        vestingVault: pda([Buffer.from('vesting_vault'), rejectedProject.toBuffer()]),
+        investorVault: pda([Buffer.from('investor_vault'), rejectedProject.toBuffer()]),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        quoteTokenProgram,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([attacker])
+      .rpc();
+  };
+
+  it('rejects a launch in a quote mint the platform does not allow', async () => {
+    await expectRejected(launch('quote', fakeMint, TOKEN_PROGRAM_ID), 'QuoteMintNotAllowed');
+  });
+
+  it("rejects a launch naming a token program that does not own the quote mint", async () => {
+    await expectRejected(launch('qprog', quoteMint, TOKEN_2022_PROGRAM_ID), 'ConstraintMintTokenProgram');
+  });
+
+  // Mirror the MAX_*_LEN bounds in lib.rs
+  it('rejects a launch title over 32 bytes', async () => {
+    await expectRejected(launch('title', quoteMint, TOKEN_PROGRAM_ID, { name: 'x'.repeat(33) }), 'TitleTooLong');
+  });
+
+  it('rejects a token symbol over 10 bytes', async () => {
+    await expectRejected(launch('symbol', quoteMint, TOKEN_PROGRAM_ID, { symbol: 'x'.repeat(11) }), 'SymbolTooLong');
+  });
+
+  it('rejects a token URI over 200 bytes', async () => {
+    await expectRejected(launch('uri', quoteMint, TOKEN_PROGRAM_ID, { uri: 'x'.repeat(201) }), 'UriTooLong');
+  });
+
+  it('rejects an invite code over 32 bytes', async () => {
+    await expectRejected(launch('invite', quoteMint, TOKEN_PROGRAM_ID, {}, 'x'.repeat(33)), 'InviteCodeTooLong');
+  });
+
+  it('rejects a proposal description over 256 bytes', async () => {
+    const { proposalCount } = await program.account.projectState.fetch(project);
+    await expectRejected(
+      program.methods
+        .createProposal('x'.repeat(257), [], new anchor.BN(3 * 24 * 60 * 60))
+        .accounts({
+          proposal: pda([Buffer.from('proposal'), project.toBuffer(), proposalCount.toArrayLike(Buffer, 'le', 8)]),
+          project,
+          creator: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc(),
+      'DescriptionTooLong'
+    );
+  });
+
+  it('rejects approving an upgrade through an unrelated multisig', async () => {
+    await expectRejected(
+      program.methods
+        .approveUpgrade()
+        .accounts({
+          upgradeProposal,
+          multisig: otherMultisig.publicKey,
+          approver: attacker.publicKey,
+        })
+        .signers([attacker])
+        .rpc(),
+      'InvalidMultisig'
+    );
+  });
+
+  it('rejects executing an upgrade through an unrelated multisig', async () => {
+    await expectRejected(
+      program.methods
+        .executeUpgrade()
+        .accounts({
+          upgradeProposal,
+          multisig: otherMultisig.publicKey,
+          executor: attacker.publicKey,
+        })
+        .signers([attacker])
+        .rpc(),
+      'InvalidMultisig'
+    );
+  });
+
+  it('rejects finalize from a signer other than the project authority', async () => {
+    await expectRejected(
+      program.methods
+        .finalizeFundraise()
+        .accounts({
+          project,
+          authority: attacker.publicKey,
+          platform: findPlatform(program),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attacker])
+        .rpc(),
+      'UnauthorizedAccess'
+    );
+  });
+
+  it('rejects pause from a signer other than the project authority', async () => {
+    await expectRejected(
+      program.methods
+        .pauseContract()
+        .accounts({ project, admin: attacker.publicKey })
+        .signers([attacker])
+        .rpc(),
+      'UnauthorizedAccess'
+    );
+  });
+
+  it('rejects cancelling through a multisig the project does not use', async () => {
+    await expectRejected(
+      program.methods
+        .cancelFundraise(0)
+        .accounts({
+          project,
+          multisig: otherMultisig.publicKey,
+          canceller: attacker.publicKey,
+        })
+        .signers([attacker])
+        .rpc(),
+      'InvalidMultisig'
+    );
+  });
+
+  it('rejects revoking vesting through a multisig the project does not use', async () => {
+    await expectRejected(
+      program.methods
+        .revokeVesting(0)
+        .accounts({
+          project,
+          vesting: pda([Buffer.from('vesting'), project.toBuffer()]),
+          vestingVault: pda([Buffer.from('vesting_vault'), project.toBuffer()]),
+          projectTokenMint,
+          multisig: otherMultisig.publicKey,
+          revoker: attacker.publicKey,
+          projectSigner,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([attacker])
+        .rpc(),
+      'InvalidMultisig'
+    );
+  });
+
+  const redeemAccounts = (overrides: Record<string, PublicKey>) => ({
+    project,
+    investor: investor.publicKey,
+    userTokenAccount: investorTokenAccount,
+    projectTokenMint,
+    quoteMint,
+    userQuoteAccount: investorQuoteAccount,
+    treasuryVault: pda([Buffer.from('treasury'), project.toBuffer()]),
+    vesting: pda([Buffer.from('vesting'), project.toBuffer()]),
+    vestingVault: pda([Buffer.from('vesting_vault'), project.toBuffer()]),
+    investorVault: pda([Buffer.from('investor_vault'), project.toBuffer()]),
+    projectSigner,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    quoteTokenProgram: TOKEN_PROGRAM_ID,
+    ...overrides,
+  });
+
+  const redeem = (overrides: Record<string, PublicKey>) =>
+    program.methods
+      .redeemTokens(new anchor.BN(10))
+      .accounts(redeemAccounts(overrides))
+      .signers([investor])
+      .rpc();
+
+  it("rejects redeeming from another wallet's token account", async () => {
+    await expectRejected(redeem({ userTokenAccount: attackerTokenAccount }), 'ConstraintTokenOwner');
+  });
+
+  it('rejects redeeming from a foreign-mint token account', async () => {
+    await expectRejected(redeem({ userTokenAccount: fakeMintAccount }), 'ConstraintTokenMint');
+  });
+
+  it('rejects a redemption paid into a foreign-mint quote account', async () => {
+    await expectRejected(redeem({ userQuoteAccount: fakeMintAccount }), 'ConstraintTokenMint');
+  });
+
+  it('rejects a redemption through a token program that does not own the project mint', async () => {
+    await expectRejected(redeem({ tokenProgram: TOKEN_2022_PROGRAM_ID }), 'ConstraintMintTokenProgram');
+  });
+
+  it("rejects a vesting claim paid into someone else's token account", async () => {
+    await expectRejected(
+      program.methods
+        .claimVested(0)
+        .accounts({
+          project,
+          vesting: pda([Buffer.from('vesting'), project.toBuffer()]),
+          vestingVault: pda([Buffer.from('vesting_vault'), project.toBuffer()]),
+          projectTokenMint,
+          beneficiary: attacker.publicKey,
+          beneficiaryTokenAccount: investorTokenAccount,
+          projectSigner,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([attacker])
+        .rpc(),
+      'InvalidTokenAccount'
+    );
+  });
+
+  it("rejects an unlock claim paid into someone else's token account", async () => {
+    const env: ProjectEnv = { program, provider };
+    const locked: ProjectAccounts = await createProject(env, {
+      lockup: { tgeUnlockBps: 1_000, unlockDuration: new anchor.BN(30 * 24 * 60 * 60) },
+    });
+    await invest(env, locked, investor, 1_000);
+    const attackerLocked = await createTokenAccount(env, locked.projectTokenMint, attacker.publicKey);
+
+    await expectRejected(
+      program.methods
+        .claimUnlocked()
+        .accounts({
+          project: locked.project,
+          investorLock: pda([Buffer.from('investor_lock'), locked.project.toBuffer(), investor.publicKey.toBuffer()]),
+          investorVault: locked.investorVault,
+          projectTokenMint: locked.projectTokenMint,
+          investor: investor.publicKey,
+          userTokenAccount: attackerLocked,
+          projectSigner: locked.projectSigner,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([investor])
+        .rpc(),
+      'InvalidTokenAccount'
+    );
+  });
+
+  it('rejects an upgrade proposed by a non-owner', async () => {
+    const { nonce } = await program.account.multisigState.fetch(multisig.publicKey);
+    await expectRejected(
+      program.methods
+        .proposeUpgrade(Keypair.generate().publicKey, 'Rogue upgrade')
+        .accounts({
+          upgradeProposal: pda([
+            Buffer.from('upgrade'),
+            multisig.publicKey.toBuffer(),
+            nonce.toArrayLike(Buffer, 'le', 8),
+          ]),
+          multisig: multisig.publicKey,
+          proposer: attacker.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([attacker])
+        .rpc(),
+      'UnauthorizedAccess'
+    );
+  });
+});
+
+// Project accounts the program itself could never produce, forged into a bank
+describe('forged project accounts', () => {
+  const creator = Keypair.generate();
+
+  let env: ProjectEnv;
+  let accounts: ProjectAccounts;
+
+  // Copy the project's state to an address that is not its PDA
+  const forgedCopy = async () => {
+    const info = await env.provider.connection.getAccountInfo(accounts.project);
+    const forged = Keypair.generate().publicKey;
+    env.context.setAccount(forged, { ...info, data: Buffer.from(info.data) });
+    return forged;
+  };
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, creator);
+    accounts = await createProject(env);
+  });
+
+  it('rejects a proposal on a project that is not initialized', async () => {
+    const { program } = env;
+    const info = await env.provider.connection.getAccountInfo(accounts.project);
+    const state = await program.account.projectState.fetch(accounts.project);
+    const encoded = await program.coder.accounts.encode('ProjectState', { ...state, isInitialized: false });
+    const data = Buffer.alloc(info.data.length);
+    encoded.copy(data);
+    env.context.setAccount(accounts.project, { ...info, data });
+
+    try {
+      await expectRejected(
+        program.methods
+          .createProposal('Proposal', [], new anchor.BN(3 * 24 * 60 * 60))
+          .accounts({
+            proposal: PublicKey.findProgramAddressSync(
+              [Buffer.from('proposal'), accounts.project.toBuffer(), state.proposalCount.toArrayLike(Buffer, 'le', 8)],
+              program.programId
+            )[0],
+            project: accounts.project,
+            creator: creator.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([creator])
+          .rpc(),
+        'InvalidProjectState'
+      );
+    } finally {
+      env.context.setAccount(accounts.project, info);
+    }
+  });
+
+  it('rejects a refund claimed against a project outside its PDA', async () => {
+    await expectRejected(
+      env.program.methods
+        .claimRefund()
+        .accounts({
+          project: await forgedCopy(),
+          investor: creator.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc(),
+      'ConstraintSeeds'
+    );
+  });
+
+  it('rejects expiring a project outside its PDA', async () => {
+    await expectRejected(
+      env.program.methods
+        .expireProject()
+        .accounts({
+          project: await forgedCopy(),
+          treasuryVault: accounts.treasuryVault,
+          projectSigner: accounts.projectSigner,
+          ...noProjectLiquidity,
+        })
+        .rpc(),
+      'ConstraintSeeds'
+    );
+  });
+});
@@ -0,0 +1,283 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  createAccount,
+  mintTo,
+} from '@solana/spl-token';
+import { expect } from 'chai';
+import * as fs from 'fs';
+import * as path from 'path';
+import { allowQuoteMint, ensurePlatform, registryAccounts } from './utils/platform';
+
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
+
+// Ceilings for the hot instructions; a regression past these fails the suite
+const COMPUTE_BUDGET = {
+  invest: 60_000,
+  castVote: 15_000,
+  swap: 60_000,
+};
+
+// Units measured before proposals and pools moved to zero-copy, recorded by
+// running this suite with RECORD_BORSH_BASELINE=1 against the last Borsh
+// build. Pools were not mounted in the program then, so swap has none.
+const BORSH_BASELINE_FILE = path.join(__dirname, 'fixtures', 'compute-units-borsh.json');
+const recordingBaseline = !!process.env.RECORD_BORSH_BASELINE;
+const borshBaseline: Record<string, number> =
+  !recordingBaseline && fs.existsSync(BORSH_BASELINE_FILE)
+    ? JSON.parse(fs.readFileSync(BORSH_BASELINE_FILE, 'utf8'))
+    : {};
+
+describe('compute units', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+
+  const authority = Keypair.generate();
+  const investor = Keypair.generate();
+  const multisig = Keypair.generate();
+  const slug = `compute-${Date.now()}`;
+
+  let project: PublicKey;
+  let proposal: PublicKey;
+  let projectSigner: PublicKey;
+  let projectTokenMint: PublicKey;
+  let investorVault: PublicKey;
+  let investorTokenAccount: PublicKey;
+  let platform: PublicKey;
+
+  const pda = (seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const deadline = () => new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+
+  const unitsConsumed = async (signature: string): Promise<number> => {
+    await provider.connection.confirmTransaction(signature, 'confirmed');
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: 'confirmed',
+      maxSupportedTransactionVersion: 0,
+    });
+    return tx.meta.computeUnitsConsumed;
+  };
+
+  // The spec reporter prints the title once the test ends, so the
+  // measurement and its delta from the Borsh baseline show up there
+  const measured: Record<string, number> = {};
+  const report = (test: Mocha.Test, name: string, units: number) => {
+    measured[name] = units;
+    const baseline = borshBaseline[name];
+    const delta = baseline
+      ? `, ${units - baseline >= 0 ? '+' : ''}${units - baseline} vs ${baseline} on Borsh`
+      : '';
+    test.title += ` (${units} CU${delta})`;
+  };
+
+  after(() => {
+    if (recordingBaseline) {
+      fs.mkdirSync(path.dirname(BORSH_BASELINE_FILE), { recursive: true });
+      fs.writeFileSync(BORSH_BASELINE_FILE, `${JSON.stringify(measured, null, 2)}\n`);
+    }
+  });
+
+  before(async () => {
+    for (const wallet of [authority, investor]) {
+      const sig = await provider.connection.requestAirdrop(
+        wallet.publicKey,
+        10 * anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig);
+    }
+
+    platform = await ensurePlatform(program, provider);
+    const quoteMint = await createMint(provider.connection, authority, authority.publicKey, null, 9);
+    await allowQuoteMint(program, provider, quoteMint);
+
+    project = pda([Buffer.from('project'), Buffer.from(slug)]);
+    proposal = pda([Buffer.from('proposal'), project.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, 'le', 8)]);
+    projectSigner = pda([Buffer.from('project_signer'), project.toBuffer()]);
+    projectTokenMint = pda([Buffer.from('project_mint'), project.toBuffer()]);
+    investorVault = pda([Buffer.from('investor_vault'), project.toBuffer()]);
+    const [tokenMetadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from('metadata'), TOKEN_METADATA_PROGRAM_ID.toBuffer(), projectTokenMint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+
+    await program.methods
+      .initializeMultisig([authority.publicKey], new anchor.BN(1))
+      .accounts({ multisig: multisig.publicKey, payer: authority.publicKey, systemProgram: SystemProgram.programId })
+      .signers([authority, multisig])
+      .rpc();
+
+    await program.methods
+      .initializeProject(
+        {
+          slug,
+          totalSupply: new anchor.BN(1_000_000),
+          saleSupply: new anchor.BN(1_000_000),
+          minRaise: new anchor.BN(1),
+          maxRaise: new anchor.BN(1_000_000),
+          minInvestment: new anchor.BN(1),
+          maxInvestment: new anchor.BN(1_000_000),
+          duration: new anchor.BN(7 * 24 * 60 * 60),
+          guardian: null,
+          multisig: multisig.publicKey,
+          lockup: null,
+        },
+        { name: 'Compute', symbol: 'CU', uri: '', decimals: 6, revokeFreezeAuthority: false },
+        new anchor.BN(1),
+        null,
+        []
+      )
+      .accounts({
+        project,
+        authority: authority.publicKey,
+        ...(await registryAccounts(program)),
+        projectSigner,
+        projectTokenMint,
+        tokenMetadata,
+        quoteMint,
+        treasuryVault: pda([Buffer.from('treasury'), project.toBuffer()]),
+        vesting: pda([Buffer.from('vesting'), project.toBuffer()]),
+        vestingVault: pda([Buffer.from('vesting_vault'), project.toBuffer()]),
+        investorVault,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([authority])
+      .rpc();
+
+    investorTokenAccount = await createAccount(provider.connection, investor, projectTokenMint, investor.publicKey);
+
+    await program.methods
+      .createProposal('Compute benchmark', [], new anchor.BN(3 * 24 * 60 * 60))
+      .accounts({
+        proposal,
+        project,
+        creator: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+  });
+
+  it('invest stays within its compute budget', async function () {
+    const signature = await program.methods
+      .invest(new anchor.BN(1_000), null)
+      .accounts({
+        project,
+        investor: investor.publicKey,
+        userTokenAccount: investorTokenAccount,
+        projectTokenMint,
+        investorVault,
+        projectSigner,
+        investorLock: null,
+        platform,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([investor])
+      .rpc();
+
+    const units = await unitsConsumed(signature);
+    report(this.test, 'invest', units);
+    expect(units).to.be.at.most(COMPUTE_BUDGET.invest);
+  });
+
+  it('cast_vote stays within its compute budget', async function () {
+    const signature = await program.methods
+      .castVote(true)
+      .accounts({
+        proposal,
+        project,
+        voter: investor.publicKey,
+        voterTokenAccount: investorTokenAccount,
+      })
+      .signers([investor])
+      .rpc();
+
+    const units = await unitsConsumed(signature);
+    report(this.test, 'castVote', units);
+    expect(units).to.be.at.most(COMPUTE_BUDGET.castVote);
+  });
+
+  it('swap stays within its compute budget', async function () {
+    const [tokenA, tokenB] = [
+      await createMint(provider.connection, authority, authority.publicKey, null, 6),
+      await createMint(provider.connection, authority, authority.publicKey, null, 6),
+    ].sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()));
+    const pool = pda([Buffer.from('pool'), tokenA.toBuffer(), tokenB.toBuffer()]);
+    const lpTokenMint = pda([Buffer.from('lp_mint'), pool.toBuffer()]);
+
+    const userTokenA = await createAccount(provider.connection, authority, tokenA, authority.publicKey);
+    const userTokenB = await createAccount(provider.connection, authority, tokenB, authority.publicKey);
+    await mintTo(provider.connection, authority, tokenA, userTokenA, authority, 10_000_000);
+    await mintTo(provider.connection, authority, tokenB, userTokenB, authority, 10_000_000);
+
+    const poolAccounts = {
+      pool,
+      tokenA,
+      tokenB,
+      poolAuthority: pda([Buffer.from('pool_authority'), pool.toBuffer()]),
+      tokenAReserve: pda([Buffer.from('reserve_a'), pool.toBuffer()]),
+      tokenBReserve: pda([Buffer.from('reserve_b'), pool.toBuffer()]),
+      tokenAProgram: TOKEN_PROGRAM_ID,
+      tokenBProgram: TOKEN_PROGRAM_ID,
+      observations: pda([Buffer.from('observations'), pool.toBuffer()]),
+    };
+
+    await program.methods
+      .initializePool(new anchor.BN(30), { constantProduct: {} })
+      .accounts({
+        ...poolAccounts,
+        lpTokenMint,
+        authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const userLpToken = await createAccount(provider.connection, authority, lpTokenMint, authority.publicKey);
+
+    await program.methods
+      .addLiquidity(new anchor.BN(1_000_000), new anchor.BN(1_000_000), new anchor.BN(0), deadline())
+      .accounts({
+        ...poolAccounts,
+        lpTokenMint,
+        userTokenAAccount: userTokenA,
+        userTokenBAccount: userTokenB,
+        userLpTokenAccount: userLpToken,
+        authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+
+    const signature = await program.methods
+      .swap(new anchor.BN(10_000), new anchor.BN(0), deadline())
+      .accounts({
+        ...poolAccounts,
+        userSourceAccount: userTokenA,
+        userDestinationAccount: userTokenB,
+        platform,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        launchPurchases: null,
+        piVerification: null,
+      })
+      .signers([authority])
+      .rpc();
+
+    const units = await unitsConsumed(signature);
+    report(this.test, 'swap', units);
+    expect(units).to.be.at.most(COMPUTE_BUDGET.swap);
+  });
+});
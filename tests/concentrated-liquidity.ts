@@ -0,0 +1,282 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import { expect } from 'chai';
+import { ensurePlatform } from './utils/platform';
+import { expectRejected } from './utils/assert';
+import { airdrop, createTraderMints, inAMinute, tokenBalance } from './utils/pool';
+
+describe('concentrated liquidity', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+
+  const trader = Keypair.generate();
+  const tickSpacing = 10;
+  const feeBps = 30;
+  // Mirrors clmm::TICK_ARRAY_SIZE
+  const tickArraySpan = tickSpacing * 64;
+  const [tickLower, tickUpper] = [-600, 600];
+  const liquidity = new BN(1_000_000_000);
+
+  let platform: PublicKey;
+  let pool: PublicKey;
+  let position: PublicKey;
+  let poolAccounts: Record<string, PublicKey>;
+  let userTokenA: PublicKey;
+  let userTokenB: PublicKey;
+
+  const pda = (seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const i32 = (value: number) => {
+    const buffer = Buffer.alloc(4);
+    buffer.writeInt32LE(value);
+    return buffer;
+  };
+
+  const tickArray = (startTickIndex: number) =>
+    pda([Buffer.from('tick_array'), pool.toBuffer(), i32(startTickIndex)]);
+
+  const balance = (account: PublicKey) => tokenBalance(provider, account);
+
+  const positionAccounts = () => ({
+    ...poolAccounts,
+    position,
+    tickArrayLower: tickArray(-tickArraySpan),
+    tickArrayUpper: tickArray(0),
+    userTokenAAccount: userTokenA,
+    userTokenBAccount: userTokenB,
+    platform,
+    owner: trader.publicKey,
+  });
+
+  const swap = (amountIn: BN, source: PublicKey, destination: PublicKey, tickArrays: number[]) =>
+    program.methods
+      .clSwap(amountIn, new BN(0), new BN(0), inAMinute())
+      .accounts({
+        ...poolAccounts,
+        userSourceAccount: source,
+        userDestinationAccount: destination,
+        platform,
+        authority: trader.publicKey,
+      })
+      .remainingAccounts(
+        tickArrays.map((start) => ({ pubkey: tickArray(start), isSigner: false, isWritable: true }))
+      )
+      .signers([trader])
+      .rpc();
+
+  before(async () => {
+    await airdrop(provider, trader);
+    platform = await ensurePlatform(program, provider);
+
+    const mints = await createTraderMints(provider, trader, 100_000_000);
+    const { tokenA, tokenB } = mints;
+    ({ userTokenA, userTokenB } = mints);
+
+    const spacingSeed = Buffer.alloc(2);
+    spacingSeed.writeUInt16LE(tickSpacing);
+    pool = pda([Buffer.from('cl_pool'), tokenA.toBuffer(), tokenB.toBuffer(), spacingSeed]);
+    poolAccounts = {
+      pool,
+      tokenA,
+      tokenB,
+      poolAuthority: pda([Buffer.from('pool_authority'), pool.toBuffer()]),
+      tokenAReserve: pda([Buffer.from('reserve_a'), pool.toBuffer()]),
+      tokenBReserve: pda([Buffer.from('reserve_b'), pool.toBuffer()]),
+      tokenAProgram: TOKEN_PROGRAM_ID,
+      tokenBProgram: TOKEN_PROGRAM_ID,
+    };
+    position = pda([
+      Buffer.from('position'),
+      pool.toBuffer(),
+      trader.publicKey.toBuffer(),
+      i32(tickLower),
+      i32(tickUpper),
+    ]);
+
+    // Starts at a price of one, i.e. tick zero
+    await program.methods
+      .initializeClPool(tickSpacing, new BN(feeBps), new BN(1).shln(64))
+      .accounts({
+        ...poolAccounts,
+        authority: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+
+    for (const start of [-tickArraySpan, 0, tickArraySpan]) {
+      await program.methods
+        .initializeTickArray(start)
+        .accounts({
+          pool,
+          tickArray: tickArray(start),
+          payer: trader.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([trader])
+        .rpc();
+    }
+
+    await program.methods
+      .openClPosition(tickLower, tickUpper)
+      .accounts({ pool, position, owner: trader.publicKey, systemProgram: SystemProgram.programId })
+      .signers([trader])
+      .rpc();
+  });
+
+  it('deposits both tokens for a range around the current price', async () => {
+    const [userABefore, userBBefore] = [await balance(userTokenA), await balance(userTokenB)];
+
+    await program.methods
+      .increaseClLiquidity(liquidity, new BN(100_000_000), new BN(100_000_000), inAMinute())
+      .accounts(positionAccounts())
+      .signers([trader])
+      .rpc();
+
+    // L * (1 - 1.0001^-300) on each side, rounded up
+    const [spentA, spentB] = [
+      userABefore.sub(await balance(userTokenA)).toNumber(),
+      userBBefore.sub(await balance(userTokenB)).toNumber(),
+    ];
+    expect(spentA).to.be.closeTo(29_553_011, 2);
+    expect(spentB).to.be.closeTo(spentA, 1);
+
+    const state = await program.account.clPoolState.fetch(pool);
+    expect(state.liquidity.toString()).to.equal(liquidity.toString());
+  });
+
+  it('rejects tick arrays that do not start at the current price', async () => {
+    await expectRejected(
+      swap(new BN(1_000), userTokenA, userTokenB, [-tickArraySpan]),
+      'InvalidTickArray'
+    );
+  });
+
+  it('swaps token A for token B within the range', async () => {
+    const amountIn = new BN(1_000_000);
+    const userBBefore = await balance(userTokenB);
+
+    await swap(amountIn, userTokenA, userTokenB, [0, -tickArraySpan]);
+
+    // About one for one after the 0.3% fee, and about 0.1% of price impact
+    const received = (await balance(userTokenB)).sub(userBBefore).toNumber();
+    expect(received).to.be.within(995_000, 997_000);
+
+    const state = await program.account.clPoolState.fetch(pool);
+    expect(state.tickCurrent).to.be.below(0);
+    expect(state.liquidity.toString()).to.equal(liquidity.toString());
+  });
+
+  it('pays the swap fee to the position', async () => {
+    const userABefore = await balance(userTokenA);
+
+    await program.methods
+      .collectClPositionFees()
+      .accounts(positionAccounts())
+      .signers([trader])
+      .rpc();
+
+    // The whole 3_000 fee less any protocol share and rounding
+    const collected = (await balance(userTokenA)).sub(userABefore).toNumber();
+    expect(collected).to.be.within(1, 3_000);
+  });
+
+  it('credits no fees to a new position withdrawn before any swap', async () => {
+    // Fresh boundary ticks, opened after the pool has already earned fees
+    const [lower, upper] = [-100, 100];
+    const freshPosition = pda([
+      Buffer.from('position'),
+      pool.toBuffer(),
+      trader.publicKey.toBuffer(),
+      i32(lower),
+      i32(upper),
+    ]);
+    const accounts = { ...positionAccounts(), position: freshPosition };
+    expect((await program.account.clPoolState.fetch(pool)).feeGrowthGlobalA.toString()).to.not.equal('0');
+
+    await program.methods
+      .openClPosition(lower, upper)
+      .accounts({ pool, position: freshPosition, owner: trader.publicKey, systemProgram: SystemProgram.programId })
+      .signers([trader])
+      .rpc();
+    await program.methods
+      .increaseClLiquidity(liquidity, new BN(100_000_000), new BN(100_000_000), inAMinute())
+      .accounts(accounts)
+      .signers([trader])
+      .rpc();
+    await program.methods
+      .decreaseClLiquidity(liquidity, new BN(0), new BN(0), inAMinute())
+      .accounts(accounts)
+      .signers([trader])
+      .rpc();
+
+    const state = await program.account.clPosition.fetch(freshPosition);
+    expect(state.tokensOwedA.toNumber()).to.equal(0);
+    expect(state.tokensOwedB.toNumber()).to.equal(0);
+
+    await program.methods
+      .closeClPosition()
+      .accounts({ position: freshPosition, owner: trader.publicKey })
+      .signers([trader])
+      .rpc();
+  });
+
+  it('crosses out of the range and takes only the input it used', async () => {
+    const amountIn = new BN(50_000_000);
+    const userBBefore = await balance(userTokenB);
+
+    // Pushes the price past the upper tick, after which no liquidity is left
+    await swap(amountIn, userTokenB, userTokenA, [-tickArraySpan, 0, tickArraySpan]);
+
+    const spent = userBBefore.sub(await balance(userTokenB)).toNumber();
+    expect(spent).to.be.above(0);
+    expect(spent).to.be.below(amountIn.toNumber());
+
+    const state = await program.account.clPoolState.fetch(pool);
+    expect(state.tickCurrent).to.be.at.least(tickUpper);
+    expect(state.liquidity.toNumber()).to.equal(0);
+  });
+
+  it('rejects closing a position that still holds liquidity', async () => {
+    await expectRejected(
+      program.methods
+        .closeClPosition()
+        .accounts({ position, owner: trader.publicKey })
+        .signers([trader])
+        .rpc(),
+      'PositionNotEmpty'
+    );
+  });
+
+  it('withdraws only token B once the price is above the range', async () => {
+    const [userABefore, userBBefore] = [await balance(userTokenA), await balance(userTokenB)];
+
+    await program.methods
+      .decreaseClLiquidity(liquidity, new BN(0), new BN(1), inAMinute())
+      .accounts(positionAccounts())
+      .signers([trader])
+      .rpc();
+    await program.methods
+      .collectClPositionFees()
+      .accounts(positionAccounts())
+      .signers([trader])
+      .rpc();
+
+    // Token A fees were collected earlier; the last swap paid its fee in token B
+    expect((await balance(userTokenA)).sub(userABefore).toNumber()).to.equal(0);
+    expect((await balance(userTokenB)).sub(userBBefore).toNumber()).to.be.above(0);
+
+    await program.methods
+      .closeClPosition()
+      .accounts({ position, owner: trader.publicKey })
+      .signers([trader])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(position)).to.be.null;
+  });
+});
@@ -0,0 +1,127 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { expect } from 'chai';
+import { expectRejected } from './utils/assert';
+import { PoolAccounts, inAMinute, setUpPool, tokenBalance } from './utils/pool';
+
+describe('dynamic fees', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+
+  const trader = Keypair.generate();
+  const staticFee = 30;
+  const [minFee, maxFee] = [10, 100];
+
+  let platform: PublicKey;
+  let platformMultisig: PublicKey;
+  let pool: PublicKey;
+  let poolAccounts: PoolAccounts;
+  let userTokenA: PublicKey;
+  let userTokenB: PublicKey;
+
+  const balance = (account: PublicKey) => tokenBalance(provider, account);
+
+  // Mirrors the constant-product quote in PoolState::quote_swap
+  const quote = (amountIn: BN, feeBps: number, reserveIn: BN, reserveOut: BN) => {
+    const amountInWithFee = amountIn.sub(amountIn.muln(feeBps).addn(9_999).divn(10_000));
+    return amountInWithFee.mul(reserveOut).div(reserveIn.add(amountInWithFee));
+  };
+
+  // Sells token A and checks the output against a quote at `feeBps`
+  const expectSwapAtFee = async (amountIn: BN, feeBps: number) => {
+    const state = await program.account.poolState.fetch(pool);
+    const expectedOut = quote(amountIn, feeBps, state.tokenAAmount, state.tokenBAmount);
+    const userBBefore = await balance(userTokenB);
+
+    await program.methods
+      .swap(amountIn, new BN(0), inAMinute())
+      .accounts({
+        ...poolAccounts,
+        userSourceAccount: userTokenA,
+        userDestinationAccount: userTokenB,
+        platform,
+        authority: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+        launchPurchases: null,
+        piVerification: null,
+      })
+      .signers([trader])
+      .rpc();
+
+    expect((await balance(userTokenB)).sub(userBBefore).toString()).to.equal(expectedOut.toString());
+  };
+
+  const governanceAccounts = (signer?: Keypair) => ({
+    pool,
+    platform,
+    multisig: platformMultisig,
+    signer: signer ? signer.publicKey : provider.wallet.publicKey,
+  });
+
+  before(async () => {
+    ({ platform, accounts: poolAccounts, userTokenA, userTokenB } = await setUpPool(program, provider, trader, {
+      feeBps: staticFee,
+    }));
+    pool = poolAccounts.pool;
+    platformMultisig = (await program.account.platformConfig.fetch(platform)).multisig;
+  });
+
+  it('rejects enabling dynamic fees without the platform multisig', async () => {
+    await expectRejected(
+      program.methods
+        .enablePoolDynamicFee(new BN(minFee), new BN(maxFee))
+        .accounts(governanceAccounts(trader))
+        .signers([trader])
+        .rpc(),
+      'InsufficientApprovals'
+    );
+  });
+
+  it('rejects a maximum above the dynamic fee cap', async () => {
+    await expectRejected(
+      program.methods
+        .enablePoolDynamicFee(new BN(minFee), new BN(1_001))
+        .accounts(governanceAccounts())
+        .rpc(),
+      'InvalidFee'
+    );
+  });
+
+  it('charges the minimum fee while the price is calm', async () => {
+    await program.methods
+      .enablePoolDynamicFee(new BN(minFee), new BN(maxFee))
+      .accounts(governanceAccounts())
+      .rpc();
+
+    const state = await program.account.poolState.fetch(pool);
+    expect(state.dynamicFeeMin.toNumber()).to.equal(minFee);
+    expect(state.dynamicFeeMax.toNumber()).to.equal(maxFee);
+
+    await expectSwapAtFee(new BN(1_000), minFee);
+  });
+
+  it('raises the fee to the maximum after a large price move', async () => {
+    // The calm swap above left a little volatility behind; well under a half-life has passed
+    const before = await program.account.poolState.fetch(pool);
+    const feeNow = minFee + Math.floor(((maxFee - minFee) * before.volatility.toNumber()) / 1_000);
+    expect(feeNow).to.be.below(maxFee);
+
+    // Moves the price by well over the 10% that saturates the fee
+    await expectSwapAtFee(new BN(200_000), feeNow);
+
+    const state = await program.account.poolState.fetch(pool);
+    expect(state.volatility.toNumber()).to.equal(1_000);
+
+    await expectSwapAtFee(new BN(1_000), maxFee);
+  });
+
+  it('returns to the static fee when dynamic fees are disabled', async () => {
+    await program.methods.disablePoolDynamicFee().accounts(governanceAccounts()).rpc();
+
+    await expectSwapAtFee(new BN(1_000), staticFee);
+  });
+});
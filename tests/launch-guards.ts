@@ -0,0 +1,301 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import { expect } from 'chai';
+import { ensurePlatform } from './utils/platform';
+import { expectRejected } from './utils/assert';
+import { PoolAccounts, findPoolAccounts, inAMinute, initializePool } from './utils/pool';
+import {
+  ProjectAccounts,
+  ProjectEnv,
+  balance,
+  createMint,
+  createProject,
+  createTokenAccount,
+  fund,
+  invest,
+  mintTokens,
+} from './utils/project';
+
+describe('launch guards', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+  const env: ProjectEnv = { program, provider };
+
+  const buyer = Keypair.generate();
+  const sniper = Keypair.generate();
+  const verifier = Keypair.generate();
+  const baseFee = 30;
+  // Long enough windows that every test runs while the guards are active
+  const guards = {
+    limitSlots: new BN(100_000),
+    maxBuyPerTx: new BN(15_000),
+    maxBuyPerWallet: new BN(20_000),
+    initialFee: new BN(2_000),
+    feeDecaySlots: new BN(100_000),
+    allowlistSlots: new BN(100_000),
+  };
+
+  let platform: PublicKey;
+  let project: ProjectAccounts;
+  let pool: PublicKey;
+  let poolAccounts: PoolAccounts;
+  let launchedMint: PublicKey;
+  let quoteMint: PublicKey;
+  const buyerAccounts = new Map<string, PublicKey>();
+
+  const pda = (seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  // Mirrors the constant-product quote in PoolState::quote_swap
+  const quote = (amountIn: BN, feeBps: number, reserveIn: BN, reserveOut: BN) => {
+    const amountInWithFee = amountIn.sub(amountIn.muln(feeBps).addn(9_999).divn(10_000));
+    return amountInWithFee.mul(reserveOut).div(reserveIn.add(amountInWithFee));
+  };
+
+  const reservesFor = async (mintIn: PublicKey) => {
+    const state = await program.account.poolState.fetch(pool);
+    return state.tokenA.equals(mintIn)
+      ? [state.tokenAAmount, state.tokenBAmount]
+      : [state.tokenBAmount, state.tokenAAmount];
+  };
+
+  const piVerification = pda([Buffer.from('pi_verification'), buyer.publicKey.toBuffer()]);
+
+  const swap = (amountIn: BN, mintIn: PublicKey, mintOut: PublicKey, verified = true) =>
+    program.methods
+      .swap(amountIn, new BN(0), inAMinute())
+      .accounts({
+        ...poolAccounts,
+        userSourceAccount: buyerAccounts.get(mintIn.toBase58()),
+        userDestinationAccount: buyerAccounts.get(mintOut.toBase58()),
+        platform,
+        authority: buyer.publicKey,
+        systemProgram: SystemProgram.programId,
+        launchPurchases: pda([Buffer.from('launch_purchases'), pool.toBuffer(), buyer.publicKey.toBuffer()]),
+        piVerification: verified ? piVerification : null,
+      })
+      .signers([buyer])
+      .rpc();
+
+  const buy = (amountIn: BN, verified = true) => swap(amountIn, quoteMint, launchedMint, verified);
+
+  const configure = (signer: Keypair) =>
+    program.methods
+      .configureLaunchGuards(guards)
+      .accounts({ pool, project: project.project, authority: signer.publicKey })
+      .signers([signer])
+      .rpc();
+
+  before(async () => {
+    await fund(env, buyer, sniper, verifier);
+    platform = await ensurePlatform(program, provider);
+
+    project = await createProject(env, { totalSupply: 10_000_000 });
+    launchedMint = project.projectTokenMint;
+    quoteMint = await createMint(env, 6);
+
+    // The buyer starts with quote tokens only, and some launched tokens to sell
+    buyerAccounts.set(launchedMint.toBase58(), await invest(env, project, buyer, 100_000));
+    const buyerQuote = await createTokenAccount(env, quoteMint, buyer.publicKey);
+    await mintTokens(env, quoteMint, buyerQuote, 10_000_000);
+    buyerAccounts.set(quoteMint.toBase58(), buyerQuote);
+
+    poolAccounts = findPoolAccounts(program, launchedMint, quoteMint);
+    pool = poolAccounts.pool;
+
+    // A third party gets to the pool before the project does
+    await initializePool(program, sniper, poolAccounts, baseFee);
+
+    await program.methods
+      .setPiVerifier(verifier.publicKey)
+      .accounts({
+        platform,
+        multisig: (await program.account.platformConfig.fetch(platform)).multisig,
+        signer: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+
+  it('lets only the project authority configure launch guards', async () => {
+    await expectRejected(configure(sniper), 'UnauthorizedAccess');
+
+    // The pool's creator has no say over the project's launch
+    await configure(project.authority);
+
+    const state = await program.account.poolState.fetch(pool);
+    expect(state.launch.maxBuyPerTx.toString()).to.equal(guards.maxBuyPerTx.toString());
+    expect(state.launch.launchedToken).to.equal(state.tokenA.equals(launchedMint) ? 1 : 2);
+  });
+
+  it('freezes the guards once the pool is seeded', async () => {
+    const authority = project.authority;
+    const authorityLaunched = await invest(env, project, authority, 1_000_000);
+    const authorityQuote = await createTokenAccount(env, quoteMint, authority.publicKey);
+    await mintTokens(env, quoteMint, authorityQuote, 1_000_000);
+    const authorityLp = await createTokenAccount(env, poolAccounts.lpTokenMint, authority.publicKey);
+    const [userA, userB] = poolAccounts.tokenA.equals(launchedMint)
+      ? [authorityLaunched, authorityQuote]
+      : [authorityQuote, authorityLaunched];
+
+    await program.methods
+      .addLiquidity(new BN(1_000_000), new BN(1_000_000), new BN(0), inAMinute())
+      .accounts({
+        ...poolAccounts,
+        userTokenAAccount: userA,
+        userTokenBAccount: userB,
+        userLpTokenAccount: authorityLp,
+        authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([authority])
+      .rpc();
+
+    expect((await program.account.poolState.fetch(pool)).launch.launchSlot.toNumber()).to.be.above(0);
+    await expectRejected(configure(authority), 'PoolAlreadyLaunched');
+  });
+
+  it('rejects buys from wallets that are not Pi-verified during the allowlist', async () => {
+    await expectRejected(buy(new BN(1_000), false), 'NotPiVerified');
+  });
+
+  it('rejects attestations not signed by the platform verifier', async () => {
+    await expectRejected(
+      program.methods
+        .verifyPiWallet()
+        .accounts({
+          platform,
+          verifier: buyer.publicKey,
+          wallet: buyer.publicKey,
+          piVerification,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc(),
+      'NotPiVerifier'
+    );
+  });
+
+  it('charges the launch fee on a verified buy', async () => {
+    await program.methods
+      .verifyPiWallet()
+      .accounts({
+        platform,
+        verifier: verifier.publicKey,
+        wallet: buyer.publicKey,
+        piVerification,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([verifier])
+      .rpc();
+
+    const amountIn = new BN(15_000);
+    const [reserveIn, reserveOut] = await reservesFor(quoteMint);
+    const launchedAccount = buyerAccounts.get(launchedMint.toBase58());
+    const before = await balance(env, launchedAccount);
+
+    await buy(amountIn);
+
+    // The fee has decayed a little from its starting point, but is far above the pool fee
+    const received = (await balance(env, launchedAccount)).sub(before);
+    expect(received.gte(quote(amountIn, guards.initialFee.toNumber(), reserveIn, reserveOut))).to.be.true;
+    expect(received.lt(quote(amountIn, baseFee * 10, reserveIn, reserveOut))).to.be.true;
+  });
+
+  it('rejects a buy over the per-transaction limit', async () => {
+    await expectRejected(buy(new BN(50_000)), 'LaunchBuyLimitExceeded');
+  });
+
+  it('rejects buys past the per-wallet limit', async () => {
+    // Within the per-transaction limit, but over the wallet's total with the first buy
+    await expectRejected(buy(new BN(15_000)), 'LaunchBuyLimitExceeded');
+  });
+
+  it('does not restrict selling the launched token', async () => {
+    const quoteAccount = buyerAccounts.get(quoteMint.toBase58());
+    const before = await balance(env, quoteAccount);
+
+    await swap(new BN(50_000), launchedMint, quoteMint, false);
+
+    expect((await balance(env, quoteAccount)).gt(before)).to.be.true;
+  });
+});
+
+describe('launch guards on a pool seeded by a third party', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+  const env: ProjectEnv = { program, provider };
+
+  const sniper = Keypair.generate();
+  const guards = {
+    limitSlots: new BN(100_000),
+    maxBuyPerTx: new BN(15_000),
+    maxBuyPerWallet: new BN(20_000),
+    initialFee: new BN(2_000),
+    feeDecaySlots: new BN(100_000),
+    allowlistSlots: new BN(0),
+  };
+
+  let project: ProjectAccounts;
+  let poolAccounts: PoolAccounts;
+
+  const configure = () =>
+    program.methods
+      .configureLaunchGuards(guards)
+      .accounts({ pool: poolAccounts.pool, project: project.project, authority: project.authority.publicKey })
+      .signers([project.authority])
+      .rpc();
+
+  before(async () => {
+    await fund(env, sniper);
+    await ensurePlatform(program, provider);
+
+    project = await createProject(env, { totalSupply: 10_000_000 });
+    const launchedMint = project.projectTokenMint;
+    const quoteMint = await createMint(env, 6);
+    poolAccounts = findPoolAccounts(program, launchedMint, quoteMint);
+
+    // An early investor creates and seeds the pool before the project acts
+    const sniperLaunched = await invest(env, project, sniper, 100_000);
+    const sniperQuote = await createTokenAccount(env, quoteMint, sniper.publicKey);
+    await mintTokens(env, quoteMint, sniperQuote, 100_000);
+    const sniperLp = await createTokenAccount(env, poolAccounts.lpTokenMint, sniper.publicKey);
+    const [userA, userB] = poolAccounts.tokenA.equals(launchedMint)
+      ? [sniperLaunched, sniperQuote]
+      : [sniperQuote, sniperLaunched];
+
+    await initializePool(program, sniper, poolAccounts, 30);
+    await program.methods
+      .addLiquidity(new BN(100_000), new BN(100_000), new BN(0), inAMinute())
+      .accounts({
+        ...poolAccounts,
+        userTokenAAccount: userA,
+        userTokenBAccount: userB,
+        userLpTokenAccount: sniperLp,
+        authority: sniper.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([sniper])
+      .rpc();
+  });
+
+  it('still lets the project set guards, starting the launch there', async () => {
+    const seededAt = (await program.account.poolState.fetch(poolAccounts.pool)).launch.launchSlot;
+
+    await configure();
+
+    const state = await program.account.poolState.fetch(poolAccounts.pool);
+    expect(state.launch.maxBuyPerTx.toString()).to.equal(guards.maxBuyPerTx.toString());
+    expect(state.launch.launchSlot.gt(seededAt)).to.be.true;
+  });
+
+  it('freezes the guards once they are set', async () => {
+    await expectRejected(configure(), 'PoolAlreadyLaunched');
+  });
+});
@@ -4,6 +4,9 @@ import { Pidaosphere } from '../target/types/pidaosphere';
 import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
 import { TOKEN_PROGRAM_ID, createMint } from '@solana/spl-token';
 import { expect } from 'chai';
+import { allowQuoteMint, findPlatform, findProjectEntry } from './utils/platform';
+
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
 
 describe('pidaosphere', () => {
   const provider = anchor.AnchorProvider.env();
@@ -15,11 +18,21 @@ describe('pidaosphere', () => {
   const authority = Keypair.generate();
   const investor = Keypair.generate();
   let projectTokenMint: PublicKey;
+  let quoteMint: PublicKey;
   let projectAccount: PublicKey;
+  let projectSigner: PublicKey;
+  let treasuryVault: PublicKey;
+  let vesting: PublicKey;
+  let vestingVault: PublicKey;
+  let investorVault: PublicKey;
+  let tokenMetadata: PublicKey;
   let proposalAccount: PublicKey;
+  let projectEntry: PublicKey;
+  let registryIndex: anchor.BN;
 
   // Test configuration
   const projectConfig = {
+    slug: 'pidaosphere-test',
     totalSupply: new anchor.BN(1000000),
     startPrice: new anchor.BN(1000000), // 1 SOL
     duration: new anchor.BN(7 * 24 * 60 * 60), // 1 week
@@ -33,8 +46,8 @@ describe('pidaosphere', () => {
     await provider.connection.requestAirdrop(authority.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
     await provider.connection.requestAirdrop(investor.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
 
-    // Create project token mint
-    projectTokenMint = await createMint(
+    // Create quote token mint used for the project treasury
+    quoteMint = await createMint(
       provider.connection,
       authority,
       authority.publicKey,
@@ -43,9 +56,43 @@ describe('pidaosphere', () => {
       TOKEN_PROGRAM_ID
     );
 
+    // New launches must raise in a quote mint the platform allows
+    await allowQuoteMint(program, provider, quoteMint);
+    registryIndex = (await program.account.platformConfig.fetch(findPlatform(program))).projectCount;
+    projectEntry = findProjectEntry(program, registryIndex);
+
     // Find project account PDA
     [projectAccount] = await PublicKey.findProgramAddress(
-      [Buffer.from('project'), authority.publicKey.toBuffer()],
+      [Buffer.from('project'), Buffer.from(projectConfig.slug)],
+      program.programId
+    );
+
+    [projectSigner] = await PublicKey.findProgramAddress(
+      [Buffer.from('project_signer'), projectAccount.toBuffer()],
+      program.programId
+    );
+    [projectTokenMint] = await PublicKey.findProgramAddress(
+      [Buffer.from('project_mint'), projectAccount.toBuffer()],
+      program.programId
+    );
+    [tokenMetadata] = await PublicKey.findProgramAddress(
+      [Buffer.from('metadata'), TOKEN_METADATA_PROGRAM_ID.toBuffer(), projectTokenMint.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    [treasuryVault] = await PublicKey.findProgramAddress(
+      [Buffer.from('treasury'), projectAccount.toBuffer()],
+      program.programId
+    );
+    [vesting] = await PublicKey.findProgramAddress(
+      [Buffer.from('vesting'), projectAccount.toBuffer()],
+      program.programId
+    );
+    [vestingVault] = await PublicKey.findProgramAddress(
+      [Buffer.from('vesting_vault'), projectAccount.toBuffer()],
+      program.programId
+    );
+    [investorVault] = await PublicKey.findProgramAddress(
+      [Buffer.from('investor_vault'), projectAccount.toBuffer()],
       program.programId
     );
   });
@@ -57,15 +104,35 @@ describe('pidaosphere', () => {
         .accounts({
           project: projectAccount,
           authority: authority.publicKey,
+          platform: findPlatform(program),
+          projectEntry,
+          projectSigner,
           projectTokenMint,
+          tokenMetadata,
+          quoteMint,
+          treasuryVault,
+          vesting,
+          vestingVault,
+          investorVault,
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
+          quoteTokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
         .signers([authority])
         .rpc();
 
       const project = await program.account.projectState.fetch(projectAccount);
       expect(project.authority.toString()).to.equal(authority.publicKey.toString());
+      expect(project.version).to.equal(1);
+      expect(project.registryIndex.toString()).to.equal(registryIndex.toString());
+
+      // The launch is listed in the platform registry
+      const entry = await program.account.projectEntry.fetch(projectEntry);
+      expect(entry.project.toString()).to.equal(projectAccount.toString());
+      const platform = await program.account.platformConfig.fetch(findPlatform(program));
+      expect(platform.projectCount.toString()).to.equal(registryIndex.addn(1).toString());
       expect(project.totalSupply.toString()).to.equal(projectConfig.totalSupply.toString());
       expect(project.currentPrice.toString()).to.equal(projectConfig.startPrice.toString());
     } catch (error) {
@@ -83,6 +150,7 @@ describe('pidaosphere', () => {
         .accounts({
           project: projectAccount,
           investor: investor.publicKey,
+          platform: findPlatform(program),
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
@@ -105,7 +173,7 @@ describe('pidaosphere', () => {
       const votingPeriod = new anchor.BN(3 * 24 * 60 * 60); // 3 days
 
       [proposalAccount] = await PublicKey.findProgramAddress(
-        [Buffer.from('proposal'), projectAccount.toBuffer(), authority.publicKey.toBuffer()],
+        [Buffer.from('proposal'), projectAccount.toBuffer(), new anchor.BN(0).toArrayLike(Buffer, 'le', 8)],
         program.programId
       );
 
@@ -121,8 +189,14 @@ describe('pidaosphere', () => {
         .rpc();
 
       const proposal = await program.account.proposalState.fetch(proposalAccount);
-      expect(proposal.description).to.equal(description);
-      expect(proposal.status).to.equal({ active: {} });
+      const stored = Buffer.from(proposal.description.slice(0, proposal.descriptionLen));
+      expect(stored.toString()).to.equal(description);
+      expect(proposal.status).to.equal(0); // ProposalStatus::Active
+      expect(proposal.version).to.equal(2);
+      expect(proposal.index.toString()).to.equal('0');
+
+      const project = await program.account.projectState.fetch(projectAccount);
+      expect(project.proposalCount.toString()).to.equal('1');
     } catch (error) {
       console.error('Test failed:', error);
       throw error;
@@ -135,6 +209,7 @@ describe('pidaosphere', () => {
         .castVote(true)
         .accounts({
           proposal: proposalAccount,
+          project: projectAccount,
           voter: investor.publicKey,
         })
         .signers([investor])
@@ -158,6 +233,7 @@ describe('pidaosphere', () => {
         .accounts({
           project: projectAccount,
           investor: investor.publicKey,
+          platform: findPlatform(program),
           systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
@@ -169,4 +245,47 @@ describe('pidaosphere', () => {
       expect(error.message).to.include('Investment amount out of bounds');
     }
   });
+
+  it('Should fail with an oversized proposal description', async () => {
+    try {
+      const project = await program.account.projectState.fetch(projectAccount);
+      const [nextProposal] = await PublicKey.findProgramAddress(
+        [Buffer.from('proposal'), projectAccount.toBuffer(), project.proposalCount.toArrayLike(Buffer, 'le', 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createProposal('x'.repeat(257), [], new anchor.BN(3 * 24 * 60 * 60))
+        .accounts({
+          proposal: nextProposal,
+          project: projectAccount,
+          creator: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      expect.fail('Expected transaction to fail');
+    } catch (error) {
+      expect(error.message).to.include('Description exceeds maximum length');
+    }
+  });
+
+  it('Should refuse to migrate an account already at the current version', async () => {
+    try {
+      await program.methods
+        .migrateProject()
+        .accounts({
+          project: projectAccount,
+          payer: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      expect.fail('Expected transaction to fail');
+    } catch (error) {
+      expect(error.message).to.include('Account is already at the current version');
+    }
+  });
 }); 
\ No newline at end of file
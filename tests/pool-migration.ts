@@ -0,0 +1,116 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import { expect } from 'chai';
+import { expectRejected } from './utils/assert';
+import { ensurePlatform } from './utils/platform';
+import { PoolAccounts, findPoolAccounts, initializePool } from './utils/pool';
+import {
+  ProjectEnv,
+  balance,
+  createMint,
+  createTokenAccount,
+  fund,
+  mintTokens,
+  now,
+  startProjectEnv,
+} from './utils/project';
+
+// Byte offsets into the pool account, discriminator included; mirror
+// pool::PoolState and pool::PoolStateV3
+const VERSION_OFFSET = 8 + 208;
+const LAUNCH_GUARDS_OFFSET = 8 + 320;
+const V3_RESERVED_LEN = 32;
+// 8 + PoolStateV0::INIT_SPACE
+const V0_POOL_LEN = 8 + 5 * 32 + 1 + 4 * 8;
+
+// Older pool layouts can only be produced by older program builds, so the
+// suite rewrites a current pool into them inside a bank
+describe('pool migration', () => {
+  const trader = Keypair.generate();
+
+  let env: ProjectEnv;
+  let platform: PublicKey;
+  let accounts: PoolAccounts;
+  let userTokenA: PublicKey;
+  let userTokenB: PublicKey;
+
+  const rewritePool = async (data: Buffer) => {
+    const info = await env.provider.connection.getAccountInfo(accounts.pool);
+    env.context.setAccount(accounts.pool, { ...info, data });
+  };
+
+  const migrate = () =>
+    env.program.methods
+      .migratePool()
+      .accounts({ pool: accounts.pool, payer: trader.publicKey, systemProgram: SystemProgram.programId })
+      .signers([trader])
+      .rpc();
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, trader);
+    platform = await ensurePlatform(env.program, env.provider);
+
+    accounts = findPoolAccounts(env.program, await createMint(env, 6), await createMint(env, 6));
+    userTokenA = await createTokenAccount(env, accounts.tokenA, trader.publicKey);
+    userTokenB = await createTokenAccount(env, accounts.tokenB, trader.publicKey);
+    await mintTokens(env, accounts.tokenA, userTokenA, 1_000_000);
+    await mintTokens(env, accounts.tokenB, userTokenB, 1_000_000);
+
+    await initializePool(env.program, trader, accounts, 30);
+    await env.program.methods
+      .addLiquidity(new BN(100_000), new BN(100_000), new BN(0), new BN((await now(env)) + 60))
+      .accounts({
+        ...accounts,
+        userTokenAAccount: userTokenA,
+        userTokenBAccount: userTokenB,
+        userLpTokenAccount: await createTokenAccount(env, accounts.lpTokenMint, trader.publicKey),
+        authority: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([trader])
+      .rpc();
+  });
+
+  it('migrates a pool from before launch guards and keeps it tradable', async () => {
+    const current = (await env.provider.connection.getAccountInfo(accounts.pool)).data;
+    const v3 = Buffer.concat([current.subarray(0, LAUNCH_GUARDS_OFFSET), Buffer.alloc(V3_RESERVED_LEN)]);
+    v3.writeUInt8(3, VERSION_OFFSET);
+    await rewritePool(v3);
+
+    await migrate();
+
+    const state = await env.program.account.poolState.fetch(accounts.pool);
+    expect(state.version).to.equal(4);
+    expect(state.launch.launchedToken).to.equal(0);
+
+    const before = await balance(env, userTokenB);
+    await env.program.methods
+      .swap(new BN(1_000), new BN(1), new BN((await now(env)) + 60))
+      .accounts({
+        ...accounts,
+        userSourceAccount: userTokenA,
+        userDestinationAccount: userTokenB,
+        platform,
+        authority: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+        launchPurchases: null,
+        piVerification: null,
+      })
+      .signers([trader])
+      .rpc();
+    expect((await balance(env, userTokenB)).gt(before)).to.be.true;
+  });
+
+  it('rejects a pool already at the current version', async () => {
+    await expectRejected(migrate(), 'AlreadyMigrated');
+  });
+
+  it('rejects a Borsh-era pool whose reserves the pool authority does not own', async () => {
+    const current = (await env.provider.connection.getAccountInfo(accounts.pool)).data;
+    await rewritePool(Buffer.concat([current.subarray(0, 8), Buffer.alloc(V0_POOL_LEN - 8)]));
+
+    await expectRejected(migrate(), 'UnsupportedPoolVersion');
+  });
+});
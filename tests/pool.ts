@@ -0,0 +1,256 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID, getAccount } from '@solana/spl-token';
+import { expect } from 'chai';
+import { expectRejected } from './utils/assert';
+import {
+  PoolAccounts,
+  createTraderMints,
+  findPoolAccounts,
+  inAMinute,
+  initializePool,
+  setUpPool,
+  tokenBalance,
+} from './utils/pool';
+
+describe('pool', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+
+  // The trader creates the pool, provides its liquidity and swaps against it
+  const trader = Keypair.generate();
+  const feeBps = 30; // The platform's protocol share is left at zero, so LPs keep it all
+
+  let platform: PublicKey;
+  let accounts: PoolAccounts;
+  let pool: PublicKey;
+  let userLpToken: PublicKey;
+  let userTokenA: PublicKey;
+  let userTokenB: PublicKey;
+
+  const balance = (account: PublicKey) => tokenBalance(provider, account);
+
+  // Mirrors pool::MINIMUM_LIQUIDITY
+  const minimumLiquidity = 1_000;
+
+  // Mirrors the constant-product quote in pool::handle_swap, fee rounded up
+  const quote = (amountIn: BN, reserveIn: BN, reserveOut: BN) => {
+    const amountInWithFee = amountIn.sub(amountIn.muln(feeBps).addn(9_999).divn(10_000));
+    return amountInWithFee.mul(reserveOut).div(reserveIn.add(amountInWithFee));
+  };
+
+  const swap = (amountIn: BN, source: PublicKey, destination: PublicKey, deadline = inAMinute()) =>
+    program.methods
+      .swap(amountIn, new BN(0), deadline)
+      .accounts({
+        ...accounts,
+        userSourceAccount: source,
+        userDestinationAccount: destination,
+        platform,
+        authority: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+        launchPurchases: null,
+        piVerification: null,
+      })
+      .signers([trader])
+      .rpc();
+
+  const liquidityAccounts = () => ({
+    ...accounts,
+    userTokenAAccount: userTokenA,
+    userTokenBAccount: userTokenB,
+    userLpTokenAccount: userLpToken,
+    authority: trader.publicKey,
+    tokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  const addLiquidity = (maxAmountA: BN, maxAmountB: BN, minLpOut: BN) =>
+    program.methods
+      .addLiquidity(maxAmountA, maxAmountB, minLpOut, inAMinute())
+      .accounts(liquidityAccounts())
+      .signers([trader])
+      .rpc();
+
+  const removeLiquidity = (lpAmount: BN, minAOut: BN, minBOut: BN) =>
+    program.methods
+      .removeLiquidity(lpAmount, minAOut, minBOut, inAMinute())
+      .accounts(liquidityAccounts())
+      .signers([trader])
+      .rpc();
+
+  before(async () => {
+    ({ platform, accounts, userTokenA, userTokenB, userLpToken } = await setUpPool(program, provider, trader, {
+      feeBps,
+      deposit: [1_000_000, 2_000_000],
+    }));
+    pool = accounts.pool;
+  });
+
+  it('locks the minimum liquidity out of the first deposit', async () => {
+    // floor(sqrt(1_000_000 * 2_000_000))
+    const liquidity = 1_414_213;
+    const state = await program.account.poolState.fetch(pool);
+    expect(state.totalLiquidity.toNumber()).to.equal(liquidity);
+    expect((await balance(userLpToken)).toNumber()).to.equal(liquidity - minimumLiquidity);
+  });
+
+  it('swaps token A for token B', async () => {
+    const amountIn = new BN(10_000);
+    const before = await program.account.poolState.fetch(pool);
+    const expectedOut = quote(amountIn, before.tokenAAmount, before.tokenBAmount);
+    const userBBefore = await balance(userTokenB);
+
+    await swap(amountIn, userTokenA, userTokenB);
+
+    const after = await program.account.poolState.fetch(pool);
+    expect(after.tokenAAmount.toString()).to.equal(before.tokenAAmount.add(amountIn).toString());
+    expect(after.tokenBAmount.toString()).to.equal(before.tokenBAmount.sub(expectedOut).toString());
+    expect((await balance(userTokenB)).sub(userBBefore).toString()).to.equal(expectedOut.toString());
+  });
+
+  it('swaps token B for token A', async () => {
+    const amountIn = new BN(20_000);
+    const before = await program.account.poolState.fetch(pool);
+    const expectedOut = quote(amountIn, before.tokenBAmount, before.tokenAAmount);
+    const userABefore = await balance(userTokenA);
+
+    await swap(amountIn, userTokenB, userTokenA);
+
+    const after = await program.account.poolState.fetch(pool);
+    expect(after.tokenBAmount.toString()).to.equal(before.tokenBAmount.add(amountIn).toString());
+    expect(after.tokenAAmount.toString()).to.equal(before.tokenAAmount.sub(expectedOut).toString());
+    expect((await balance(userTokenA)).sub(userABefore).toString()).to.equal(expectedOut.toString());
+  });
+
+  it('holds the reserves under the pool authority', async () => {
+    for (const reserve of [accounts.tokenAReserve, accounts.tokenBReserve]) {
+      const account = await getAccount(provider.connection, reserve);
+      expect(account.owner.toBase58()).to.equal(accounts.poolAuthority.toBase58());
+    }
+  });
+
+  it('keeps the tracked amounts equal to the reserve balances', async () => {
+    const state = await program.account.poolState.fetch(pool);
+    expect(state.tokenAAmount.toString()).to.equal((await balance(accounts.tokenAReserve)).toString());
+    expect(state.tokenBAmount.toString()).to.equal((await balance(accounts.tokenBReserve)).toString());
+  });
+
+  it('pulls only the ratio-matching amount of the larger side', async () => {
+    const before = await program.account.poolState.fetch(pool);
+    const [userABefore, userBBefore] = [await balance(userTokenA), await balance(userTokenB)];
+    const amountA = new BN(100_000);
+    // Rounded up, so the depositor never pays less than their share
+    const expectedB = amountA.mul(before.tokenBAmount).add(before.tokenAAmount.subn(1)).div(before.tokenAAmount);
+
+    await addLiquidity(amountA, new BN(1_000_000), new BN(0));
+
+    expect(userABefore.sub(await balance(userTokenA)).toString()).to.equal(amountA.toString());
+    expect(userBBefore.sub(await balance(userTokenB)).toString()).to.equal(expectedB.toString());
+  });
+
+  it('rejects a deposit that mints less than min_lp_out', async () => {
+    await expectRejected(addLiquidity(new BN(1_000), new BN(1_000_000), new BN(1_000_000)), 'SlippageExceeded');
+  });
+
+  it('returns the pro-rata share of both reserves on withdrawal', async () => {
+    const lpAmount = new BN(100_000);
+    const before = await program.account.poolState.fetch(pool);
+    const expectedA = lpAmount.mul(before.tokenAAmount).div(before.totalLiquidity);
+    const expectedB = lpAmount.mul(before.tokenBAmount).div(before.totalLiquidity);
+    const [userABefore, userBBefore] = [await balance(userTokenA), await balance(userTokenB)];
+
+    await removeLiquidity(lpAmount, expectedA, expectedB);
+
+    expect((await balance(userTokenA)).sub(userABefore).toString()).to.equal(expectedA.toString());
+    expect((await balance(userTokenB)).sub(userBBefore).toString()).to.equal(expectedB.toString());
+  });
+
+  it('rejects a withdrawal below the minimum outputs', async () => {
+    const lpAmount = new BN(100_000);
+    const state = await program.account.poolState.fetch(pool);
+    const expectedB = lpAmount.mul(state.tokenBAmount).div(state.totalLiquidity);
+    await expectRejected(removeLiquidity(lpAmount, new BN(0), expectedB.addn(1)), 'SlippageExceeded');
+  });
+
+  it('rejects a swap submitted after its deadline', async () => {
+    const anHourAgo = new BN(Math.floor(Date.now() / 1000) - 60 * 60);
+    await expectRejected(swap(new BN(1_000), userTokenA, userTokenB, anHourAgo), 'DeadlineExceeded');
+  });
+
+  it('reports a time-weighted price close to the pool price', async () => {
+    // The first deposit took the oldest observation; let a full second pass
+    await new Promise((resolve) => setTimeout(resolve, 2_000));
+    const twap = await program.methods.getTwap(1).accounts({ pool, observations: accounts.observations }).view();
+
+    // Swaps above move the 2:1 price by a couple of percent at most
+    const priceA = Number(twap.priceA.toString()) / 2 ** 64;
+    const priceB = Number(twap.priceB.toString()) / 2 ** 64;
+    expect(priceA).to.be.closeTo(2, 0.05);
+    expect(priceB).to.be.closeTo(0.5, 0.0125);
+    expect(twap.endTime.sub(twap.startTime).toNumber()).to.be.at.least(1);
+  });
+
+  it('rejects a TWAP window older than the recorded history', async () => {
+    await expectRejected(
+      program.methods.getTwap(24 * 60 * 60).accounts({ pool, observations: accounts.observations }).view(),
+      'InsufficientObservationHistory'
+    );
+  });
+
+  describe('observation interval', () => {
+    const setInterval = async (minutes: number, signer?: Keypair) => {
+      const { multisig } = await program.account.platformConfig.fetch(platform);
+      return program.methods
+        .setPoolObservationInterval(minutes)
+        .accounts({
+          pool,
+          observations: accounts.observations,
+          platform,
+          multisig,
+          signer: signer ? signer.publicKey : provider.wallet.publicKey,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+    };
+
+    it('rejects an interval change without the platform multisig', async () => {
+      await expectRejected(setInterval(60, trader), 'InsufficientApprovals');
+    });
+
+    it('rejects an interval longer than a day', async () => {
+      await expectRejected(setInterval(24 * 60 + 1), 'InvalidObservationInterval');
+      await expectRejected(setInterval(0), 'InvalidObservationInterval');
+    });
+
+    it('widens the spacing between stored observations', async () => {
+      await setInterval(60);
+
+      const before = await program.account.poolObservations.fetch(accounts.observations);
+      expect(before.intervalMinutes).to.equal(60);
+
+      // Over a minute after the newest observation, which the default spacing would record
+      await new Promise((resolve) => setTimeout(resolve, 61_000));
+      await swap(new BN(1_000), userTokenA, userTokenB);
+
+      const after = await program.account.poolObservations.fetch(accounts.observations);
+      expect(after.len).to.equal(before.len);
+      expect(after.head).to.equal(before.head);
+    });
+  });
+
+  it('rejects a swap into the same side of the pool', async () => {
+    await expectRejected(swap(new BN(1_000), userTokenA, userTokenA), 'InvalidSwapAccounts');
+  });
+
+  it('rejects a pool fee of 100% or more', async () => {
+    const { tokenA, tokenB } = await createTraderMints(provider, trader, 0);
+    await expectRejected(
+      initializePool(program, trader, findPoolAccounts(program, tokenA, tokenB), 10_000),
+      'InvalidFee'
+    );
+  });
+});
@@ -0,0 +1,628 @@
+import { BN } from '@project-serum/anchor';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID, MintLayout } from '@solana/spl-token';
+import { expect } from 'chai';
+import { expectRejected } from './utils/assert';
+import {
+  ProjectAccounts,
+  ProjectEnv,
+  balance,
+  closeProject,
+  createProject,
+  createTokenAccount,
+  expireProject,
+  finalizeProject,
+  fund,
+  invest,
+  mintTokens,
+  noProjectLiquidity,
+  now,
+  startProjectEnv,
+  warpTo,
+} from './utils/project';
+
+const supplyOf = async (env: ProjectEnv, mint: PublicKey) => {
+  const info = await env.provider.connection.getAccountInfo(mint);
+  return new BN(MintLayout.decode(info.data).supply.toString());
+};
+
+const redeem = (
+  env: ProjectEnv,
+  accounts: ProjectAccounts,
+  investor: Keypair,
+  tokens: PublicKey,
+  quote: PublicKey,
+  amount: number | BN
+) =>
+  env.program.methods
+    .redeemTokens(new BN(amount))
+    .accounts({
+      project: accounts.project,
+      investor: investor.publicKey,
+      userTokenAccount: tokens,
+      projectTokenMint: accounts.projectTokenMint,
+      quoteMint: accounts.quoteMint,
+      userQuoteAccount: quote,
+      treasuryVault: accounts.treasuryVault,
+      vesting: accounts.vesting,
+      vestingVault: accounts.vestingVault,
+      investorVault: accounts.investorVault,
+      projectSigner: accounts.projectSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      quoteTokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([investor])
+    .rpc();
+
+describe('project expiry and close', () => {
+  const investor = Keypair.generate();
+
+  let env: ProjectEnv;
+  let accounts: ProjectAccounts;
+  let investorTokens: PublicKey;
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, investor);
+
+    accounts = await createProject(env);
+    investorTokens = await invest(env, accounts, investor, 10_000);
+    await finalizeProject(env, accounts);
+  });
+
+  it('rejects the expiry crank before the expiration time', async () => {
+    await expectRejected(
+      env.program.methods
+        .expireProject()
+        .accounts({
+          project: accounts.project,
+          treasuryVault: accounts.treasuryVault,
+          projectSigner: accounts.projectSigner,
+          ...noProjectLiquidity,
+        })
+        .rpc(),
+      'ProjectNotExpired'
+    );
+  });
+
+  it('expires the project once the expiration time has passed', async () => {
+    await expireProject(env, accounts);
+
+    const state = await env.program.account.projectState.fetch(accounts.project);
+    expect(state.status).to.deep.equal({ expired: {} });
+    expect(state.liquidityAmount.toNumber()).to.equal(0);
+  });
+
+  it('refuses to close while tokens or treasury funds remain', async () => {
+    await expectRejected(closeProject(env, accounts), 'OutstandingRedemptions');
+  });
+
+  it('closes every project account once fully redeemed', async () => {
+    const investorQuote = await createTokenAccount(env, accounts.quoteMint, investor.publicKey);
+    await redeem(env, accounts, investor, investorTokens, investorQuote, 10_000);
+
+    const rentBefore = await env.provider.connection.getBalance(accounts.authority.publicKey);
+    await closeProject(env, accounts);
+
+    for (const account of [
+      accounts.project,
+      accounts.treasuryVault,
+      accounts.vesting,
+      accounts.vestingVault,
+      accounts.investorVault,
+      accounts.projectEntry,
+    ]) {
+      expect(await env.provider.connection.getAccountInfo(account)).to.be.null;
+    }
+    expect(await env.provider.connection.getBalance(accounts.authority.publicKey)).to.be.above(rentBefore);
+  });
+});
+
+describe('project pool liquidity', () => {
+  let env: ProjectEnv;
+  let project: ProjectAccounts;
+  let poolAccounts: Record<Exclude<keyof typeof noProjectLiquidity, 'projectLpAccount' | 'lpTokenProgram'>, PublicKey>;
+  let projectLp: PublicKey;
+
+  const liquidity = (lpAccount: PublicKey) => ({
+    ...poolAccounts,
+    projectLpAccount: lpAccount,
+    lpTokenProgram: TOKEN_PROGRAM_ID,
+  });
+
+  const registerLiquidity = (lpAccount: PublicKey) =>
+    env.program.methods
+      .registerProjectLiquidity()
+      .accounts({
+        project: project.project,
+        authority: project.authority.publicKey,
+        projectSigner: project.projectSigner,
+        pool: poolAccounts.pool,
+        projectLpAccount: lpAccount,
+      })
+      .signers([project.authority])
+      .rpc();
+
+  before(async () => {
+    env = await startProjectEnv();
+    project = await createProject(env);
+    const { program } = env;
+    const pda = (seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    // Seed the project's pair with LP minted straight to the project signer
+    const authorityTokens = await invest(env, project, project.authority, 100_000);
+    const authorityQuote = await createTokenAccount(env, project.quoteMint, project.authority.publicKey);
+    await mintTokens(env, project.quoteMint, authorityQuote, 100_000);
+
+    const [tokenA, tokenB] = [project.projectTokenMint, project.quoteMint].sort((a, b) =>
+      Buffer.compare(a.toBuffer(), b.toBuffer())
+    );
+    const pool = pda([Buffer.from('pool'), tokenA.toBuffer(), tokenB.toBuffer()]);
+    poolAccounts = {
+      pool,
+      tokenA,
+      tokenB,
+      poolAuthority: pda([Buffer.from('pool_authority'), pool.toBuffer()]),
+      tokenAReserve: pda([Buffer.from('reserve_a'), pool.toBuffer()]),
+      tokenBReserve: pda([Buffer.from('reserve_b'), pool.toBuffer()]),
+      tokenAProgram: TOKEN_PROGRAM_ID,
+      tokenBProgram: TOKEN_PROGRAM_ID,
+      observations: pda([Buffer.from('observations'), pool.toBuffer()]),
+      lpTokenMint: pda([Buffer.from('lp_mint'), pool.toBuffer()]),
+    };
+    await program.methods
+      .initializePool(new BN(30), { constantProduct: {} })
+      .accounts({
+        ...poolAccounts,
+        authority: project.authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([project.authority])
+      .rpc();
+
+    projectLp = await createTokenAccount(env, poolAccounts.lpTokenMint, project.projectSigner);
+    const [userA, userB] = tokenA.equals(project.projectTokenMint)
+      ? [authorityTokens, authorityQuote]
+      : [authorityQuote, authorityTokens];
+    await program.methods
+      .addLiquidity(new BN(100_000), new BN(100_000), new BN(0), new BN((await now(env)) + 60))
+      .accounts({
+        ...poolAccounts,
+        userTokenAAccount: userA,
+        userTokenBAccount: userB,
+        userLpTokenAccount: projectLp,
+        authority: project.authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([project.authority])
+      .rpc();
+    await registerLiquidity(projectLp);
+
+    await finalizeProject(env, project);
+  });
+
+  it('records the LP account only once', async () => {
+    const state = await env.program.account.projectState.fetch(project.project);
+    expect(state.lpAccount.toBase58()).to.equal(projectLp.toBase58());
+
+    const otherLp = await createTokenAccount(env, poolAccounts.lpTokenMint, project.projectSigner);
+    await expectRejected(registerLiquidity(otherLp), 'ProjectLiquidityRegistered');
+  });
+
+  it('rejects expiry without the registered pool accounts', async () => {
+    await expectRejected(expireProject(env, project), 'ProjectLiquidityMissing');
+  });
+
+  it('rejects expiry with another LP account swapped in', async () => {
+    // Empty, but owned by the project signer and on the right LP mint
+    const emptyLp = await createTokenAccount(env, poolAccounts.lpTokenMint, project.projectSigner);
+    await expectRejected(expireProject(env, project, liquidity(emptyLp)), 'InvalidProjectPool');
+  });
+
+  it('withdraws the registered liquidity on expiry', async () => {
+    const { program } = env;
+    const treasuryBefore = await balance(env, project.treasuryVault);
+    const supplyBefore = await supplyOf(env, project.projectTokenMint);
+    const mintedBefore = (await program.account.projectState.fetch(project.project)).mintedSupply;
+
+    await expireProject(env, project, liquidity(projectLp));
+
+    // Everything but the locked minimum liquidity comes out of the pool
+    const burned = supplyBefore.sub(await supplyOf(env, project.projectTokenMint));
+    expect(burned.toNumber()).to.equal(99_000);
+    expect((await balance(env, project.treasuryVault)).sub(treasuryBefore).toNumber()).to.equal(99_000);
+    expect((await balance(env, projectLp)).toNumber()).to.equal(0);
+    const state = await program.account.projectState.fetch(project.project);
+    expect(state.status).to.deep.equal({ expired: {} });
+    expect(mintedBefore.sub(state.mintedSupply).toString()).to.equal(burned.toString());
+  });
+});
+
+describe('redemption', () => {
+  const investors = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+
+  let env: ProjectEnv;
+  let accounts: ProjectAccounts;
+  const holdings: { tokens: PublicKey; quote: PublicKey }[] = [];
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, ...investors);
+
+    accounts = await createProject(env);
+    for (const [investor, amount] of [
+      [investors[0], 3_333],
+      [investors[1], 3_333],
+      [investors[2], 3_334],
+    ] as [Keypair, number][]) {
+      holdings.push({
+        tokens: await invest(env, accounts, investor, amount),
+        quote: await createTokenAccount(env, accounts.quoteMint, investor.publicKey),
+      });
+    }
+    await finalizeProject(env, accounts);
+    await expireProject(env, accounts);
+
+    // One unit more than the raise, so the shares do not divide evenly
+    await mintTokens(env, accounts.quoteMint, accounts.treasuryVault, 1);
+  });
+
+  it('pays out a pro-rata share of the treasury, rounded down', async () => {
+    // 3_333 * 10_001 / 10_000, then 3_333 * 6_668 / 6_667
+    for (const [i, expected] of [[0, 3_333], [1, 3_333]]) {
+      await redeem(env, accounts, investors[i], holdings[i].tokens, holdings[i].quote, 3_333);
+      expect((await balance(env, holdings[i].quote)).toNumber()).to.equal(expected);
+    }
+    expect((await balance(env, accounts.treasuryVault)).toNumber()).to.equal(3_335);
+  });
+
+  it('sweeps the rounding dust to the final redeemer', async () => {
+    await redeem(env, accounts, investors[2], holdings[2].tokens, holdings[2].quote, 3_334);
+
+    expect((await balance(env, holdings[2].quote)).toNumber()).to.equal(3_335);
+    expect((await balance(env, accounts.treasuryVault)).toNumber()).to.equal(0);
+    expect((await supplyOf(env, accounts.projectTokenMint)).toNumber()).to.equal(0);
+  });
+});
+
+describe('redemption with tokens still vesting', () => {
+  const DAY = 24 * 60 * 60;
+  const beneficiary = Keypair.generate();
+  const investor = Keypair.generate();
+
+  let env: ProjectEnv;
+  let accounts: ProjectAccounts;
+  let investorTokens: PublicKey;
+  let investorQuote: PublicKey;
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, investor);
+
+    // The team allocation is still mostly unvested when the project expires
+    accounts = await createProject(env, {
+      saleSupply: 900_000,
+      vesting: [
+        {
+          beneficiary: beneficiary.publicKey,
+          amount: new BN(100_000),
+          cliff: new BN(10 * DAY),
+          duration: new BN(365 * DAY),
+          revocable: false,
+        },
+      ],
+    });
+    investorTokens = await invest(env, accounts, investor, 10_000);
+    investorQuote = await createTokenAccount(env, accounts.quoteMint, investor.publicKey);
+    await finalizeProject(env, accounts);
+    await expireProject(env, accounts);
+  });
+
+  it('shares the treasury only among tokens outside the vaults', async () => {
+    expect((await balance(env, accounts.vestingVault)).toNumber()).to.equal(100_000);
+    const mintedBefore = (await env.program.account.projectState.fetch(accounts.project)).mintedSupply;
+
+    await redeem(env, accounts, investor, investorTokens, investorQuote, 10_000);
+
+    // The investor holds every circulating token, so sweeps the whole treasury
+    expect((await balance(env, investorQuote)).toNumber()).to.equal(10_000);
+    expect((await balance(env, accounts.treasuryVault)).toNumber()).to.equal(0);
+    const state = await env.program.account.projectState.fetch(accounts.project);
+    expect(mintedBefore.sub(state.mintedSupply).toNumber()).to.equal(10_000);
+  });
+});
+
+describe('fundraise cancellation', () => {
+  const guardian = Keypair.generate();
+  const owners = [Keypair.generate(), Keypair.generate()];
+  const investor = Keypair.generate();
+  const outsider = Keypair.generate();
+  const multisig = Keypair.generate();
+
+  let env: ProjectEnv;
+
+  const cancel = (project: PublicKey, canceller: Keypair, cosigners: Keypair[] = [], withMultisig = false) =>
+    env.program.methods
+      .cancelFundraise(0)
+      .accounts({
+        project,
+        multisig: withMultisig ? multisig.publicKey : null,
+        canceller: canceller.publicKey,
+      })
+      .remainingAccounts(
+        cosigners.map((cosigner) => ({ pubkey: cosigner.publicKey, isSigner: true, isWritable: false }))
+      )
+      .signers([canceller, ...cosigners])
+      .rpc();
+
+  const expectRefunding = async (project: PublicKey) => {
+    const state = await env.program.account.projectState.fetch(project);
+    expect(state.status).to.deep.equal({ refunding: {} });
+    expect(state.refundsEnabled).to.be.true;
+  };
+
+  // A project that already has an investor, so only governance can cancel it
+  const investedProject = async () => {
+    const accounts = await createProject(env, { guardian: guardian.publicKey, multisig: multisig.publicKey });
+    await invest(env, accounts, investor, 1_000);
+    return accounts;
+  };
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, guardian, ...owners, investor, outsider);
+
+    await env.program.methods
+      .initializeMultisig(owners.map((owner) => owner.publicKey), new BN(2))
+      .accounts({ multisig: multisig.publicKey, payer: owners[0].publicKey, systemProgram: SystemProgram.programId })
+      .signers([owners[0], multisig])
+      .rpc();
+  });
+
+  it('lets the creator cancel before anyone has invested', async () => {
+    const accounts = await createProject(env);
+    await cancel(accounts.project, accounts.authority);
+    await expectRefunding(accounts.project);
+  });
+
+  it('rejects the creator cancelling once investments are in', async () => {
+    const accounts = await investedProject();
+    await expectRejected(cancel(accounts.project, accounts.authority), 'InvestmentsAlreadyMade');
+  });
+
+  it('lets the guardian cancel after investments', async () => {
+    const accounts = await investedProject();
+    await cancel(accounts.project, guardian);
+    await expectRefunding(accounts.project);
+  });
+
+  it('rejects a cancel from anyone else', async () => {
+    const accounts = await investedProject();
+    await expectRejected(cancel(accounts.project, outsider), 'UnauthorizedAccess');
+  });
+
+  it('lets the multisig cancel once its threshold has signed', async () => {
+    const accounts = await investedProject();
+    await cancel(accounts.project, owners[0], [owners[1]], true);
+    await expectRefunding(accounts.project);
+  });
+
+  it('rejects a multisig cancel below its threshold', async () => {
+    const accounts = await investedProject();
+    await expectRejected(cancel(accounts.project, owners[0], [], true), 'UnauthorizedAccess');
+  });
+});
+
+describe('vesting', () => {
+  const DAY = 24 * 60 * 60;
+  const beneficiary = Keypair.generate();
+  const revocableBeneficiary = Keypair.generate();
+  const investor = Keypair.generate();
+  const owner = Keypair.generate();
+  const multisig = Keypair.generate();
+
+  let env: ProjectEnv;
+  let accounts: ProjectAccounts;
+  let cliffTime: number;
+  const beneficiaryTokens = new Map<string, PublicKey>();
+
+  const claim = (index: number, claimant: Keypair) =>
+    env.program.methods
+      .claimVested(index)
+      .accounts({
+        project: accounts.project,
+        vesting: accounts.vesting,
+        vestingVault: accounts.vestingVault,
+        projectTokenMint: accounts.projectTokenMint,
+        beneficiary: claimant.publicKey,
+        beneficiaryTokenAccount: beneficiaryTokens.get(claimant.publicKey.toBase58()),
+        projectSigner: accounts.projectSigner,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([claimant])
+      .rpc();
+
+  const claimed = (claimant: Keypair) => balance(env, beneficiaryTokens.get(claimant.publicKey.toBase58()));
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, beneficiary, revocableBeneficiary, investor, owner);
+
+    await env.program.methods
+      .initializeMultisig([owner.publicKey], new BN(1))
+      .accounts({ multisig: multisig.publicKey, payer: owner.publicKey, systemProgram: SystemProgram.programId })
+      .signers([owner, multisig])
+      .rpc();
+
+    // Two identical 100_000 allocations releasing over 100 days after a 10 day cliff
+    const allocation = { amount: new BN(100_000), cliff: new BN(10 * DAY), duration: new BN(100 * DAY) };
+    accounts = await createProject(env, {
+      saleSupply: 800_000,
+      multisig: multisig.publicKey,
+      vesting: [
+        { ...allocation, beneficiary: beneficiary.publicKey, revocable: false },
+        { ...allocation, beneficiary: revocableBeneficiary.publicKey, revocable: true },
+      ],
+    });
+    for (const wallet of [beneficiary, revocableBeneficiary]) {
+      beneficiaryTokens.set(
+        wallet.publicKey.toBase58(),
+        await createTokenAccount(env, accounts.projectTokenMint, wallet.publicKey)
+      );
+    }
+    // The raise has to succeed for the project to go active
+    await invest(env, accounts, investor, 1_000);
+    await finalizeProject(env, accounts);
+
+    cliffTime = (await env.program.account.vestingState.fetch(accounts.vesting)).schedules[0].cliffTime.toNumber();
+  });
+
+  it('releases nothing until the cliff has passed', async () => {
+    await expectRejected(claim(0, beneficiary), 'NothingToClaim');
+    await warpTo(env, cliffTime);
+    await expectRejected(claim(0, beneficiary), 'NothingToClaim');
+  });
+
+  it('releases linearly after the cliff', async () => {
+    await warpTo(env, cliffTime + 50 * DAY);
+    await claim(0, beneficiary);
+    expect((await claimed(beneficiary)).toNumber()).to.equal(50_000);
+  });
+
+  it('burns the unvested remainder on revoke and frees it from the supply cap', async () => {
+    const mintedBefore = (await env.program.account.projectState.fetch(accounts.project)).mintedSupply;
+    const supplyBefore = await supplyOf(env, accounts.projectTokenMint);
+
+    await env.program.methods
+      .revokeVesting(1)
+      .accounts({
+        project: accounts.project,
+        vesting: accounts.vesting,
+        vestingVault: accounts.vestingVault,
+        projectTokenMint: accounts.projectTokenMint,
+        multisig: multisig.publicKey,
+        revoker: owner.publicKey,
+        projectSigner: accounts.projectSigner,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([owner])
+      .rpc();
+
+    const { mintedSupply } = await env.program.account.projectState.fetch(accounts.project);
+    expect(mintedBefore.sub(mintedSupply).toNumber()).to.equal(50_000);
+    expect(supplyBefore.sub(await supplyOf(env, accounts.projectTokenMint)).toNumber()).to.equal(50_000);
+
+    // What had vested by the revoke stays claimable
+    await claim(1, revocableBeneficiary);
+    expect((await claimed(revocableBeneficiary)).toNumber()).to.equal(50_000);
+  });
+
+  it('releases the whole allocation at the end', async () => {
+    await warpTo(env, cliffTime + 100 * DAY);
+    await claim(0, beneficiary);
+    expect((await claimed(beneficiary)).toNumber()).to.equal(100_000);
+    await expectRejected(claim(1, revocableBeneficiary), 'NothingToClaim');
+  });
+});
+
+describe('investor lockup', () => {
+  const DAY = 24 * 60 * 60;
+  const investor = Keypair.generate();
+
+  let env: ProjectEnv;
+  let accounts: ProjectAccounts;
+  let investorTokens: PublicKey;
+  let fundraiseEnd: number;
+
+  const claim = () =>
+    env.program.methods
+      .claimUnlocked()
+      .accounts({
+        project: accounts.project,
+        investorLock: PublicKey.findProgramAddressSync(
+          [Buffer.from('investor_lock'), accounts.project.toBuffer(), investor.publicKey.toBuffer()],
+          env.program.programId
+        )[0],
+        investorVault: accounts.investorVault,
+        projectTokenMint: accounts.projectTokenMint,
+        investor: investor.publicKey,
+        userTokenAccount: investorTokens,
+        projectSigner: accounts.projectSigner,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([investor])
+      .rpc();
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, investor);
+
+    // 20% unlocks at the fundraise end, the rest over the following 100 days
+    accounts = await createProject(env, {
+      lockup: { tgeUnlockBps: 2_000, unlockDuration: new BN(100 * DAY) },
+    });
+    investorTokens = await invest(env, accounts, investor, 10_000);
+    fundraiseEnd = (await env.program.account.projectState.fetch(accounts.project)).fundraiseEndTime.toNumber();
+  });
+
+  it('holds purchased tokens in the vault during the raise', async () => {
+    expect((await balance(env, investorTokens)).toNumber()).to.equal(0);
+    expect((await balance(env, accounts.investorVault)).toNumber()).to.equal(10_000);
+    await expectRejected(claim(), 'InvalidProjectState');
+  });
+
+  it('unlocks the TGE share once the raise closes', async () => {
+    await finalizeProject(env, accounts);
+    await claim();
+    expect((await balance(env, investorTokens)).toNumber()).to.equal(2_000);
+  });
+
+  it('releases the remainder linearly over the unlock period', async () => {
+    await warpTo(env, fundraiseEnd + 50 * DAY);
+    await claim();
+    expect((await balance(env, investorTokens)).toNumber()).to.equal(6_000);
+
+    await warpTo(env, fundraiseEnd + 100 * DAY);
+    await claim();
+    expect((await balance(env, investorTokens)).toNumber()).to.equal(10_000);
+    await expectRejected(claim(), 'NothingToClaim');
+  });
+});
+
+describe('supply cap', () => {
+  const investor = Keypair.generate();
+
+  let env: ProjectEnv;
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, investor);
+  });
+
+  it('mints up to the total supply and no further', async () => {
+    // A raise ceiling above the supply leaves the supply as the only limit
+    const accounts = await createProject(env, { totalSupply: 10_000, maxRaise: 20_000, maxInvestment: 20_000 });
+    await invest(env, accounts, investor, 10_000);
+
+    const state = await env.program.account.projectState.fetch(accounts.project);
+    expect(state.mintedSupply.toNumber()).to.equal(10_000);
+    await expectRejected(invest(env, accounts, investor, 1), 'InvestmentCapReached');
+  });
+
+  it('keeps the vesting allocation out of the sale', async () => {
+    const accounts = await createProject(env, {
+      totalSupply: 10_000,
+      saleSupply: 8_000,
+      maxRaise: 20_000,
+      maxInvestment: 20_000,
+      vesting: [
+        { beneficiary: investor.publicKey, amount: new BN(2_000), cliff: new BN(0), duration: new BN(0), revocable: false },
+      ],
+    });
+
+    await expectRejected(invest(env, accounts, investor, 8_001), 'InvestmentCapReached');
+    await invest(env, accounts, investor, 8_000);
+    expect((await env.program.account.projectState.fetch(accounts.project)).mintedSupply.toNumber()).to.equal(10_000);
+  });
+});
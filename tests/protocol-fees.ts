@@ -0,0 +1,295 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID, createAccount, getAccount } from '@solana/spl-token';
+import { expect } from 'chai';
+import { ensurePlatform, findPlatform, findProgramData } from './utils/platform';
+import { expectRejected } from './utils/assert';
+import { airdrop, inAMinute, setUpPool } from './utils/pool';
+import {
+  ProjectEnv,
+  balance,
+  createProject,
+  createTokenAccount,
+  expireProject,
+  finalizeProject,
+  fund,
+  invest,
+  startProjectEnv,
+} from './utils/project';
+
+describe('protocol fees', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+
+  const trader = Keypair.generate();
+  const attacker = Keypair.generate();
+  const feeRecipient = Keypair.generate();
+  const otherMultisig = Keypair.generate();
+
+  let platform: PublicKey;
+  let platformMultisig: PublicKey;
+
+  before(async () => {
+    await airdrop(provider, attacker);
+
+    platform = await ensurePlatform(program, provider);
+    platformMultisig = (await program.account.platformConfig.fetch(platform)).multisig;
+
+    await program.methods
+      .initializeMultisig([attacker.publicKey], new anchor.BN(1))
+      .accounts({ multisig: otherMultisig.publicKey, payer: attacker.publicKey, systemProgram: SystemProgram.programId })
+      .signers([attacker, otherMultisig])
+      .rpc();
+  });
+
+  after(async () => {
+    // Leave fees at zero for the suites that share the config
+    await program.methods
+      .updatePlatformFees(provider.wallet.publicKey, 0, 0)
+      .accounts({ platform, multisig: platformMultisig, signer: provider.wallet.publicKey })
+      .rpc();
+  });
+
+  it('lets the platform multisig update the fees', async () => {
+    await program.methods
+      .updatePlatformFees(feeRecipient.publicKey, 250, 2_000)
+      .accounts({ platform, multisig: platformMultisig, signer: provider.wallet.publicKey })
+      .rpc();
+
+    const config = await program.account.platformConfig.fetch(platform);
+    expect(config.feeRecipient.toBase58()).to.equal(feeRecipient.publicKey.toBase58());
+    expect(config.fundraiseFeeBps).to.equal(250);
+    expect(config.swapProtocolFeeBps).to.equal(2_000);
+  });
+
+  it('rejects an update signed by a non-owner', async () => {
+    await expectRejected(
+      program.methods
+        .updatePlatformFees(attacker.publicKey, 250, 2_000)
+        .accounts({ platform, multisig: platformMultisig, signer: attacker.publicKey })
+        .signers([attacker])
+        .rpc(),
+      'InsufficientApprovals'
+    );
+  });
+
+  it('rejects an update through a different multisig', async () => {
+    await expectRejected(
+      program.methods
+        .updatePlatformFees(attacker.publicKey, 250, 2_000)
+        .accounts({ platform, multisig: otherMultisig.publicKey, signer: attacker.publicKey })
+        .signers([attacker])
+        .rpc(),
+      'InvalidMultisig'
+    );
+  });
+
+  it('rejects fees above the allowed maximum', async () => {
+    await expectRejected(
+      program.methods
+        .updatePlatformFees(feeRecipient.publicKey, 1_001, 2_000)
+        .accounts({ platform, multisig: platformMultisig, signer: provider.wallet.publicKey })
+        .rpc(),
+      'InvalidFeeConfig'
+    );
+  });
+
+  it('sets aside the protocol share of swap fees and pays it out', async () => {
+    const { accounts: poolAccounts, userTokenA, userTokenB } = await setUpPool(program, provider, trader);
+    const { pool, tokenA, tokenB } = poolAccounts;
+    const recipientTokenA = await createAccount(provider.connection, trader, tokenA, feeRecipient.publicKey);
+    const recipientTokenB = await createAccount(provider.connection, trader, tokenB, feeRecipient.publicKey);
+
+    // 0.3% of 100_000 is a 300 fee, of which the platform takes 20%
+    await program.methods
+      .swap(new anchor.BN(100_000), new anchor.BN(0), inAMinute())
+      .accounts({
+        ...poolAccounts,
+        userSourceAccount: userTokenA,
+        userDestinationAccount: userTokenB,
+        platform,
+        authority: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+        launchPurchases: null,
+        piVerification: null,
+      })
+      .signers([trader])
+      .rpc();
+
+    let state = await program.account.poolState.fetch(pool);
+    expect(state.protocolFeesA.toNumber()).to.equal(60);
+    expect(state.tokenAAmount.toNumber()).to.equal(1_000_000 + 100_000 - 60);
+
+    await program.methods
+      .collectPoolProtocolFees()
+      .accounts({
+        ...poolAccounts,
+        platform,
+        feeRecipientTokenA: recipientTokenA,
+        feeRecipientTokenB: recipientTokenB,
+      })
+      .rpc();
+
+    state = await program.account.poolState.fetch(pool);
+    expect(state.protocolFeesA.toNumber()).to.equal(0);
+    const collected = await getAccount(provider.connection, recipientTokenA);
+    expect(Number(collected.amount)).to.equal(60);
+  });
+});
+
+describe('fundraise protocol fee', () => {
+  const investor = Keypair.generate();
+  const feeRecipient = Keypair.generate();
+
+  let env: ProjectEnv;
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, investor);
+  });
+
+  it('keeps the uncollected fee out of redemptions', async () => {
+    const { program } = env;
+    const platform = await ensurePlatform(program, env.provider);
+    const { multisig } = await program.account.platformConfig.fetch(platform);
+    await program.methods
+      .updatePlatformFees(feeRecipient.publicKey, 250, 0)
+      .accounts({ platform, multisig, signer: env.provider.wallet.publicKey })
+      .rpc();
+
+    const accounts = await createProject(env);
+    const investorTokens = await invest(env, accounts, investor, 10_000);
+    await finalizeProject(env, accounts);
+    await expireProject(env, accounts);
+
+    // 2.5% of the 10_000 raise stays owed to the platform
+    const investorQuote = await createTokenAccount(env, accounts.quoteMint, investor.publicKey);
+    await program.methods
+      .redeemTokens(new anchor.BN(10_000))
+      .accounts({
+        project: accounts.project,
+        investor: investor.publicKey,
+        userTokenAccount: investorTokens,
+        projectTokenMint: accounts.projectTokenMint,
+        quoteMint: accounts.quoteMint,
+        userQuoteAccount: investorQuote,
+        treasuryVault: accounts.treasuryVault,
+        vesting: accounts.vesting,
+        vestingVault: accounts.vestingVault,
+        investorVault: accounts.investorVault,
+        projectSigner: accounts.projectSigner,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([investor])
+      .rpc();
+    expect((await balance(env, investorQuote)).toNumber()).to.equal(9_750);
+
+    const recipientQuote = await createTokenAccount(env, accounts.quoteMint, feeRecipient.publicKey);
+    await program.methods
+      .collectProtocolFees()
+      .accounts({
+        project: accounts.project,
+        platform,
+        treasuryVault: accounts.treasuryVault,
+        quoteMint: accounts.quoteMint,
+        feeRecipientAccount: recipientQuote,
+        projectSigner: accounts.projectSigner,
+        quoteTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    expect((await balance(env, recipientQuote)).toNumber()).to.equal(250);
+    expect((await balance(env, accounts.treasuryVault)).toNumber()).to.equal(0);
+    expect((await program.account.projectState.fetch(accounts.project)).protocolFeeOwed.toNumber()).to.equal(0);
+  });
+});
+
+describe('platform initialization', () => {
+  const outsider = Keypair.generate();
+
+  let env: ProjectEnv;
+
+  before(async () => {
+    env = await startProjectEnv();
+    await fund(env, outsider);
+  });
+
+  it('rejects initialization by anyone but the upgrade authority', async () => {
+    const { program } = env;
+    const multisig = Keypair.generate();
+    await program.methods
+      .initializeMultisig([outsider.publicKey], new anchor.BN(1))
+      .accounts({ multisig: multisig.publicKey, payer: outsider.publicKey, systemProgram: SystemProgram.programId })
+      .signers([outsider, multisig])
+      .rpc();
+
+    await expectRejected(
+      program.methods
+        .initializePlatform(outsider.publicKey, 0, 0, [])
+        .accounts({
+          platform: findPlatform(program),
+          multisig: multisig.publicKey,
+          programData: findProgramData(program),
+          payer: outsider.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([outsider])
+        .rpc(),
+      'UnauthorizedAccess'
+    );
+  });
+
+  it('lets the upgrade authority initialize the platform', async () => {
+    const platform = await ensurePlatform(env.program, env.provider);
+    expect(await env.provider.connection.getAccountInfo(platform)).to.not.be.null;
+  });
+});
+
+describe('platform config migration', () => {
+  // Byte length of the fields shared by every layout, discriminator included;
+  // mirrors migration::PlatformConfigV1
+  const SHARED_PREFIX_LEN = 8 + 1 + 1 + 32 + 32 + 2 + 2;
+  const V1_RESERVED_LEN = 64;
+
+  let env: ProjectEnv;
+  let platform: PublicKey;
+
+  const migrate = () =>
+    env.program.methods
+      .migratePlatformConfig()
+      .accounts({ platform, payer: env.provider.wallet.publicKey, systemProgram: SystemProgram.programId })
+      .rpc();
+
+  before(async () => {
+    env = await startProjectEnv();
+    platform = await ensurePlatform(env.program, env.provider);
+  });
+
+  it('migrates a fee-only platform config and keeps its settings', async () => {
+    const before = await env.program.account.platformConfig.fetch(platform);
+    const info = await env.provider.connection.getAccountInfo(platform);
+    const v1 = Buffer.concat([info.data.subarray(0, SHARED_PREFIX_LEN), Buffer.alloc(V1_RESERVED_LEN)]);
+    v1.writeUInt8(1, 8);
+    env.context.setAccount(platform, { ...info, data: v1 });
+
+    await migrate();
+
+    const after = await env.program.account.platformConfig.fetch(platform);
+    expect(after.version).to.equal(2);
+    expect(after.multisig.toBase58()).to.equal(before.multisig.toBase58());
+    expect(after.feeRecipient.toBase58()).to.equal(before.feeRecipient.toBase58());
+    expect(after.fundraiseFeeBps).to.equal(before.fundraiseFeeBps);
+    expect(after.swapProtocolFeeBps).to.equal(before.swapProtocolFeeBps);
+    expect(after.isPaused).to.be.false;
+    expect(after.allowedQuoteMints).to.be.empty;
+  });
+
+  it('rejects a platform config already at the current version', async () => {
+    await expectRejected(migrate(), 'AlreadyMigrated');
+  });
+});
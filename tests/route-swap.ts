@@ -0,0 +1,168 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, AccountMeta } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo } from '@solana/spl-token';
+import { expect } from 'chai';
+import { ensurePlatform } from './utils/platform';
+import { expectRejected } from './utils/assert';
+import { airdrop, findPoolAccounts, inAMinute, initializePool, tokenBalance } from './utils/pool';
+
+describe('route swap', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+
+  const trader = Keypair.generate();
+  const feeBps = 30;
+
+  // Two project tokens, each paired only with the quote token
+  let platform: PublicKey;
+  let tokenX: PublicKey;
+  let tokenY: PublicKey;
+  let quoteMint: PublicKey;
+  const userAccounts = new Map<string, PublicKey>();
+
+  const balance = (account: PublicKey) => tokenBalance(provider, account);
+
+  const userAccount = (mint: PublicKey) => userAccounts.get(mint.toBase58());
+
+  // Mirrors the constant-product quote in PoolState::quote_swap
+  const quote = (amountIn: BN, reserveIn: BN, reserveOut: BN) => {
+    const amountInWithFee = amountIn.sub(amountIn.muln(feeBps).addn(9_999).divn(10_000));
+    return amountInWithFee.mul(reserveOut).div(reserveIn.add(amountInWithFee));
+  };
+
+  // Accounts for one hop, in the order pool::RouteSwap expects
+  const hopAccounts = (mintIn: PublicKey, mintOut: PublicKey): AccountMeta[] => {
+    const accounts = findPoolAccounts(program, mintIn, mintOut);
+    const aToB = accounts.tokenA.equals(mintIn);
+    const [reserveIn, reserveOut] = aToB
+      ? [accounts.tokenAReserve, accounts.tokenBReserve]
+      : [accounts.tokenBReserve, accounts.tokenAReserve];
+    return [
+      { pubkey: accounts.pool, isSigner: false, isWritable: true },
+      { pubkey: accounts.observations, isSigner: false, isWritable: true },
+      { pubkey: accounts.poolAuthority, isSigner: false, isWritable: false },
+      { pubkey: reserveIn, isSigner: false, isWritable: true },
+      { pubkey: reserveOut, isSigner: false, isWritable: true },
+      { pubkey: mintIn, isSigner: false, isWritable: false },
+      { pubkey: mintOut, isSigner: false, isWritable: false },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ];
+  };
+
+  const routeSwap = (amountIn: BN, minAmountOut: BN, path: PublicKey[]) =>
+    program.methods
+      .routeSwap(amountIn, minAmountOut, inAMinute())
+      .accounts({
+        userSourceAccount: userAccount(path[0]),
+        userDestinationAccount: userAccount(path[path.length - 1]),
+        platform,
+        authority: trader.publicKey,
+        sourceTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(path.slice(1).flatMap((mintOut, i) => hopAccounts(path[i], mintOut)))
+      .signers([trader])
+      .rpc();
+
+  const reservesFor = async (mintIn: PublicKey, mintOut: PublicKey) => {
+    const state = await program.account.poolState.fetch(findPoolAccounts(program, mintIn, mintOut).pool);
+    return state.tokenA.equals(mintIn)
+      ? [state.tokenAAmount, state.tokenBAmount]
+      : [state.tokenBAmount, state.tokenAAmount];
+  };
+
+  const createPool = async (projectMint: PublicKey, projectAmount: number, quoteAmount: number) => {
+    const accounts = findPoolAccounts(program, projectMint, quoteMint);
+    const { tokenA, tokenB } = accounts;
+    await initializePool(program, trader, accounts, feeBps);
+
+    const amounts = tokenA.equals(projectMint) ? [projectAmount, quoteAmount] : [quoteAmount, projectAmount];
+    const userLpToken = await createAccount(provider.connection, trader, accounts.lpTokenMint, trader.publicKey);
+    await program.methods
+      .addLiquidity(new BN(amounts[0]), new BN(amounts[1]), new BN(0), inAMinute())
+      .accounts({
+        ...accounts,
+        userTokenAAccount: userAccount(tokenA),
+        userTokenBAccount: userAccount(tokenB),
+        userLpTokenAccount: userLpToken,
+        authority: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([trader])
+      .rpc();
+  };
+
+  before(async () => {
+    await airdrop(provider, trader);
+    platform = await ensurePlatform(program, provider);
+
+    [tokenX, tokenY, quoteMint] = [
+      await createMint(provider.connection, trader, trader.publicKey, null, 6),
+      await createMint(provider.connection, trader, trader.publicKey, null, 6),
+      await createMint(provider.connection, trader, trader.publicKey, null, 6),
+    ];
+    for (const mint of [tokenX, tokenY, quoteMint]) {
+      const account = await createAccount(provider.connection, trader, mint, trader.publicKey);
+      await mintTo(provider.connection, trader, mint, account, trader, 100_000_000);
+      userAccounts.set(mint.toBase58(), account);
+    }
+
+    await createPool(tokenX, 10_000_000, 5_000_000);
+    await createPool(tokenY, 4_000_000, 8_000_000);
+  });
+
+  it('swaps one project token for another through the quote token', async () => {
+    const amountIn = new BN(100_000);
+    const [xIn, quoteOut] = await reservesFor(tokenX, quoteMint);
+    const [quoteIn, yOut] = await reservesFor(quoteMint, tokenY);
+    const quoteAmount = quote(amountIn, xIn, quoteOut);
+    const expectedOut = quote(quoteAmount, quoteIn, yOut);
+    const [xBefore, quoteBefore, yBefore] = await Promise.all(
+      [tokenX, quoteMint, tokenY].map((mint) => balance(userAccount(mint)))
+    );
+
+    await routeSwap(amountIn, expectedOut, [tokenX, quoteMint, tokenY]);
+
+    expect(xBefore.sub(await balance(userAccount(tokenX))).toString()).to.equal(amountIn.toString());
+    expect((await balance(userAccount(tokenY))).sub(yBefore).toString()).to.equal(expectedOut.toString());
+    // The intermediate token moves pool to pool without touching the trader
+    expect((await balance(userAccount(quoteMint))).toString()).to.equal(quoteBefore.toString());
+
+    const [, yOutAfter] = await reservesFor(quoteMint, tokenY);
+    expect(yOutAfter.toString()).to.equal(yOut.sub(expectedOut).toString());
+  });
+
+  it('applies the minimum output to the whole route', async () => {
+    const amountIn = new BN(100_000);
+    const [xIn, quoteOut] = await reservesFor(tokenX, quoteMint);
+    const [quoteIn, yOut] = await reservesFor(quoteMint, tokenY);
+    const expectedOut = quote(quote(amountIn, xIn, quoteOut), quoteIn, yOut);
+    const xBefore = await balance(userAccount(tokenX));
+
+    await expectRejected(routeSwap(amountIn, expectedOut.addn(1), [tokenX, quoteMint, tokenY]), 'SlippageExceeded');
+    // The first hop is rolled back with the rest
+    expect((await balance(userAccount(tokenX))).toString()).to.equal(xBefore.toString());
+  });
+
+  it('rejects hops that do not connect', async () => {
+    const hops = [...hopAccounts(tokenX, quoteMint), ...hopAccounts(tokenX, quoteMint)];
+    await expectRejected(
+      program.methods
+        .routeSwap(new BN(1_000), new BN(0), inAMinute())
+        .accounts({
+          userSourceAccount: userAccount(tokenX),
+          userDestinationAccount: userAccount(quoteMint),
+          platform,
+          authority: trader.publicKey,
+          sourceTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(hops)
+        .signers([trader])
+        .rpc(),
+      'InvalidRoute'
+    );
+  });
+});
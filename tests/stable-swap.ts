@@ -0,0 +1,111 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { expect } from 'chai';
+import { expectRejected } from './utils/assert';
+import { PoolAccounts, setUpPool, tokenBalance } from './utils/pool';
+
+describe('stable swap', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+
+  const trader = Keypair.generate();
+  const amp = 100;
+
+  let platform: PublicKey;
+  let platformMultisig: PublicKey;
+  let pool: PublicKey;
+  let poolAccounts: PoolAccounts;
+  let userTokenA: PublicKey;
+  let userTokenB: PublicKey;
+
+  const now = () => Math.floor(Date.now() / 1000);
+
+  const balance = (account: PublicKey) => tokenBalance(provider, account);
+
+  const rampAmp = (targetAmp: number, rampEnd: number, signer?: Keypair) =>
+    program.methods
+      .rampPoolAmp(new BN(targetAmp), new BN(rampEnd))
+      .accounts({
+        pool,
+        platform,
+        multisig: platformMultisig,
+        signer: signer ? signer.publicKey : provider.wallet.publicKey,
+      })
+      .signers(signer ? [signer] : [])
+      .rpc();
+
+  before(async () => {
+    ({ platform, accounts: poolAccounts, userTokenA, userTokenB } = await setUpPool(program, provider, trader, {
+      feeBps: 4,
+      curve: { stableSwap: { amp: new BN(amp) } },
+    }));
+    pool = poolAccounts.pool;
+    platformMultisig = (await program.account.platformConfig.fetch(platform)).multisig;
+  });
+
+  it('trades a balanced pool close to one for one', async () => {
+    const amountIn = new BN(10_000);
+    const userBBefore = await balance(userTokenB);
+
+    await program.methods
+      .swap(amountIn, new BN(0), new BN(now() + 60))
+      .accounts({
+        ...poolAccounts,
+        userSourceAccount: userTokenA,
+        userDestinationAccount: userTokenB,
+        platform,
+        authority: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+        launchPurchases: null,
+        piVerification: null,
+      })
+      .signers([trader])
+      .rpc();
+
+    // Constant product would pay out about 9_897 here; the curve stays within
+    // a few units of the 9_996 left after the 0.04% fee
+    const received = (await balance(userTokenB)).sub(userBBefore).toNumber();
+    expect(received).to.be.within(9_990, 9_996);
+  });
+
+  it('rejects an amplification ramp without the platform multisig', async () => {
+    await expectRejected(rampAmp(200, now() + 2 * 24 * 60 * 60, trader), 'InsufficientApprovals');
+  });
+
+  it('rejects a ramp shorter than the minimum duration', async () => {
+    await expectRejected(rampAmp(200, now() + 60 * 60), 'InvalidAmpRamp');
+  });
+
+  it('rejects a ramp beyond the maximum change', async () => {
+    await expectRejected(rampAmp(amp * 11, now() + 2 * 24 * 60 * 60), 'InvalidAmpRamp');
+  });
+
+  it('lets the platform multisig ramp the amplification', async () => {
+    const rampEnd = now() + 2 * 24 * 60 * 60;
+    await rampAmp(200, rampEnd);
+
+    const state = await program.account.poolState.fetch(pool);
+    expect(state.ampInitial.toNumber()).to.equal(amp);
+    expect(state.ampTarget.toNumber()).to.equal(200);
+    expect(state.ampRampEnd.toNumber()).to.equal(rampEnd);
+
+    await expectRejected(rampAmp(300, rampEnd + 60), 'InvalidAmpRamp');
+  });
+
+  it('freezes the amplification when the ramp is stopped', async () => {
+    await program.methods
+      .stopPoolAmpRamp()
+      .accounts({ pool, platform, multisig: platformMultisig, signer: provider.wallet.publicKey })
+      .rpc();
+
+    // Only seconds into a two-day ramp, so barely above the starting value
+    const state = await program.account.poolState.fetch(pool);
+    expect(state.ampTarget.toNumber()).to.equal(state.ampInitial.toNumber());
+    expect(state.ampTarget.toNumber()).to.be.within(amp, amp + 1);
+    expect(state.ampRampEnd.toNumber()).to.equal(state.ampRampStart.toNumber());
+  });
+});
@@ -0,0 +1,176 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { Pidaosphere } from '../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram, Transaction, TransactionInstruction } from '@solana/web3.js';
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  createMint,
+  createAccount,
+  getAccount,
+  getMintLen,
+  mintTo,
+  createInitializeMintInstruction,
+  createInitializePermanentDelegateInstruction,
+  createInitializeTransferFeeConfigInstruction,
+} from '@solana/spl-token';
+import { expect } from 'chai';
+import { ensurePlatform } from './utils/platform';
+
+describe('token-2022 pools', () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.Pidaosphere as Program<Pidaosphere>;
+
+  const trader = Keypair.generate();
+  const transferFeeBps = 100;
+
+  let platform: PublicKey;
+
+  const pda = (seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const inAMinute = () => new BN(Math.floor(Date.now() / 1000) + 60);
+
+  const programOf = async (mint: PublicKey) => (await provider.connection.getAccountInfo(mint)).owner;
+
+  // A Token-2022 mint carrying the given extensions, with the trader as every authority
+  const createMint2022 = async (extensions: ExtensionType[], init: (mint: PublicKey) => TransactionInstruction[]) => {
+    const mint = Keypair.generate();
+    const space = getMintLen(extensions);
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: trader.publicKey,
+        newAccountPubkey: mint.publicKey,
+        space,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      ...init(mint.publicKey),
+      createInitializeMintInstruction(mint.publicKey, 6, trader.publicKey, null, TOKEN_2022_PROGRAM_ID)
+    );
+    await provider.sendAndConfirm(tx, [trader, mint]);
+    return mint.publicKey;
+  };
+
+  const poolAccountsFor = async (mintX: PublicKey, mintY: PublicKey) => {
+    const [tokenA, tokenB] = [mintX, mintY].sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()));
+    const pool = pda([Buffer.from('pool'), tokenA.toBuffer(), tokenB.toBuffer()]);
+    return {
+      pool,
+      tokenA,
+      tokenB,
+      poolAuthority: pda([Buffer.from('pool_authority'), pool.toBuffer()]),
+      tokenAReserve: pda([Buffer.from('reserve_a'), pool.toBuffer()]),
+      tokenBReserve: pda([Buffer.from('reserve_b'), pool.toBuffer()]),
+      tokenAProgram: await programOf(tokenA),
+      tokenBProgram: await programOf(tokenB),
+      observations: pda([Buffer.from('observations'), pool.toBuffer()]),
+      lpTokenMint: pda([Buffer.from('lp_mint'), pool.toBuffer()]),
+    };
+  };
+
+  const initializePool = (accounts: Record<string, PublicKey>) =>
+    program.methods
+      .initializePool(new BN(30), { constantProduct: {} })
+      .accounts({
+        ...accounts,
+        authority: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+
+  before(async () => {
+    const sig = await provider.connection.requestAirdrop(
+      trader.publicKey,
+      10 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(sig);
+
+    platform = await ensurePlatform(program, provider);
+  });
+
+  it('pairs a transfer-fee Token-2022 mint with a legacy mint', async () => {
+    const feeMint = await createMint2022([ExtensionType.TransferFeeConfig], (mint) => [
+      createInitializeTransferFeeConfigInstruction(
+        mint,
+        trader.publicKey,
+        trader.publicKey,
+        transferFeeBps,
+        BigInt(1_000_000),
+        TOKEN_2022_PROGRAM_ID
+      ),
+    ]);
+    const legacyMint = await createMint(provider.connection, trader, trader.publicKey, null, 6);
+    const accounts = await poolAccountsFor(feeMint, legacyMint);
+    await initializePool(accounts);
+
+    const userFee = await createAccount(
+      provider.connection, trader, feeMint, trader.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID
+    );
+    const userLegacy = await createAccount(provider.connection, trader, legacyMint, trader.publicKey);
+    await mintTo(
+      provider.connection, trader, feeMint, userFee, trader, 10_000_000, [], undefined, TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(provider.connection, trader, legacyMint, userLegacy, trader, 10_000_000);
+    const userAccount = (mint: PublicKey) => (mint.equals(feeMint) ? userFee : userLegacy);
+    const userLp = await createAccount(provider.connection, trader, accounts.lpTokenMint, trader.publicKey);
+
+    await program.methods
+      .addLiquidity(new BN(1_000_000), new BN(1_000_000), new BN(0), inAMinute())
+      .accounts({
+        ...accounts,
+        userTokenAAccount: userAccount(accounts.tokenA),
+        userTokenBAccount: userAccount(accounts.tokenB),
+        userLpTokenAccount: userLp,
+        authority: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([trader])
+      .rpc();
+
+    await program.methods
+      .swap(new BN(100_000), new BN(0), inAMinute())
+      .accounts({
+        ...accounts,
+        userSourceAccount: userFee,
+        userDestinationAccount: userLegacy,
+        platform,
+        authority: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+        launchPurchases: null,
+        piVerification: null,
+      })
+      .signers([trader])
+      .rpc();
+
+    // The pool only counts what reached its reserve, net of the transfer fee
+    const state = await program.account.poolState.fetch(accounts.pool);
+    const feeIsA = accounts.tokenA.equals(feeMint);
+    const feeReserve = feeIsA ? accounts.tokenAReserve : accounts.tokenBReserve;
+    const tracked = (feeIsA ? state.tokenAAmount : state.tokenBAmount).add(
+      feeIsA ? state.protocolFeesA : state.protocolFeesB
+    );
+    const reserve = await getAccount(provider.connection, feeReserve, undefined, TOKEN_2022_PROGRAM_ID);
+    expect(tracked.toString()).to.equal(reserve.amount.toString());
+    expect(tracked.toNumber()).to.equal(1_000_000 - 10_000 + 100_000 - 1_000);
+  });
+
+  it('rejects a mint whose permanent delegate could drain the reserves', async () => {
+    const delegateMint = await createMint2022([ExtensionType.PermanentDelegate], (mint) => [
+      createInitializePermanentDelegateInstruction(mint, trader.publicKey, TOKEN_2022_PROGRAM_ID),
+    ]);
+    const legacyMint = await createMint(provider.connection, trader, trader.publicKey, null, 6);
+
+    try {
+      await initializePool(await poolAccountsFor(delegateMint, legacyMint));
+      expect.fail('Expected transaction to fail');
+    } catch (error) {
+      expect(error.message).to.include('UnsupportedMintExtension');
+    }
+  });
+});
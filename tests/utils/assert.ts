@@ -0,0 +1,11 @@
+import { expect } from 'chai';
+
+// Fail unless the transaction is rejected with an error mentioning `message`
+export const expectRejected = async (tx: Promise<unknown>, message: string) => {
+  try {
+    await tx;
+    expect.fail('Expected transaction to fail');
+  } catch (error) {
+    expect(error.message).to.include(message);
+  }
+};
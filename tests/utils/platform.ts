@@ -0,0 +1,76 @@
+import * as anchor from '@project-serum/anchor';
+import { Program } from '@project-serum/anchor';
+import { Pidaosphere } from '../../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+
+const MAX_QUOTE_MINTS = 8;
+
+export const findPlatform = (program: Program<Pidaosphere>) =>
+  PublicKey.findProgramAddressSync([Buffer.from('platform_config')], program.programId)[0];
+
+export const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey('BPFLoaderUpgradeab1e11111111111111111111111');
+
+// Holds the program's upgrade authority, which alone may initialize the platform
+export const findProgramData = (program: Program<Pidaosphere>) =>
+  PublicKey.findProgramAddressSync([program.programId.toBuffer()], BPF_LOADER_UPGRADEABLE_PROGRAM_ID)[0];
+
+export const findProjectEntry = (program: Program<Pidaosphere>, index: anchor.BN) =>
+  PublicKey.findProgramAddressSync(
+    [Buffer.from('project_entry'), index.toArrayLike(Buffer, 'le', 8)],
+    program.programId
+  )[0];
+
+// The platform is a singleton shared by every suite. The provider wallet
+// deployed the program and is the only owner of its multisig, so any suite can
+// adjust it; fees start at zero.
+export const ensurePlatform = async (
+  program: Program<Pidaosphere>,
+  provider: anchor.AnchorProvider
+): Promise<PublicKey> => {
+  const platform = findPlatform(program);
+  if (await provider.connection.getAccountInfo(platform)) {
+    return platform;
+  }
+
+  const multisig = Keypair.generate();
+  await program.methods
+    .initializeMultisig([provider.wallet.publicKey], new anchor.BN(1))
+    .accounts({ multisig: multisig.publicKey, payer: provider.wallet.publicKey, systemProgram: SystemProgram.programId })
+    .signers([multisig])
+    .rpc();
+  await program.methods
+    .initializePlatform(provider.wallet.publicKey, 0, 0, [])
+    .accounts({
+      platform,
+      multisig: multisig.publicKey,
+      programData: findProgramData(program),
+      payer: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+
+  return platform;
+};
+
+// Allow a suite's quote mint, dropping the oldest entry once the list is full
+export const allowQuoteMint = async (
+  program: Program<Pidaosphere>,
+  provider: anchor.AnchorProvider,
+  quoteMint: PublicKey
+): Promise<void> => {
+  const platform = await ensurePlatform(program, provider);
+  const state = await program.account.platformConfig.fetch(platform);
+  const allowed = [...state.allowedQuoteMints, quoteMint].slice(-MAX_QUOTE_MINTS);
+
+  await program.methods
+    .setAllowedQuoteMints(allowed)
+    .accounts({ platform, multisig: state.multisig, signer: provider.wallet.publicKey })
+    .rpc();
+};
+
+// Accounts initialize_project needs to register the next launch
+export const registryAccounts = async (program: Program<Pidaosphere>) => {
+  const platform = findPlatform(program);
+  const { projectCount } = await program.account.platformConfig.fetch(platform);
+  return { platform, projectEntry: findProjectEntry(program, projectCount) };
+};
@@ -0,0 +1,144 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { Pidaosphere } from '../../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID, createMint, createAccount, getAccount, mintTo } from '@solana/spl-token';
+import { ensurePlatform } from './platform';
+
+// Pool suites run on the shared validator, so deadlines follow the wall clock
+export const inAMinute = () => new BN(Math.floor(Date.now() / 1000) + 60);
+
+export const tokenBalance = async (provider: anchor.AnchorProvider, account: PublicKey) =>
+  new BN((await getAccount(provider.connection, account)).amount.toString());
+
+export const airdrop = async (provider: anchor.AnchorProvider, ...wallets: Keypair[]) => {
+  for (const wallet of wallets) {
+    const sig = await provider.connection.requestAirdrop(wallet.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(sig);
+  }
+};
+
+// Pools order their pair by mint address
+export const sortMints = (x: PublicKey, y: PublicKey): [PublicKey, PublicKey] =>
+  x.toBuffer().compare(y.toBuffer()) <= 0 ? [x, y] : [y, x];
+
+export interface PoolAccounts {
+  pool: PublicKey;
+  tokenA: PublicKey;
+  tokenB: PublicKey;
+  poolAuthority: PublicKey;
+  tokenAReserve: PublicKey;
+  tokenBReserve: PublicKey;
+  tokenAProgram: PublicKey;
+  tokenBProgram: PublicKey;
+  observations: PublicKey;
+  lpTokenMint: PublicKey;
+}
+
+// The pool for a pair and every account derived from it
+export const findPoolAccounts = (
+  program: Program<Pidaosphere>,
+  mintX: PublicKey,
+  mintY: PublicKey,
+  tokenPrograms: Map<string, PublicKey> = new Map()
+): PoolAccounts => {
+  const pda = (seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const [tokenA, tokenB] = sortMints(mintX, mintY);
+  const pool = pda([Buffer.from('pool'), tokenA.toBuffer(), tokenB.toBuffer()]);
+  return {
+    pool,
+    tokenA,
+    tokenB,
+    poolAuthority: pda([Buffer.from('pool_authority'), pool.toBuffer()]),
+    tokenAReserve: pda([Buffer.from('reserve_a'), pool.toBuffer()]),
+    tokenBReserve: pda([Buffer.from('reserve_b'), pool.toBuffer()]),
+    tokenAProgram: tokenPrograms.get(tokenA.toBase58()) ?? TOKEN_PROGRAM_ID,
+    tokenBProgram: tokenPrograms.get(tokenB.toBase58()) ?? TOKEN_PROGRAM_ID,
+    observations: pda([Buffer.from('observations'), pool.toBuffer()]),
+    lpTokenMint: pda([Buffer.from('lp_mint'), pool.toBuffer()]),
+  };
+};
+
+// Mints owned by the trader, sorted like a pool pair, with a funded account for each
+export const createTraderMints = async (provider: anchor.AnchorProvider, trader: Keypair, amount: number) => {
+  const [tokenA, tokenB] = sortMints(
+    await createMint(provider.connection, trader, trader.publicKey, null, 6),
+    await createMint(provider.connection, trader, trader.publicKey, null, 6)
+  );
+  const userTokenA = await createAccount(provider.connection, trader, tokenA, trader.publicKey);
+  const userTokenB = await createAccount(provider.connection, trader, tokenB, trader.publicKey);
+  await mintTo(provider.connection, trader, tokenA, userTokenA, trader, amount);
+  await mintTo(provider.connection, trader, tokenB, userTokenB, trader, amount);
+  return { tokenA, tokenB, userTokenA, userTokenB };
+};
+
+export type PoolCurve = Parameters<Program<Pidaosphere>['methods']['initializePool']>[1];
+
+export const initializePool = (
+  program: Program<Pidaosphere>,
+  creator: Keypair,
+  accounts: PoolAccounts,
+  feeBps: number,
+  curve: PoolCurve = { constantProduct: {} }
+) =>
+  program.methods
+    .initializePool(new BN(feeBps), curve)
+    .accounts({
+      ...accounts,
+      authority: creator.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([creator])
+    .rpc();
+
+export interface PoolFixture {
+  platform: PublicKey;
+  accounts: PoolAccounts;
+  userTokenA: PublicKey;
+  userTokenB: PublicKey;
+  userLpToken: PublicKey;
+}
+
+export interface PoolOptions {
+  feeBps?: number;
+  curve?: PoolCurve;
+  mintAmount?: number;
+  deposit?: [number, number];
+}
+
+// A pool between two fresh mints, created and seeded by the trader
+export const setUpPool = async (
+  program: Program<Pidaosphere>,
+  provider: anchor.AnchorProvider,
+  trader: Keypair,
+  options: PoolOptions = {}
+): Promise<PoolFixture> => {
+  const [amountA, amountB] = options.deposit ?? [1_000_000, 1_000_000];
+
+  await airdrop(provider, trader);
+  const platform = await ensurePlatform(program, provider);
+  const { tokenA, tokenB, userTokenA, userTokenB } = await createTraderMints(
+    provider,
+    trader,
+    options.mintAmount ?? 10_000_000
+  );
+  const accounts = findPoolAccounts(program, tokenA, tokenB);
+  await initializePool(program, trader, accounts, options.feeBps ?? 30, options.curve);
+
+  const userLpToken = await createAccount(provider.connection, trader, accounts.lpTokenMint, trader.publicKey);
+  await program.methods
+    .addLiquidity(new BN(amountA), new BN(amountB), new BN(0), inAMinute())
+    .accounts({
+      ...accounts,
+      userTokenAAccount: userTokenA,
+      userTokenBAccount: userTokenB,
+      userLpTokenAccount: userLpToken,
+      authority: trader.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([trader])
+    .rpc();
+
+  return { platform, accounts, userTokenA, userTokenB, userLpToken };
+};
@@ -0,0 +1,358 @@
+import * as anchor from '@project-serum/anchor';
+import { Program, BN } from '@project-serum/anchor';
+import { IDL, Pidaosphere } from '../../target/types/pidaosphere';
+import { PublicKey, Keypair, SystemProgram, Transaction, TransactionInstruction } from '@solana/web3.js';
+import {
+  TOKEN_PROGRAM_ID,
+  ACCOUNT_SIZE,
+  MINT_SIZE,
+  AccountLayout,
+  createInitializeAccount3Instruction,
+  createInitializeMint2Instruction,
+  createMintToInstruction,
+} from '@solana/spl-token';
+import { startAnchor, Clock, ProgramTestContext } from 'solana-bankrun';
+import { BankrunProvider } from 'anchor-bankrun';
+import { BPF_LOADER_UPGRADEABLE_PROGRAM_ID, allowQuoteMint, findProgramData, registryAccounts } from './platform';
+
+export const TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
+
+const DAY = 24 * 60 * 60;
+
+// Suites that only need a project run on the shared validator. Those that must
+// move the clock past the fundraise and vesting windows run against an
+// in-process bank instead, which loads token metadata from
+// tests/fixtures/mpl_token_metadata.so, dumped by `yarn test:fixtures`.
+export interface ProjectEnv {
+  program: Program<Pidaosphere>;
+  provider: anchor.AnchorProvider;
+  context?: ProgramTestContext;
+}
+
+export const startProjectEnv = async (): Promise<ProjectEnv> => {
+  const context = await startAnchor(
+    '',
+    [{ name: 'mpl_token_metadata', programId: TOKEN_METADATA_PROGRAM_ID }],
+    []
+  );
+  const provider = new BankrunProvider(context) as unknown as anchor.AnchorProvider;
+  const programId = (anchor.workspace.Pidaosphere as Program<Pidaosphere>).programId;
+  const program = new Program<Pidaosphere>(IDL, programId, provider);
+
+  // The bank loads the program without an upgrade authority, so stand in the
+  // ProgramData state naming the payer, as a deploy from that wallet would
+  const programData = Buffer.alloc(45);
+  programData.writeUInt32LE(3, 0); // UpgradeableLoaderState::ProgramData
+  programData.writeUInt8(1, 12); // Some(upgrade authority)
+  context.payer.publicKey.toBuffer().copy(programData, 13);
+  context.setAccount(findProgramData(program), {
+    lamports: 1_000_000_000,
+    data: programData,
+    owner: BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+    executable: false,
+  });
+
+  return { context, provider, program };
+};
+
+const bank = (env: ProjectEnv): ProgramTestContext => {
+  if (!env.context) {
+    throw new Error('Only bank-backed suites can move the clock');
+  }
+  return env.context;
+};
+
+export const now = async (env: ProjectEnv) =>
+  Number((await bank(env).banksClient.getClock()).unixTimestamp);
+
+export const warpTo = async (env: ProjectEnv, unixTimestamp: number) => {
+  const clock = await bank(env).banksClient.getClock();
+  bank(env).setClock(
+    new Clock(
+      clock.slot,
+      clock.epochStartTimestamp,
+      clock.epoch,
+      clock.leaderScheduleEpoch,
+      BigInt(unixTimestamp)
+    )
+  );
+};
+
+export const fund = async (env: ProjectEnv, ...wallets: Keypair[]) => {
+  const lamports = 10 * anchor.web3.LAMPORTS_PER_SOL;
+  for (const wallet of wallets) {
+    if (env.context) {
+      env.context.setAccount(wallet.publicKey, {
+        lamports,
+        data: Buffer.alloc(0),
+        owner: SystemProgram.programId,
+        executable: false,
+      });
+    } else {
+      const sig = await env.provider.connection.requestAirdrop(wallet.publicKey, lamports);
+      await env.provider.connection.confirmTransaction(sig);
+    }
+  }
+};
+
+const send = (env: ProjectEnv, instructions: TransactionInstruction[], signers: Keypair[] = []) =>
+  env.provider.sendAndConfirm(new Transaction().add(...instructions), signers);
+
+const rentFor = (env: ProjectEnv, space: number) =>
+  env.provider.connection.getMinimumBalanceForRentExemption(space);
+
+// Mints created here use the provider wallet as their authority
+export const createMint = async (env: ProjectEnv, decimals = 9): Promise<PublicKey> => {
+  const mint = Keypair.generate();
+  const payer = env.provider.wallet.publicKey;
+  await send(
+    env,
+    [
+      SystemProgram.createAccount({
+        fromPubkey: payer,
+        newAccountPubkey: mint.publicKey,
+        space: MINT_SIZE,
+        lamports: await rentFor(env, MINT_SIZE),
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      createInitializeMint2Instruction(mint.publicKey, decimals, payer, null),
+    ],
+    [mint]
+  );
+  return mint.publicKey;
+};
+
+export const createTokenAccount = async (env: ProjectEnv, mint: PublicKey, owner: PublicKey): Promise<PublicKey> => {
+  const account = Keypair.generate();
+  await send(
+    env,
+    [
+      SystemProgram.createAccount({
+        fromPubkey: env.provider.wallet.publicKey,
+        newAccountPubkey: account.publicKey,
+        space: ACCOUNT_SIZE,
+        lamports: await rentFor(env, ACCOUNT_SIZE),
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      createInitializeAccount3Instruction(account.publicKey, mint, owner),
+    ],
+    [account]
+  );
+  return account.publicKey;
+};
+
+export const mintTokens = (env: ProjectEnv, mint: PublicKey, destination: PublicKey, amount: number | BN) =>
+  send(env, [createMintToInstruction(mint, destination, env.provider.wallet.publicKey, BigInt(amount.toString()))]);
+
+export const balance = async (env: ProjectEnv, account: PublicKey): Promise<BN> => {
+  const info = await env.provider.connection.getAccountInfo(account);
+  return new BN(AccountLayout.decode(info.data).amount.toString());
+};
+
+export interface ProjectOptions {
+  slug?: string;
+  totalSupply?: number;
+  saleSupply?: number;
+  minRaise?: number;
+  maxRaise?: number;
+  maxInvestment?: number;
+  duration?: number;
+  guardian?: PublicKey | null;
+  multisig?: PublicKey | null;
+  lockup?: { tgeUnlockBps: number; unlockDuration: BN } | null;
+  vesting?: { beneficiary: PublicKey; amount: BN; cliff: BN; duration: BN; revocable: boolean }[];
+}
+
+export interface ProjectAccounts {
+  authority: Keypair;
+  project: PublicKey;
+  projectSigner: PublicKey;
+  projectTokenMint: PublicKey;
+  quoteMint: PublicKey;
+  treasuryVault: PublicKey;
+  vesting: PublicKey;
+  vestingVault: PublicKey;
+  investorVault: PublicKey;
+  projectEntry: PublicKey;
+}
+
+// Launch a project priced at one token per quote unit, raising in a fresh quote mint
+export const createProject = async (env: ProjectEnv, options: ProjectOptions = {}): Promise<ProjectAccounts> => {
+  const { program, provider } = env;
+  const pda = (seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const authority = Keypair.generate();
+  await fund(env, authority);
+  const quoteMint = await createMint(env);
+  await allowQuoteMint(program, provider, quoteMint);
+
+  // Slugs are seeds, so each launch on the shared validator needs its own
+  const slug = options.slug ?? `project-${authority.publicKey.toBase58().slice(0, 8)}`;
+  const project = pda([Buffer.from('project'), Buffer.from(slug)]);
+  const projectTokenMint = pda([Buffer.from('project_mint'), project.toBuffer()]);
+  const registry = await registryAccounts(program);
+  const accounts: ProjectAccounts = {
+    authority,
+    project,
+    projectSigner: pda([Buffer.from('project_signer'), project.toBuffer()]),
+    projectTokenMint,
+    quoteMint,
+    treasuryVault: pda([Buffer.from('treasury'), project.toBuffer()]),
+    vesting: pda([Buffer.from('vesting'), project.toBuffer()]),
+    vestingVault: pda([Buffer.from('vesting_vault'), project.toBuffer()]),
+    investorVault: pda([Buffer.from('investor_vault'), project.toBuffer()]),
+    projectEntry: registry.projectEntry,
+  };
+  const [tokenMetadata] = PublicKey.findProgramAddressSync(
+    [Buffer.from('metadata'), TOKEN_METADATA_PROGRAM_ID.toBuffer(), projectTokenMint.toBuffer()],
+    TOKEN_METADATA_PROGRAM_ID
+  );
+
+  const totalSupply = options.totalSupply ?? 1_000_000;
+  await program.methods
+    .initializeProject(
+      {
+        slug,
+        totalSupply: new BN(totalSupply),
+        saleSupply: new BN(options.saleSupply ?? totalSupply),
+        minRaise: new BN(options.minRaise ?? 1),
+        maxRaise: new BN(options.maxRaise ?? totalSupply),
+        minInvestment: new BN(1),
+        maxInvestment: new BN(options.maxInvestment ?? totalSupply),
+        duration: new BN(options.duration ?? 30 * DAY),
+        guardian: options.guardian ?? null,
+        multisig: options.multisig ?? null,
+        lockup: options.lockup ?? null,
+      },
+      { name: 'Project', symbol: 'PRJ', uri: '', decimals: 6, revokeFreezeAuthority: false },
+      new BN(1),
+      null,
+      options.vesting ?? []
+    )
+    .accounts({
+      ...accounts,
+      ...registry,
+      authority: authority.publicKey,
+      tokenMetadata,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      quoteTokenProgram: TOKEN_PROGRAM_ID,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    })
+    .signers([authority])
+    .rpc();
+
+  return accounts;
+};
+
+// Buy `amount` of a project's tokens, returning the investor's token account
+export const invest = async (
+  env: ProjectEnv,
+  accounts: ProjectAccounts,
+  investor: Keypair,
+  amount: number
+): Promise<PublicKey> => {
+  const { program } = env;
+  const userTokenAccount = await createTokenAccount(env, accounts.projectTokenMint, investor.publicKey);
+  const { lockup } = await program.account.projectState.fetch(accounts.project);
+  const investorLock = lockup
+    ? PublicKey.findProgramAddressSync(
+        [Buffer.from('investor_lock'), accounts.project.toBuffer(), investor.publicKey.toBuffer()],
+        program.programId
+      )[0]
+    : null;
+
+  await program.methods
+    .invest(new BN(amount), null)
+    .accounts({
+      project: accounts.project,
+      investor: investor.publicKey,
+      userTokenAccount,
+      projectTokenMint: accounts.projectTokenMint,
+      investorVault: accounts.investorVault,
+      projectSigner: accounts.projectSigner,
+      investorLock,
+      platform: PublicKey.findProgramAddressSync([Buffer.from('platform_config')], program.programId)[0],
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([investor])
+    .rpc();
+
+  return userTokenAccount;
+};
+
+// Close the raise once its window has passed. Pi payments settle off-chain,
+// so the raise reaches the treasury as a plain quote deposit first.
+export const finalizeProject = async (env: ProjectEnv, accounts: ProjectAccounts) => {
+  const { program } = env;
+  const state = await program.account.projectState.fetch(accounts.project);
+  if (state.totalInvestment.gtn(0)) {
+    await mintTokens(env, accounts.quoteMint, accounts.treasuryVault, state.totalInvestment);
+  }
+  await warpTo(env, state.fundraiseEndTime.toNumber() + 1);
+  await program.methods
+    .finalizeFundraise()
+    .accounts({
+      project: accounts.project,
+      authority: accounts.authority.publicKey,
+      platform: PublicKey.findProgramAddressSync([Buffer.from('platform_config')], program.programId)[0],
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([accounts.authority])
+    .rpc();
+};
+
+// The pool accounts expiry takes when the project holds LP in its own pool
+export const noProjectLiquidity = {
+  pool: null,
+  observations: null,
+  poolAuthority: null,
+  tokenA: null,
+  tokenB: null,
+  tokenAReserve: null,
+  tokenBReserve: null,
+  lpTokenMint: null,
+  projectLpAccount: null,
+  tokenAProgram: null,
+  tokenBProgram: null,
+  lpTokenProgram: null,
+};
+
+export const expireProject = async (
+  env: ProjectEnv,
+  accounts: ProjectAccounts,
+  liquidity: Record<keyof typeof noProjectLiquidity, PublicKey | null> = noProjectLiquidity
+) => {
+  const { program } = env;
+  const state = await program.account.projectState.fetch(accounts.project);
+  await warpTo(env, state.expirationTime.toNumber() + 1);
+  await program.methods
+    .expireProject()
+    .accounts({
+      project: accounts.project,
+      treasuryVault: accounts.treasuryVault,
+      projectSigner: accounts.projectSigner,
+      ...liquidity,
+    })
+    .rpc();
+};
+
+export const closeProject = (env: ProjectEnv, accounts: ProjectAccounts) =>
+  env.program.methods
+    .closeProject()
+    .accounts({
+      project: accounts.project,
+      authority: accounts.authority.publicKey,
+      projectTokenMint: accounts.projectTokenMint,
+      treasuryVault: accounts.treasuryVault,
+      vesting: accounts.vesting,
+      vestingVault: accounts.vestingVault,
+      investorVault: accounts.investorVault,
+      projectEntry: accounts.projectEntry,
+      projectSigner: accounts.projectSigner,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      quoteTokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();